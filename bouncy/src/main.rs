@@ -1,6 +1,5 @@
 use bevy::{
     diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
-    ecs::entity,
     platform::collections::{HashMap, HashSet},
     prelude::*,
 };
@@ -220,7 +219,7 @@ fn main() -> anyhow::Result<()> {
         GamePhase::Bouncing,
         GamePhase::GameOver,
     ))
-    .add_plugins(RandomPlugin)
+    .add_plugins(RandomPlugin::new())
     .add_plugins(AssetManager::new().add_image("green_ball", "green_ball.png")?)
     .run();
 
@@ -229,21 +228,32 @@ fn main() -> anyhow::Result<()> {
 
 fn spawn_bouncies(
     to_spawn: usize,
+    existing: impl Iterator<Item = Vec2>,
     commands: &mut Commands,
     rng: &mut ResMut<RandomNumberGenerator>,
     assets: &AssetStore,
     loaded_assets: &LoadedAssets,
 ) {
-    for _ in 0..to_spawn {
-        let position = Vec3::new(rng.range(-512.0..512.0), rng.range(-384.0..384.0), 0.0);
-        let velocity = Vec3::new(rng.range(-1.0..1.0), rng.range(-1.0..1.0), 0.0);
+    // Spread the new balls out, so they don't start out overlapping each
+    // other (or existing balls). Once the screen is full, any remaining
+    // balls are placed at random.
+    let screen = my_library::Rect2D::new(Vec2::new(-512.0, -384.0), Vec2::new(512.0, 384.0));
+    let sampler = PoissonDisk::new(screen, 12.0)
+        .with_points(existing)
+        .with_limit(to_spawn);
+    let mut positions = rng.poisson_disk(&sampler);
+    while positions.len() < to_spawn {
+        positions.push(rng.point_in_rect(&screen));
+    }
+    for position in positions {
+        let velocity = rng.point_in_circle(Vec2::ZERO, 1.0).extend(0.0);
         spawn_image!(
             assets,
             commands,
             "green_ball",
             position.x,
             position.y,
-            position.z,
+            0.0,
             &loaded_assets,
             BouncyElement,
             Velocity::new(velocity.x, velocity.y, velocity.z),
@@ -259,13 +269,20 @@ fn setup(
     assets: Res<AssetStore>,
     loaded_assets: Res<LoadedAssets>,
 ) {
-    commands.spawn(Camera2d::default()).insert(BouncyElement);
+    commands.spawn(Camera2d).insert(BouncyElement);
     commands.insert_resource(CollisionTime::default());
     commands.insert_resource(StaticQuadTree::new(
         Vec2::new(1024.0, 768.0),
         QUAD_TREE_DEPTH,
     ));
-    spawn_bouncies(1, &mut commands, &mut rng, &assets, &loaded_assets);
+    spawn_bouncies(
+        1,
+        std::iter::empty(),
+        &mut commands,
+        &mut rng,
+        &assets,
+        &loaded_assets,
+    );
 }
 
 fn warp_at_edge(mut query: Query<&mut Transform, With<Ball>>) {
@@ -286,6 +303,7 @@ fn warp_at_edge(mut query: Query<&mut Transform, With<Ball>>) {
 }
 
 //START: show_performance
+#[allow(clippy::too_many_arguments)]
 fn show_performance(
     mut egui_context: egui::EguiContexts,
     diagnostics: Res<DiagnosticsStore>, //<callout id="bouncy.diagnostics" />
@@ -297,6 +315,11 @@ fn show_performance(
     loaded_assets: Res<LoadedAssets>,
 ) {
     let n_balls = query.iter().count(); //<callout id="bouncy.count_balls" />
+    let positions = || {
+        query
+            .iter()
+            .map(|transform| transform.translation.truncate())
+    };
     let fps = diagnostics //<callout id="bouncy.get_fps" />
         .get(&FrameTimeDiagnosticsPlugin::FPS)
         .and_then(|fps| fps.average())
@@ -311,33 +334,51 @@ fn show_performance(
             _ => Color32::GREEN,
         };
         ui.colored_label(color, &fps_text);
-        ui.colored_label(
-            color,
-            &format!("Collision Time: {} ms", collision_time.time),
-        );
-        ui.label(&format!("Collision Checks: {}", collision_time.checks));
-        ui.label(&format!("# Balls: {n_balls}"));
+        ui.colored_label(color, format!("Collision Time: {} ms", collision_time.time));
+        ui.label(format!("Collision Checks: {}", collision_time.checks));
+        ui.label(format!("# Balls: {n_balls}"));
         if ui.button("Add Ball").clicked() {
             //<callout id="bouncy.add_balls" />
             println!(
                 "{n_balls}, {}, {}, {:.0}",
                 collision_time.time, collision_time.checks, collision_time.fps
             );
-            spawn_bouncies(1, &mut commands, &mut rng, &assets, &loaded_assets);
+            spawn_bouncies(
+                1,
+                positions(),
+                &mut commands,
+                &mut rng,
+                &assets,
+                &loaded_assets,
+            );
         }
         if ui.button("Add 100 Balls").clicked() {
             println!(
                 "{n_balls}, {}, {}, {:.0}",
                 collision_time.time, collision_time.checks, collision_time.fps
             );
-            spawn_bouncies(100, &mut commands, &mut rng, &assets, &loaded_assets);
+            spawn_bouncies(
+                100,
+                positions(),
+                &mut commands,
+                &mut rng,
+                &assets,
+                &loaded_assets,
+            );
         }
         if ui.button("Add 1000 Balls").clicked() {
             println!(
                 "{n_balls}, {}, {}, {:.0}",
                 collision_time.time, collision_time.checks, collision_time.fps
             );
-            spawn_bouncies(1000, &mut commands, &mut rng, &assets, &loaded_assets);
+            spawn_bouncies(
+                1000,
+                positions(),
+                &mut commands,
+                &mut rng,
+                &assets,
+                &loaded_assets,
+            );
         }
     });
 }
@@ -389,20 +430,19 @@ fn collisions(
 
     let mut n = 0;
     for (entity, node, box_a) in tree_positions {
-        if let Some(entities_here) = spatial_index.get(&node) {
-            if let Some((entity_b, _)) = entities_here
+        if let Some(entities_here) = spatial_index.get(&node)
+            && let Some((entity_b, _)) = entities_here
                 .iter()
                 .filter(|(entity_b, _)| *entity_b != entity)
                 .find(|(_, box_b)| {
                     n += 1;
                     box_a.intersect(box_b)
                 })
-            {
-                // A Collision occurred
-                let (_, ball_a, _) = query.get(entity).unwrap();
-                let (_, ball_b, _) = query.get(*entity_b).unwrap();
-                bounce_on_collision(entity, ball_a.translation, ball_b.translation, &mut impulse);
-            }
+        {
+            // A Collision occurred
+            let (_, ball_a, _) = query.get(entity).unwrap();
+            let (_, ball_b, _) = query.get(*entity_b).unwrap();
+            bounce_on_collision(entity, ball_a.translation, ball_b.translation, &mut impulse);
         }
     }
 
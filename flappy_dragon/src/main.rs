@@ -10,11 +10,17 @@ struct Obstacle;
 #[derive(Component)]
 struct FlappyElement;
 
+/// Wall gap positions, drawn from a shuffle bag so the same gap doesn't
+/// come up again and again.
+#[derive(Resource)]
+struct WallGaps(ShuffleBag<i32>);
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default, States)]
 enum GamePhase {
     #[default]
     Loading,
     MainMenu,
+    Options,
     Flapping,
     GameOver,
 }
@@ -40,12 +46,18 @@ fn main() -> anyhow::Result<()> {
         }),
         ..default()
     }))
-    .add_plugins(RandomPlugin)
-    .add_plugins(GameStatePlugin::new(
-        GamePhase::MainMenu,
-        GamePhase::Flapping,
-        GamePhase::GameOver,
-    ))
+    .add_plugins(RandomPlugin::new())
+    .insert_resource(WallGaps(ShuffleBag::new(-5..5).with_no_repeats()))
+    .add_plugins(SettingsPlugin::new("flappy_dragon").with_key_binding("Flap", KeyCode::Space))
+    .add_plugins(HighScorePlugin::new("flappy_dragon"))
+    .add_plugins(
+        GameStatePlugin::new(
+            GamePhase::MainMenu,
+            GamePhase::Flapping,
+            GamePhase::GameOver,
+        )
+        .with_settings_menu(GamePhase::Options),
+    )
     .add_plugins(
         AssetManager::new()
             .add_image("dragon", "flappy_dragon.png")?
@@ -93,7 +105,8 @@ fn main() -> anyhow::Result<()> {
 
 fn setup(
     mut commands: Commands,
-    mut rng: ResMut<RandomNumberGenerator>,
+    mut rng: SystemRng,
+    mut gaps: ResMut<WallGaps>,
     assets: Res<AssetStore>,
     loaded_assets: Res<LoadedAssets>,
 ) {
@@ -119,7 +132,8 @@ fn setup(
         },
         4,
     ));
-    build_wall(&mut commands, &assets, rng.range(-5..5), &loaded_assets);
+    let gap_y = *rng.get().draw(&mut gaps.0).unwrap();
+    build_wall(&mut commands, &assets, gap_y, &loaded_assets);
 
     spawn_image!(
         assets,
@@ -226,18 +240,19 @@ fn build_wall(
 
 fn flap(
     keyboard: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
     mut query: Query<(Entity, &mut AnimationCycle)>,
     mut impulse: EventWriter<Impulse>,
 ) {
-    if keyboard.pressed(KeyCode::Space) {
-        if let Ok((flappy, mut animation)) = query.single_mut() {
-            impulse.write(Impulse {
-                target: flappy,
-                amount: Vec3::Y,
-                absolute: false,
-            });
-            animation.switch("Flapping");
-        }
+    if keyboard.pressed(settings.key("Flap"))
+        && let Ok((flappy, mut animation)) = query.single_mut()
+    {
+        impulse.write(Impulse {
+            target: flappy,
+            amount: Vec3::Y,
+            absolute: false,
+        });
+        animation.switch("Flapping");
     }
 }
 
@@ -251,13 +266,16 @@ fn clamp(mut query: Query<&mut Transform, With<Flappy>>, mut state: ResMut<NextS
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn move_walls(
     mut commands: Commands,
     query: Query<&Transform, With<Obstacle>>,
     delete: Query<Entity, With<Obstacle>>,
     assets: Res<AssetStore>,
-    mut rng: ResMut<RandomNumberGenerator>,
+    mut rng: SystemRng,
+    mut gaps: ResMut<WallGaps>,
     loaded_assets: Res<LoadedAssets>,
+    mut score: ResMut<Score>,
 ) {
     let mut rebuild = false;
     for transform in query.iter() {
@@ -268,10 +286,13 @@ fn move_walls(
     }
 
     if rebuild {
+        // The dragon made it through this wall.
+        score.add(1);
         for entity in delete.iter() {
             commands.entity(entity).despawn();
         }
-        build_wall(&mut commands, &assets, rng.range(-5..5), &loaded_assets);
+        let gap_y = *rng.get().draw(&mut gaps.0).unwrap();
+        build_wall(&mut commands, &assets, gap_y, &loaded_assets);
     }
 }
 
@@ -284,6 +305,6 @@ fn hit_wall(
 ) {
     for _collision in collisions.read() {
         assets.play("crash", &mut commands, &loaded_assets);
-        let _ = state.set(GamePhase::GameOver);
+        state.set(GamePhase::GameOver);
     }
 }
@@ -1,14 +1,96 @@
-use criterion::{Criterion, criterion_group, criterion_main};
+//! Compares random number generation with and without contention.
+//!
+//! Run with `cargo bench -p my_library` for the default (mutable)
+//! generator, and with `--features locking` to add the shared generator,
+//! with and without per-thread generators.
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
 use my_library::*;
+use std::sync::Mutex;
+
+const THREADS: usize = 4;
+const DRAWS: usize = 200_000;
 
 pub fn criterion_benchmark(c: &mut Criterion) {
     // My benchmarks go here
     c.bench_function("random", |b| {
-        let mut rng = RandomNumberGenerator::new();
+        let rng = &mut RandomNumberGenerator::new();
         b.iter(|| {
             rng.range(1.0_f32..10_000_000_f32);
         })
     });
+
+    let mut group = c.benchmark_group("parallel");
+    // Every thread locks one generator, wrapped in a mutex by hand.
+    group.bench_function(BenchmarkId::new("mutex", THREADS), |b| {
+        let rng = Mutex::new(RandomNumberGenerator::seeded(1));
+        b.iter(|| {
+            std::thread::scope(|scope| {
+                for _ in 0..THREADS {
+                    scope.spawn(|| {
+                        for _ in 0..DRAWS {
+                            rng.lock().unwrap().range(1.0_f32..10_000_000_f32);
+                        }
+                    });
+                }
+            })
+        })
+    });
+    // Threads share one generator through `&RandomNumberGenerator`, as
+    // systems taking `Res<RandomNumberGenerator>` do.
+    #[cfg(feature = "locking")]
+    group.bench_function(BenchmarkId::new("shared", THREADS), |b| {
+        let rng = RandomNumberGenerator::seeded(1);
+        b.iter(|| {
+            std::thread::scope(|scope| {
+                for _ in 0..THREADS {
+                    scope.spawn(|| {
+                        for _ in 0..DRAWS {
+                            rng.range(1.0_f32..10_000_000_f32);
+                        }
+                    });
+                }
+            })
+        })
+    });
+    // As above, with per-thread generators. Threads keep their generators
+    // after they exit, so each iteration (which starts new threads) gets
+    // a new `RandomNumberGenerator`.
+    #[cfg(feature = "locking")]
+    group.bench_function(BenchmarkId::new("threads", THREADS), |b| {
+        b.iter_batched(
+            || RandomNumberGenerator::seeded(1).with_thread_generators(),
+            |rng| {
+                std::thread::scope(|scope| {
+                    for _ in 0..THREADS {
+                        scope.spawn(|| {
+                            for _ in 0..DRAWS {
+                                rng.range(1.0_f32..10_000_000_f32);
+                            }
+                        });
+                    }
+                })
+            },
+            criterion::BatchSize::SmallInput,
+        )
+    });
+    // Each thread draws from its own generator, derived with `task`.
+    group.bench_function(BenchmarkId::new("task", THREADS), |b| {
+        let rng = RandomNumberGenerator::new();
+        b.iter(|| {
+            std::thread::scope(|scope| {
+                for thread in 0..THREADS {
+                    let rng = &rng;
+                    scope.spawn(move || {
+                        let rng = &mut rng.task(thread as u64);
+                        for _ in 0..DRAWS {
+                            rng.range(1.0_f32..10_000_000_f32);
+                        }
+                    });
+                }
+            })
+        })
+    });
+    group.finish();
 }
 
 criterion_group!(benches, criterion_benchmark);
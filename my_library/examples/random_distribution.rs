@@ -1,14 +1,35 @@
-//! Roll 3d6 repeatedly and graph the resulting distribution.
-use my_library::RandomNumberGenerator;
+//! Roll 3d6 repeatedly and graph the resulting distribution, followed by
+//! each of the non-uniform distributions `RandomNumberGenerator` offers.
+use my_library::{DiceExpr, RandomNumberGenerator};
 
-fn main() {
+/// Sorts samples into `buckets` equal-width bins between `min` and `max`,
+/// and prints a histogram scaled to fit the terminal.
+fn plot(title: &str, samples: impl Iterator<Item = f32>, min: f32, max: f32, buckets: usize) {
+    let mut results = vec![0; buckets];
+    for sample in samples {
+        let bucket = ((sample - min) / (max - min) * buckets as f32) as usize;
+        results[bucket.min(buckets - 1)] += 1;
+    }
+    let largest = results.iter().copied().max().unwrap_or(1).max(1);
+    println!();
+    println!("{title}:");
+    for (i, count) in results.iter().enumerate() {
+        let start = min + (max - min) * i as f32 / buckets as f32;
+        println!("{start: >6.2} : {}", "#".repeat(count * 60 / largest));
+    }
+}
+
+fn main() -> anyhow::Result<()> {
     // Create a random number generator
-    let mut rng = RandomNumberGenerator::new();
+    // (a `&mut` works with or without the `locking` feature)
+    let rng = &mut RandomNumberGenerator::new();
+    // Parse the dice expression once, rather than on every roll
+    let three_d6: DiceExpr = "3d6".parse()?;
     // Store the results (minus 3)
     let mut results = [0; 16];
     // Roll 1,000 sets of 3d6 and increment results to map distribution
     for _ in 0..1_000 {
-        let roll: usize = rng.range(1..=6) + rng.range(1..=6) + rng.range(1..=6);
+        let roll = rng.roll(&three_d6).total as usize;
         results[roll - 3] += 1;
     }
     // Print the distribution histogram
@@ -20,4 +41,50 @@ fn main() {
         }
         println!();
     }
+
+    // Plot 10,000 samples from each of the other distributions
+    const SAMPLES: usize = 10_000;
+    plot(
+        "Normal (mean 10, standard deviation 2)",
+        (0..SAMPLES).map(|_| rng.normal(10.0, 2.0)),
+        2.0,
+        18.0,
+        16,
+    );
+    plot(
+        "Exponential (lambda 0.5)",
+        (0..SAMPLES).map(|_| rng.exponential(0.5)),
+        0.0,
+        10.0,
+        20,
+    );
+    plot(
+        "Poisson (lambda 3)",
+        (0..SAMPLES).map(|_| rng.poisson(3.0) as f32),
+        0.0,
+        12.0,
+        12,
+    );
+    plot(
+        "Triangular (0 to 10, mode 2)",
+        (0..SAMPLES).map(|_| rng.triangular(0.0, 10.0, 2.0)),
+        0.0,
+        10.0,
+        20,
+    );
+    plot(
+        "Beta (alpha 2, beta 5)",
+        (0..SAMPLES).map(|_| rng.beta(2.0, 5.0)),
+        0.0,
+        1.0,
+        20,
+    );
+    plot(
+        "Binomial (20 trials, p 0.3)",
+        (0..SAMPLES).map(|_| rng.binomial(20, 0.3) as f32),
+        0.0,
+        21.0,
+        21,
+    );
+    Ok(())
 }
@@ -1,11 +1,12 @@
 use bevy::prelude::*;
 
-use crate::{AssetStore, FutureAtlas};
+use crate::{AssetStore, FutureAtlas, WordList, bevy_assets::WordListLoader};
 
 #[derive(Clone)]
 pub enum AssetType {
     Image,
     Sound,
+    WordList,
     SpriteSheet {
         tile_size: Vec2,
         sprites_x: usize,
@@ -68,6 +69,14 @@ impl AssetManager {
         Ok(self)
     }
 
+    pub fn add_word_list<S: ToString>(mut self, tag: S, filename: S) -> anyhow::Result<Self> {
+        let filename = filename.to_string();
+        AssetManager::asset_exists(&filename)?;
+        self.asset_list
+            .push((tag.to_string(), filename, AssetType::WordList));
+        Ok(self)
+    }
+
     pub fn add_sprite_sheet<S: ToString>(
         mut self,
         tag: S,
@@ -93,9 +102,17 @@ impl AssetManager {
     }
 }
 
+impl Default for AssetManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Plugin for AssetManager {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.insert_resource(self.clone());
+        app.init_asset::<WordList>()
+            .init_asset_loader::<WordListLoader>();
         // app.add_systems(Startup, setup);
     }
 }
@@ -41,7 +41,7 @@ impl AssetStore {
 
     pub fn play(&self, sound_name: &str, commands: &mut Commands, assets: &LoadedAssets) {
         let sound_handle: Handle<AudioSource> = self.get_handle(sound_name, assets).unwrap();
-        commands.spawn(AudioPlayer::new(sound_handle.clone()));
+        commands.spawn((AudioPlayer::new(sound_handle.clone()), crate::SoundEffect));
     }
 }
 
@@ -2,12 +2,35 @@ use crate::LoadedAssets;
 use crate::bevy_assets::setup_asset_store;
 use crate::{AssetManager, AssetStore, MenuResource, egui::egui::Window};
 use bevy::state::state::FreelyMutableState;
-use bevy::{asset::LoadedUntypedAsset, prelude::*};
-use bevy_egui::EguiContexts;
+use bevy::{asset::LoadedUntypedAsset, platform::collections::HashMap, prelude::*};
+use bevy_egui::{EguiContexts, egui::ProgressBar};
 
 #[derive(Resource)]
 pub(crate) struct AssetsToLoad(Vec<Handle<LoadedUntypedAsset>>);
 
+/// Background work, such as a [`WfcTask`](crate::WfcTask), that the loading
+/// screen waits for. Each task reports its label and progress (from 0 to
+/// 1) under the entity that runs it.
+#[derive(Resource, Default)]
+pub(crate) struct LoadingProgress(HashMap<Entity, (String, f32)>);
+
+impl LoadingProgress {
+    pub(crate) fn report(&mut self, entity: Entity, label: &str, progress: f32) {
+        self.0.insert(entity, (label.to_string(), progress));
+    }
+
+    pub(crate) fn finish(&mut self, entity: Entity) {
+        self.0.remove(&entity);
+    }
+
+    /// The unfinished tasks, sorted by label.
+    pub(crate) fn tasks(&self) -> Vec<(String, f32)> {
+        let mut tasks: Vec<(String, f32)> = self.0.values().cloned().collect();
+        tasks.sort_by(|a, b| a.0.cmp(&b.0));
+        tasks
+    }
+}
+
 pub(crate) fn setup(
     assets: Option<Res<AssetStore>>,
     asset_manager: Option<Res<AssetManager>>,
@@ -27,30 +50,37 @@ pub(crate) fn setup(
     commands.insert_resource(AssetsToLoad(assets_to_load));
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn run<T>(
     asset_server: Res<AssetServer>,
     mut to_load: ResMut<AssetsToLoad>,
     mut state: ResMut<NextState<T>>,
     mut egui_context: EguiContexts,
     menu_info: Res<MenuResource<T>>,
+    progress: Res<LoadingProgress>,
     mut store: ResMut<AssetStore>,
     mut texture_atlases: ResMut<Assets<TextureAtlasLayout>>,
     loaded_assets: Res<LoadedAssets>,
 ) where
     T: States + FromWorld + FreelyMutableState,
 {
-    to_load
-        .0
-        .retain(|handle| match asset_server.get_load_state(handle.id()) {
-            Some(bevy::asset::LoadState::Loaded) => false,
-            _ => true,
-        });
-    if to_load.0.is_empty() {
+    to_load.0.retain(|handle| {
+        !matches!(
+            asset_server.get_load_state(handle.id()),
+            Some(bevy::asset::LoadState::Loaded)
+        )
+    });
+    let tasks = progress.tasks();
+    if to_load.0.is_empty() && tasks.is_empty() {
         load_atlases(&mut store, &mut texture_atlases, &loaded_assets);
         state.set(menu_info.menu_state.clone());
     }
     Window::new("Loading, Please Wait").show(egui_context.ctx_mut(), |ui| {
-        ui.label(format!("{} assets remaining", to_load.0.len()))
+        ui.label(format!("{} assets remaining", to_load.0.len()));
+        for (label, done) in tasks {
+            ui.label(label);
+            ui.add(ProgressBar::new(done).show_percentage());
+        }
     });
 }
 
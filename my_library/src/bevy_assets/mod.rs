@@ -5,6 +5,8 @@ mod asset_store;
 pub use asset_store::*;
 mod loading_menu;
 pub(crate) use loading_menu::*;
+mod word_list;
+pub use word_list::*;
 
 #[macro_export]
 macro_rules! spawn_image {
@@ -0,0 +1,90 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+
+/// `WordList` is a text asset holding one word (or name) per line, such as
+/// training data for a [`NameGenerator`](crate::NameGenerator). Blank
+/// lines, and lines starting with `#`, are ignored. Word list files use
+/// the `.words` extension.
+///
+/// Add word lists with [`AssetManager::add_word_list`](crate::AssetManager::add_word_list),
+/// and read them with `Res<Assets<WordList>>`.
+///
+/// ## Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use my_library::*;
+///
+/// fn name_planet(
+///     assets: Res<AssetStore>,
+///     loaded_assets: AssetResource,
+///     word_lists: Res<Assets<WordList>>,
+///     mut rng: ResMut<RandomNumberGenerator>,
+/// ) {
+///     let handle = assets.get_handle::<WordList>("planets", &loaded_assets).unwrap();
+///     let names = NameGenerator::new(3).with_words(word_lists.get(&handle).unwrap().words());
+///     println!("Welcome to {}", names.generate(&mut *rng).unwrap());
+/// }
+/// ```
+#[derive(Asset, TypePath, Debug, Clone, PartialEq, Eq)]
+pub struct WordList {
+    words: Vec<String>,
+}
+
+impl WordList {
+    /// Parses a word list from text.
+    pub fn parse(text: &str) -> Self {
+        Self {
+            words: text
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+
+    /// The words in the list.
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+}
+
+/// Loads `.words` files as [`WordList`]s. Plain `.txt` is left alone, so
+/// it doesn't clash with other loaders.
+#[derive(Default)]
+pub(crate) struct WordListLoader;
+
+impl AssetLoader for WordListLoader {
+    type Asset = WordList;
+    type Settings = ();
+    type Error = anyhow::Error;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> anyhow::Result<WordList> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(WordList::parse(&String::from_utf8(bytes)?))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["words"]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let list = WordList::parse("# Planets\n  Mercury \n\nVenus\r\nEarth\n");
+        assert_eq!(list.words(), ["Mercury", "Venus", "Earth"]);
+    }
+}
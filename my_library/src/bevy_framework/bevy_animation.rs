@@ -48,6 +48,12 @@ impl Animations {
     }
 }
 
+impl Default for Animations {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[derive(Component)]
 pub struct AnimationCycle {
     animation_tag: String,
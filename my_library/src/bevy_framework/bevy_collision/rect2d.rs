@@ -11,6 +11,26 @@ impl Rect2D {
         Self { min, max }
     }
 
+    /// The corner with the smallest coordinates.
+    pub fn min(&self) -> Vec2 {
+        self.min
+    }
+
+    /// The corner with the largest coordinates.
+    pub fn max(&self) -> Vec2 {
+        self.max
+    }
+
+    /// The width and height of the rectangle.
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+
+    /// Returns `true` if `point` lies inside the rectangle (or on its edge).
+    pub fn contains(&self, point: Vec2) -> bool {
+        point.cmpge(self.min).all() && point.cmple(self.max).all()
+    }
+
     pub fn intersect(&self, other: &Self) -> bool {
         self.min.x <= other.max.x
             && self.max.x >= other.min.x
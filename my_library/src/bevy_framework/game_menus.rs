@@ -1,11 +1,23 @@
-use super::MenuResource;
+use super::{
+    Menu, MenuAction, MenuResource, MenuStyle, MenuTarget,
+    score::{GameOverScreen, SUBMIT_NAME_BUTTONS, is_typing_key},
+};
 use crate::{AssetResource, AssetStore};
 use bevy::state::state::FreelyMutableState;
 use bevy::{app::AppExit, prelude::*};
+use bevy_egui::{EguiContexts, egui};
 
 #[derive(Component)]
 pub(crate) struct MenuElement;
 
+/// The selected button of a [`MenuStyle::Buttons`] menu, or the selected
+/// row of the options menu.
+#[derive(Resource, Default)]
+pub(crate) struct MenuFocus(pub(crate) usize);
+
+/// The keys that choose the focused button, along with South on a gamepad.
+pub(crate) const CHOOSE_KEYS: [KeyCode; 2] = [KeyCode::Enter, KeyCode::Space];
+
 pub(crate) fn setup<T>(
     state: Res<State<T>>,
     mut commands: Commands,
@@ -15,50 +27,385 @@ pub(crate) fn setup<T>(
 ) where
     T: States + FromWorld + FreelyMutableState,
 {
-    let current_state = state.get();
-    let menu_graphic = match current_state {
-        current_state if menu_resource.menu_state == *current_state => {
-            assets.get_handle("main_menu", &loaded_assets).unwrap()
-        }
-        current_state if menu_resource.game_end_state == *current_state => {
-            assets.get_handle("game_over", &loaded_assets).unwrap()
-        }
-        _ => panic!("Unknown menu state"),
-    };
+    let menu = menu_resource.menu(state.get()).expect("Unknown menu state");
 
+    commands.insert_resource(MenuFocus::default());
     commands.spawn(Camera2d).insert(MenuElement);
-    commands
-        .spawn((
-            Sprite {
-                image: menu_graphic,
-                ..default()
-            },
-            Transform::from_xyz(0.0, 0.0, 1.0),
-        ))
-        .insert(MenuElement);
+    if let Some(tag) = menu.image() {
+        let menu_graphic = assets
+            .get_handle(tag, &loaded_assets)
+            .unwrap_or_else(|| panic!("No image tagged {tag} for the menu"));
+        commands
+            .spawn((
+                Sprite {
+                    image: menu_graphic,
+                    ..default()
+                },
+                Transform::from_xyz(0.0, 0.0, 1.0),
+            ))
+            .insert(MenuElement);
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn run<T>(
     keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
     mut exit: EventWriter<AppExit>,
     current_state: Res<State<T>>,
     mut state: ResMut<NextState<T>>,
-    menu_state: Res<MenuResource<T>>,
+    menu_resource: Res<MenuResource<T>>,
+    mut focus: ResMut<MenuFocus>,
+    game_over: Option<Res<GameOverScreen>>,
 ) where
     T: States + FromWorld + FreelyMutableState,
 {
-    let current_state = current_state.get().clone();
-    if current_state == menu_state.menu_state {
-        if keyboard.just_pressed(KeyCode::KeyP) {
-            state.set(menu_state.game_start_state.clone());
-        } else if keyboard.just_pressed(KeyCode::KeyQ) {
-            exit.write(AppExit::Success);
+    // While a high score name is being entered, typing keys edit the name
+    // and the gamepad buttons that submit it are left to the name field.
+    let entering_name = game_over.is_some_and(|screen| screen.entering_name());
+    if entering_name
+        && gamepads
+            .iter()
+            .any(|gamepad| gamepad.any_just_pressed(SUBMIT_NAME_BUTTONS))
+    {
+        return;
+    }
+    let mut keyboard = keyboard.clone();
+    if entering_name {
+        let typed: Vec<KeyCode> = keyboard
+            .get_just_pressed()
+            .copied()
+            .filter(|key| is_typing_key(*key))
+            .collect();
+        for key in typed {
+            keyboard.clear_just_pressed(key);
+        }
+    }
+    let Some(menu) = menu_resource.menu(current_state.get()) else {
+        return;
+    };
+    if let Some(action) = read_input(menu, menu_resource.style, &mut focus, &keyboard, &gamepads) {
+        choose(action, &mut state, &mut exit);
+    }
+}
+
+/// Finds the action chosen this frame, if any. With
+/// [`MenuStyle::Buttons`], this also moves the focus.
+pub(crate) fn read_input<'a, T>(
+    menu: &'a Menu<T>,
+    style: MenuStyle,
+    focus: &mut MenuFocus,
+    keyboard: &ButtonInput<KeyCode>,
+    gamepads: &Query<&Gamepad>,
+) -> Option<&'a MenuAction<T>> {
+    match style {
+        MenuStyle::Buttons => match navigate(menu, focus, keyboard, gamepads) {
+            Navigation::Moved => None,
+            Navigation::Chose(action) => Some(action),
+            Navigation::None => menu.pressed(keyboard, gamepads),
+        },
+        MenuStyle::Image => menu.pressed(keyboard, gamepads),
+    }
+}
+
+enum Navigation<'a, T> {
+    None,
+    Moved,
+    Chose(&'a MenuAction<T>),
+}
+
+/// Moves the focus with the arrow keys or D-pad, or chooses the focused
+/// action.
+fn navigate<'a, T>(
+    menu: &'a Menu<T>,
+    focus: &mut MenuFocus,
+    keyboard: &ButtonInput<KeyCode>,
+    gamepads: &Query<&Gamepad>,
+) -> Navigation<'a, T> {
+    let count = menu.actions().len();
+    if count == 0 {
+        return Navigation::None;
+    }
+    if move_focus(focus, count, keyboard, gamepads) {
+        Navigation::Moved
+    } else if just_pressed(keyboard, gamepads, &CHOOSE_KEYS, GamepadButton::South) {
+        Navigation::Chose(&menu.actions()[focus.0.min(count - 1)])
+    } else {
+        Navigation::None
+    }
+}
+
+/// Moves the focus through `count` entries with the up and down arrow
+/// keys or D-pad, wrapping around. Returns `true` if it moved.
+pub(crate) fn move_focus(
+    focus: &mut MenuFocus,
+    count: usize,
+    keyboard: &ButtonInput<KeyCode>,
+    gamepads: &Query<&Gamepad>,
+) -> bool {
+    if count == 0 {
+        return false;
+    }
+    if just_pressed(
+        keyboard,
+        gamepads,
+        &[KeyCode::ArrowUp],
+        GamepadButton::DPadUp,
+    ) {
+        focus.0 = (focus.0.min(count - 1) + count - 1) % count;
+        true
+    } else if just_pressed(
+        keyboard,
+        gamepads,
+        &[KeyCode::ArrowDown],
+        GamepadButton::DPadDown,
+    ) {
+        focus.0 = (focus.0 + 1).min(count) % count;
+        true
+    } else {
+        false
+    }
+}
+
+/// Returns `true` if one of `keys`, or `button` on any gamepad, was
+/// pressed this frame.
+pub(crate) fn just_pressed(
+    keyboard: &ButtonInput<KeyCode>,
+    gamepads: &Query<&Gamepad>,
+    keys: &[KeyCode],
+    button: GamepadButton,
+) -> bool {
+    keyboard.any_just_pressed(keys.iter().copied())
+        || gamepads.iter().any(|gamepad| gamepad.just_pressed(button))
+}
+
+/// Draws a [`MenuStyle::Buttons`] menu in front of its image.
+pub(crate) fn show<T>(
+    mut egui_context: EguiContexts,
+    mut exit: EventWriter<AppExit>,
+    current_state: Res<State<T>>,
+    mut state: ResMut<NextState<T>>,
+    menu_resource: Res<MenuResource<T>>,
+    mut focus: ResMut<MenuFocus>,
+) where
+    T: States + FromWorld + FreelyMutableState,
+{
+    let Some(menu) = menu_resource.menu(current_state.get()) else {
+        return;
+    };
+    let chosen = egui::CentralPanel::default()
+        .frame(egui::Frame::NONE)
+        .show(egui_context.ctx_mut(), |ui| {
+            draw_buttons(ui, menu, &mut focus)
+        })
+        .inner;
+    if let Some(action) = chosen {
+        choose(action, &mut state, &mut exit);
+    }
+}
+
+/// Draws a menu's title and buttons in the middle of `ui`, returning the
+/// action that was clicked.
+pub(crate) fn draw_buttons<'a, T>(
+    ui: &mut egui::Ui,
+    menu: &'a Menu<T>,
+    focus: &mut MenuFocus,
+) -> Option<&'a MenuAction<T>> {
+    let mut chosen = None;
+    ui.vertical_centered(|ui| {
+        ui.add_space(ui.available_height() / 3.0);
+        if let Some(title) = menu.title() {
+            ui.label(egui::RichText::new(title).heading().size(48.0));
+            ui.add_space(24.0);
+        }
+        for (index, action) in menu.actions().iter().enumerate() {
+            let button = ui.add(
+                egui::Button::new(egui::RichText::new(action.label()).size(24.0))
+                    .selected(index == focus.0)
+                    .min_size(egui::vec2(240.0, 0.0)),
+            );
+            // Only follow the mouse when it moves, so it doesn't fight
+            // the keyboard.
+            if button.hovered() && ui.input(|input| input.pointer.is_moving()) {
+                focus.0 = index;
+            }
+            if button.clicked() {
+                chosen = Some(action);
+            }
         }
-    } else if current_state == menu_state.game_end_state {
-        if keyboard.just_pressed(KeyCode::KeyM) {
-            state.set(menu_state.menu_state.clone());
-        } else if keyboard.just_pressed(KeyCode::KeyQ) {
+    });
+    chosen
+}
+
+pub(crate) fn choose<T: FreelyMutableState>(
+    action: &MenuAction<T>,
+    state: &mut NextState<T>,
+    exit: &mut EventWriter<AppExit>,
+) {
+    match action.target() {
+        MenuTarget::State(target) => state.set(target.clone()),
+        MenuTarget::Quit => {
             exit.write(AppExit::Success);
         }
+        MenuTarget::Resume => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{GameStatePlugin, HighScores, Menu, MenuAction, Score};
+    use bevy::state::app::StatesPlugin;
+
+    #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default, States)]
+    enum Phase {
+        #[default]
+        Loading,
+        MainMenu,
+        Options,
+        Playing,
+        GameOver,
+    }
+
+    fn app(plugin: GameStatePlugin<Phase>, state: Phase) -> App {
+        let mut app = App::new();
+        app.add_plugins(StatesPlugin)
+            .insert_state(state)
+            .insert_resource(plugin.menu_resource())
+            .init_resource::<MenuFocus>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .add_event::<AppExit>()
+            .add_systems(Update, run::<Phase>);
+        app
+    }
+
+    fn press(app: &mut App, key: KeyCode) {
+        let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keyboard.reset_all();
+        keyboard.press(key);
+        app.update();
+    }
+
+    #[test]
+    fn test_menu_actions() {
+        let plugin = GameStatePlugin::new(Phase::MainMenu, Phase::Playing, Phase::GameOver)
+            .with_menu_action(
+                Phase::MainMenu,
+                MenuAction::go_to("Options", Phase::Options).with_key(KeyCode::KeyO),
+            )
+            .with_menu(
+                Phase::Options,
+                Menu::new().with_action(
+                    MenuAction::go_to("Back", Phase::MainMenu).with_key(KeyCode::Escape),
+                ),
+            );
+        let mut app = app(plugin, Phase::MainMenu);
+
+        press(&mut app, KeyCode::KeyM);
+        app.update();
+        assert_eq!(
+            *app.world().resource::<State<Phase>>().get(),
+            Phase::MainMenu
+        );
+        press(&mut app, KeyCode::KeyO);
+        app.update();
+        assert_eq!(
+            *app.world().resource::<State<Phase>>().get(),
+            Phase::Options
+        );
+        press(&mut app, KeyCode::Escape);
+        app.update();
+        assert_eq!(
+            *app.world().resource::<State<Phase>>().get(),
+            Phase::MainMenu
+        );
+        press(&mut app, KeyCode::KeyP);
+        app.update();
+        assert_eq!(
+            *app.world().resource::<State<Phase>>().get(),
+            Phase::Playing
+        );
+    }
+
+    #[test]
+    fn test_quit() {
+        let plugin = GameStatePlugin::new(Phase::MainMenu, Phase::Playing, Phase::GameOver);
+        let mut app = app(plugin, Phase::GameOver);
+        press(&mut app, KeyCode::KeyQ);
+        let exits = app.world().resource::<Events<AppExit>>();
+        assert_eq!(exits.len(), 1);
+    }
+
+    #[test]
+    fn test_button_navigation() {
+        let plugin = GameStatePlugin::new(Phase::MainMenu, Phase::Playing, Phase::GameOver)
+            .with_menu_style(MenuStyle::Buttons)
+            .with_menu_action(
+                Phase::MainMenu,
+                MenuAction::go_to("Options", Phase::Options),
+            );
+        let mut app = app(plugin, Phase::MainMenu);
+
+        // Up from "Play" wraps around to "Options".
+        press(&mut app, KeyCode::ArrowUp);
+        assert_eq!(app.world().resource::<MenuFocus>().0, 2);
+        press(&mut app, KeyCode::ArrowDown);
+        press(&mut app, KeyCode::ArrowDown);
+        assert_eq!(app.world().resource::<MenuFocus>().0, 1);
+        press(&mut app, KeyCode::ArrowUp);
+        press(&mut app, KeyCode::ArrowUp);
+        press(&mut app, KeyCode::Enter);
+        app.update();
+        assert_eq!(
+            *app.world().resource::<State<Phase>>().get(),
+            Phase::Options
+        );
+    }
+
+    #[test]
+    fn test_high_score_name_entry() {
+        let plugin = GameStatePlugin::new(Phase::MainMenu, Phase::Playing, Phase::GameOver)
+            .with_menu_action(
+                Phase::GameOver,
+                MenuAction::go_to("Back", Phase::MainMenu).with_key(KeyCode::Escape),
+            );
+        let mut app = app(plugin, Phase::Playing);
+        app.insert_resource(HighScores::new(5))
+            .init_resource::<Score>()
+            .add_systems(
+                OnEnter(Phase::GameOver),
+                crate::bevy_framework::score::start_game_over,
+            );
+
+        app.world_mut().resource_mut::<Score>().add(10);
+        app.world_mut()
+            .resource_mut::<NextState<Phase>>()
+            .set(Phase::GameOver);
+        app.update();
+        assert!(app.world().resource::<GameOverScreen>().entering_name());
+
+        // Typing "M" doesn't go back to the main menu.
+        press(&mut app, KeyCode::KeyM);
+        app.update();
+        assert_eq!(
+            *app.world().resource::<State<Phase>>().get(),
+            Phase::GameOver
+        );
+
+        // East on a gamepad submits the name rather than quitting.
+        let mut gamepad = Gamepad::default();
+        gamepad.digital_mut().press(GamepadButton::East);
+        let gamepad = app.world_mut().spawn(gamepad).id();
+        app.update();
+        assert!(app.world().resource::<Events<AppExit>>().is_empty());
+        app.world_mut().despawn(gamepad);
+
+        // Keys that don't type still work.
+        press(&mut app, KeyCode::Escape);
+        app.update();
+        assert_eq!(
+            *app.world().resource::<State<Phase>>().get(),
+            Phase::MainMenu
+        );
     }
 }
@@ -0,0 +1,238 @@
+use bevy::prelude::*;
+
+/// How [`GameStatePlugin`](crate::GameStatePlugin) draws its menus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MenuStyle {
+    /// Only the menu's image, which should show which keys to press.
+    #[default]
+    Image,
+    /// The menu's title and a button for each action, drawn with egui in
+    /// front of the image. Buttons can be clicked, or picked with the
+    /// arrow keys or the gamepad's D-pad and chosen with Enter, Space or
+    /// the South button. Actions' own keys still work, except for keys and
+    /// buttons used to navigate.
+    Buttons,
+}
+
+/// What happens when a [`MenuAction`] is chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuTarget<T> {
+    /// Switch to this game state.
+    State(T),
+    /// Close the game.
+    Quit,
+    /// Close the pause menu and carry on playing. Does nothing in other
+    /// menus.
+    Resume,
+}
+
+/// One entry in a [`Menu`], such as "Play" or "Quit", with the key and
+/// gamepad button that choose it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MenuAction<T> {
+    label: String,
+    key: Option<KeyCode>,
+    button: Option<GamepadButton>,
+    target: MenuTarget<T>,
+}
+
+impl<T> MenuAction<T> {
+    pub fn new<S: ToString>(label: S, target: MenuTarget<T>) -> Self {
+        Self {
+            label: label.to_string(),
+            key: None,
+            button: None,
+            target,
+        }
+    }
+
+    /// An action that switches to `state`.
+    pub fn go_to<S: ToString>(label: S, state: T) -> Self {
+        Self::new(label, MenuTarget::State(state))
+    }
+
+    /// An action that closes the game.
+    pub fn quit<S: ToString>(label: S) -> Self {
+        Self::new(label, MenuTarget::Quit)
+    }
+
+    pub fn with_key(mut self, key: KeyCode) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    pub fn with_button(mut self, button: GamepadButton) -> Self {
+        self.button = Some(button);
+        self
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn key(&self) -> Option<KeyCode> {
+        self.key
+    }
+
+    pub fn button(&self) -> Option<GamepadButton> {
+        self.button
+    }
+
+    pub fn target(&self) -> &MenuTarget<T> {
+        &self.target
+    }
+}
+
+/// A menu screen shown by [`GameStatePlugin`](crate::GameStatePlugin)
+/// while the game is in its state: a background image from the
+/// [`AssetStore`](crate::AssetStore) and a list of actions.
+///
+/// ## Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use my_library::*;
+///
+/// #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default, States)]
+/// enum GamePhase {
+///     #[default]
+///     Loading,
+///     MainMenu,
+///     Credits,
+///     Playing,
+///     GameOver,
+/// }
+///
+/// let plugin = GameStatePlugin::new(GamePhase::MainMenu, GamePhase::Playing, GamePhase::GameOver)
+///     .with_menu_style(MenuStyle::Buttons)
+///     .with_menu_action(
+///         GamePhase::MainMenu,
+///         MenuAction::go_to("Credits", GamePhase::Credits).with_key(KeyCode::KeyC),
+///     )
+///     .with_menu(
+///         GamePhase::Credits,
+///         Menu::new()
+///             .with_title("Credits")
+///             .with_image("credits")
+///             .with_action(MenuAction::go_to("Back", GamePhase::MainMenu).with_key(KeyCode::Escape)),
+///     );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Menu<T> {
+    title: Option<String>,
+    image: Option<String>,
+    actions: Vec<MenuAction<T>>,
+}
+
+impl<T> Menu<T> {
+    pub fn new() -> Self {
+        Self {
+            title: None,
+            image: None,
+            actions: Vec::new(),
+        }
+    }
+
+    /// Shows a title above the buttons, with [`MenuStyle::Buttons`].
+    pub fn with_title<S: ToString>(mut self, title: S) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// Shows the image with this tag behind the menu.
+    pub fn with_image<S: ToString>(mut self, tag: S) -> Self {
+        self.image = Some(tag.to_string());
+        self
+    }
+
+    pub fn with_action(mut self, action: MenuAction<T>) -> Self {
+        self.actions.push(action);
+        self
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    pub fn image(&self) -> Option<&str> {
+        self.image.as_deref()
+    }
+
+    pub fn actions(&self) -> &[MenuAction<T>] {
+        &self.actions
+    }
+
+    /// Adds an action to an existing menu.
+    pub fn add_action(&mut self, action: MenuAction<T>) {
+        self.actions.push(action);
+    }
+
+    /// The main menu [`GameStatePlugin`](crate::GameStatePlugin) shows
+    /// unless told otherwise: the `main_menu` image, P (or the gamepad's
+    /// South button) to play and Q (or East) to quit.
+    pub fn main_menu(game_start_state: T) -> Self {
+        Self::new()
+            .with_image("main_menu")
+            .with_action(
+                MenuAction::go_to("Play", game_start_state)
+                    .with_key(KeyCode::KeyP)
+                    .with_button(GamepadButton::South),
+            )
+            .with_action(
+                MenuAction::quit("Quit")
+                    .with_key(KeyCode::KeyQ)
+                    .with_button(GamepadButton::East),
+            )
+    }
+
+    /// The default game over screen: the `game_over` image, M (or South)
+    /// to return to the main menu and Q (or East) to quit.
+    pub fn game_over(menu_state: T) -> Self {
+        Self::new()
+            .with_image("game_over")
+            .with_action(
+                MenuAction::go_to("Main Menu", menu_state)
+                    .with_key(KeyCode::KeyM)
+                    .with_button(GamepadButton::South),
+            )
+            .with_action(
+                MenuAction::quit("Quit")
+                    .with_key(KeyCode::KeyQ)
+                    .with_button(GamepadButton::East),
+            )
+    }
+
+    /// The pause menu [`GameStatePlugin`](crate::GameStatePlugin) shows
+    /// unless told otherwise: resume, M to return to the main menu and Q
+    /// to quit.
+    pub fn pause_menu(menu_state: T) -> Self {
+        Self::new()
+            .with_title("Paused")
+            .with_action(MenuAction::new("Resume", MenuTarget::Resume))
+            .with_action(MenuAction::go_to("Main Menu", menu_state).with_key(KeyCode::KeyM))
+            .with_action(MenuAction::quit("Quit").with_key(KeyCode::KeyQ))
+    }
+
+    /// The first action whose key or gamepad button was just pressed.
+    pub(crate) fn pressed<'a>(
+        &self,
+        keyboard: &ButtonInput<KeyCode>,
+        gamepads: impl IntoIterator<Item = &'a Gamepad> + Clone,
+    ) -> Option<&MenuAction<T>> {
+        self.actions.iter().find(|action| {
+            action.key.is_some_and(|key| keyboard.just_pressed(key))
+                || action.button.is_some_and(|button| {
+                    gamepads
+                        .clone()
+                        .into_iter()
+                        .any(|gamepad| gamepad.just_pressed(button))
+                })
+        })
+    }
+}
+
+impl<T> Default for Menu<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -4,24 +4,143 @@ use bevy::state::state::FreelyMutableState;
 mod bevy_animation;
 mod game_menus;
 pub use bevy_animation::*;
+mod menu_config;
+pub use menu_config::*;
+mod pause;
+pub use pause::{PauseState, not_paused};
+mod score;
+pub use score::{GameOverMessage, HighScore, HighScorePlugin, HighScores, Score};
+mod settings;
+pub use settings::*;
 mod bevy_physics;
 pub use bevy_physics::*;
 mod bevy_collision;
 pub use bevy_collision::*;
+mod settings_menu;
 
 pub struct GameStatePlugin<T> {
     menu_state: T,
     game_start_state: T,
     game_end_state: T,
+    menus: Vec<(T, Menu<T>)>,
+    menu_style: MenuStyle,
+    pause_menu: Menu<T>,
+    pause_key: Option<KeyCode>,
+    pause_button: Option<GamepadButton>,
+    settings_state: Option<T>,
 }
 
-impl<T> GameStatePlugin<T> {
+impl<T: Copy + PartialEq> GameStatePlugin<T> {
+    /// Creates the plugin with the default main menu, game over and pause
+    /// screens (see [`Menu::main_menu`], [`Menu::game_over`] and
+    /// [`Menu::pause_menu`]). Escape, or Start on a gamepad, pauses the
+    /// game.
     #[allow(clippy::new_without_default)]
     pub fn new(menu_state: T, game_start_state: T, game_end_state: T) -> Self {
         Self {
             menu_state,
-            game_end_state,
             game_start_state,
+            game_end_state,
+            menu_style: MenuStyle::default(),
+            pause_menu: Menu::pause_menu(menu_state),
+            pause_key: Some(KeyCode::Escape),
+            pause_button: Some(GamepadButton::Start),
+            settings_state: None,
+            menus: vec![
+                (menu_state, Menu::main_menu(game_start_state)),
+                (game_end_state, Menu::game_over(menu_state)),
+            ],
+        }
+    }
+
+    /// Chooses how menus are drawn: just their images (the default) or
+    /// with egui buttons.
+    pub fn with_menu_style(mut self, style: MenuStyle) -> Self {
+        self.menu_style = style;
+        self
+    }
+
+    /// Sets the key that pauses and resumes the game.
+    pub fn with_pause_key(mut self, key: KeyCode) -> Self {
+        self.pause_key = Some(key);
+        self
+    }
+
+    /// Sets the gamepad button that pauses and resumes the game.
+    pub fn with_pause_button(mut self, button: GamepadButton) -> Self {
+        self.pause_button = Some(button);
+        self
+    }
+
+    /// Replaces the menu shown while the game is paused. Use
+    /// [`MenuTarget::Resume`] for an action that carries on playing.
+    pub fn with_pause_menu(mut self, menu: Menu<T>) -> Self {
+        self.pause_menu = menu;
+        self
+    }
+
+    /// Stops the player from pausing the game.
+    pub fn without_pause(mut self) -> Self {
+        self.pause_key = None;
+        self.pause_button = None;
+        self
+    }
+
+    /// Adds an options menu, shown in `state`, where the player can
+    /// change their [`Settings`], and an "Options" entry (O, or North on a
+    /// gamepad) on the main menu. Add a [`SettingsPlugin`] as well.
+    ///
+    /// Besides the mouse, the arrow keys or D-pad move between the options
+    /// and change the selected one, Enter, Space or South press the
+    /// selected button, and Escape or East go back.
+    pub fn with_settings_menu(mut self, state: T) -> Self {
+        self.settings_state = Some(state);
+        let menu_state = self.menu_state;
+        self.with_menu_action(
+            menu_state,
+            MenuAction::go_to("Options", state)
+                .with_key(KeyCode::KeyO)
+                .with_button(GamepadButton::North),
+        )
+    }
+
+    /// Shows `menu` while the game is in `state`, replacing any menu
+    /// already shown there.
+    pub fn with_menu(mut self, state: T, menu: Menu<T>) -> Self {
+        match self
+            .menus
+            .iter_mut()
+            .find(|(existing, _)| *existing == state)
+        {
+            Some((_, existing)) => *existing = menu,
+            None => self.menus.push((state, menu)),
+        }
+        self
+    }
+
+    /// Adds an action to the menu shown in `state`, such as an "Options"
+    /// entry on the main menu.
+    pub fn with_menu_action(mut self, state: T, action: MenuAction<T>) -> Self {
+        match self
+            .menus
+            .iter_mut()
+            .find(|(existing, _)| *existing == state)
+        {
+            Some((_, menu)) => menu.add_action(action),
+            None => self.menus.push((state, Menu::new().with_action(action))),
+        }
+        self
+    }
+
+    fn menu_resource(&self) -> MenuResource<T> {
+        MenuResource {
+            menu_state: self.menu_state,
+            menus: self.menus.clone(),
+            style: self.menu_style,
+            pause_menu: self.pause_menu.clone(),
+            pause_key: self.pause_key,
+            pause_button: self.pause_button,
+            settings_state: self.settings_state,
         }
     }
 }
@@ -38,30 +157,50 @@ where
         app.add_plugins(bevy_egui::EguiPlugin {
             enable_multipass_for_primary_context: false,
         });
-        let start = MenuResource {
-            menu_state: self.menu_state,
-            game_start_state: self.game_start_state,
-            game_end_state: self.game_end_state,
-        };
-        app.insert_resource(start);
+        app.insert_resource(self.menu_resource());
+        app.init_resource::<game_menus::MenuFocus>();
 
-        app.add_systems(OnEnter(self.menu_state), game_menus::setup::<T>);
+        app.init_state::<PauseState>();
+        app.add_systems(Update, pause::toggle::<T>);
+        app.add_systems(OnEnter(PauseState::Paused), pause::enter);
         app.add_systems(
             Update,
-            game_menus::run::<T>.run_if(in_state(self.menu_state)),
+            (pause::run::<T>, pause::show::<T>).run_if(in_state(PauseState::Paused)),
         );
-        app.add_systems(OnExit(self.menu_state), cleanup::<game_menus::MenuElement>);
+        app.add_systems(OnExit(PauseState::Paused), pause::exit);
 
-        app.add_systems(OnEnter(self.game_end_state), game_menus::setup::<T>);
+        app.init_resource::<Score>();
+        app.add_systems(OnEnter(self.game_start_state), score::reset_score);
+        app.add_systems(OnEnter(self.game_end_state), score::start_game_over);
         app.add_systems(
             Update,
-            game_menus::run::<T>.run_if(in_state(self.game_end_state)),
-        );
-        app.add_systems(
-            OnExit(self.game_end_state),
-            cleanup::<game_menus::MenuElement>,
+            score::show_game_over
+                .after(game_menus::run::<T>)
+                .run_if(in_state(self.game_end_state)),
         );
+        app.add_systems(OnExit(self.game_end_state), score::end_game_over);
 
+        if let Some(state) = self.settings_state {
+            app.add_systems(OnEnter(state), settings_menu::setup);
+            app.add_systems(
+                Update,
+                (settings_menu::run::<T>, settings_menu::show::<T>)
+                    .chain()
+                    .run_if(in_state(state)),
+            );
+            app.add_systems(OnExit(state), cleanup::<game_menus::MenuElement>);
+        }
+
+        for (state, _) in self.menus.iter() {
+            app.add_systems(OnEnter(*state), game_menus::setup::<T>);
+            app.add_systems(Update, game_menus::run::<T>.run_if(in_state(*state)));
+            if self.menu_style == MenuStyle::Buttons {
+                app.add_systems(Update, game_menus::show::<T>.run_if(in_state(*state)));
+            }
+            app.add_systems(OnExit(*state), cleanup::<game_menus::MenuElement>);
+        }
+
+        app.init_resource::<crate::bevy_assets::LoadingProgress>();
         app.add_systems(OnEnter(T::default()), crate::bevy_assets::setup);
         app.add_systems(
             Update,
@@ -83,8 +222,32 @@ where
 #[derive(Resource)]
 pub(crate) struct MenuResource<T> {
     pub(crate) menu_state: T,
-    pub(crate) game_start_state: T,
-    pub(crate) game_end_state: T,
+    pub(crate) menus: Vec<(T, Menu<T>)>,
+    pub(crate) style: MenuStyle,
+    pub(crate) pause_menu: Menu<T>,
+    pub(crate) pause_key: Option<KeyCode>,
+    pub(crate) pause_button: Option<GamepadButton>,
+    pub(crate) settings_state: Option<T>,
+}
+
+impl<T: PartialEq> MenuResource<T> {
+    /// The menu shown in `state`, if there is one.
+    pub(crate) fn menu(&self, state: &T) -> Option<&Menu<T>> {
+        self.menus
+            .iter()
+            .find(|(menu_state, _)| menu_state == state)
+            .map(|(_, menu)| menu)
+    }
+}
+
+impl<T: PartialEq + Default> MenuResource<T> {
+    /// Whether the game can be paused in `state`: anywhere but the
+    /// loading screen and menus.
+    pub(crate) fn can_pause(&self, state: &T) -> bool {
+        *state != T::default()
+            && self.menu(state).is_none()
+            && self.settings_state.as_ref() != Some(state)
+    }
 }
 
 #[macro_export]
@@ -100,7 +263,8 @@ macro_rules! add_phase {
             $start
         );)*
         $($app.add_systems(
-            bevy::prelude::Update, $run.run_if(in_state($phase))
+            bevy::prelude::Update,
+            $run.run_if(in_state($phase)).run_if($crate::not_paused)
         );)*
         $($app.add_systems(
             bevy::prelude::OnExit::<$type>($phase),
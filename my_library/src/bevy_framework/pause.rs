@@ -0,0 +1,225 @@
+use super::{
+    MenuResource, MenuStyle, MenuTarget,
+    game_menus::{MenuFocus, choose, draw_buttons, read_input},
+};
+use bevy::state::state::FreelyMutableState;
+use bevy::{app::AppExit, prelude::*};
+use bevy_egui::{EguiContexts, egui};
+
+/// Whether the game is paused. [`GameStatePlugin`](crate::GameStatePlugin)
+/// adds this state, and pauses when the player presses the pause key
+/// (Escape, or Start on a gamepad, unless configured otherwise) outside
+/// of the loading screen and menus.
+///
+/// While paused, [`Time`] stands still, so the physics clock, animations
+/// and parallax stop, and systems added with [`add_phase!`](crate::add_phase)
+/// don't run at all.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default, States)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+}
+
+/// A run condition that holds unless the game is paused.
+///
+/// ## Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use my_library::*;
+///
+/// fn spin(mut query: Query<&mut Transform>) {}
+///
+/// App::new().add_systems(Update, spin.run_if(not_paused));
+/// ```
+pub fn not_paused(state: Option<Res<State<PauseState>>>) -> bool {
+    state.is_none_or(|state| *state.get() == PauseState::Running)
+}
+
+pub(crate) fn toggle<T>(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    current_state: Res<State<T>>,
+    pause_state: Res<State<PauseState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    menu_resource: Res<MenuResource<T>>,
+) where
+    T: States + FromWorld + FreelyMutableState + Default,
+{
+    let paused = *pause_state.get() == PauseState::Paused;
+    if !menu_resource.can_pause(current_state.get()) {
+        // The game moved on to a menu without going through the pause menu.
+        if paused {
+            next_pause_state.set(PauseState::Running);
+        }
+        return;
+    }
+    let pressed = menu_resource
+        .pause_key
+        .is_some_and(|key| keyboard.just_pressed(key))
+        || menu_resource
+            .pause_button
+            .is_some_and(|button| gamepads.iter().any(|gamepad| gamepad.just_pressed(button)));
+    if pressed {
+        next_pause_state.set(match paused {
+            true => PauseState::Running,
+            false => PauseState::Paused,
+        });
+    }
+}
+
+pub(crate) fn enter(mut time: ResMut<Time<Virtual>>, mut commands: Commands) {
+    time.pause();
+    commands.insert_resource(MenuFocus::default());
+}
+
+pub(crate) fn exit(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+pub(crate) fn run<T>(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut exit: EventWriter<AppExit>,
+    mut state: ResMut<NextState<T>>,
+    mut pause_state: ResMut<NextState<PauseState>>,
+    menu_resource: Res<MenuResource<T>>,
+    mut focus: ResMut<MenuFocus>,
+) where
+    T: States + FromWorld + FreelyMutableState,
+{
+    let menu = &menu_resource.pause_menu;
+    if let Some(action) = read_input(menu, MenuStyle::Buttons, &mut focus, &keyboard, &gamepads) {
+        resume(action.target(), &mut pause_state);
+        choose(action, &mut state, &mut exit);
+    }
+}
+
+/// Draws the pause menu over the game.
+pub(crate) fn show<T>(
+    mut egui_context: EguiContexts,
+    mut exit: EventWriter<AppExit>,
+    mut state: ResMut<NextState<T>>,
+    mut pause_state: ResMut<NextState<PauseState>>,
+    menu_resource: Res<MenuResource<T>>,
+    mut focus: ResMut<MenuFocus>,
+) where
+    T: States + FromWorld + FreelyMutableState,
+{
+    let menu = &menu_resource.pause_menu;
+    let chosen = egui::CentralPanel::default()
+        .frame(egui::Frame::NONE.fill(egui::Color32::from_black_alpha(160)))
+        .show(egui_context.ctx_mut(), |ui| {
+            draw_buttons(ui, menu, &mut focus)
+        })
+        .inner;
+    if let Some(action) = chosen {
+        resume(action.target(), &mut pause_state);
+        choose(action, &mut state, &mut exit);
+    }
+}
+
+/// Leaves the pause menu, unless the game is quitting.
+fn resume<T>(target: &MenuTarget<T>, pause_state: &mut NextState<PauseState>) {
+    if !matches!(target, MenuTarget::Quit) {
+        pause_state.set(PauseState::Running);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::GameStatePlugin;
+    use bevy::{state::app::StatesPlugin, time::TimePlugin};
+
+    #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default, States)]
+    enum Phase {
+        #[default]
+        Loading,
+        MainMenu,
+        Playing,
+        GameOver,
+    }
+
+    #[derive(Resource, Default)]
+    struct Ticks(u32);
+
+    fn tick(mut ticks: ResMut<Ticks>) {
+        ticks.0 += 1;
+    }
+
+    fn app(state: Phase) -> App {
+        let plugin = GameStatePlugin::new(Phase::MainMenu, Phase::Playing, Phase::GameOver);
+        let mut app = App::new();
+        app.add_plugins((TimePlugin, StatesPlugin))
+            .insert_state(state)
+            .init_state::<PauseState>()
+            .insert_resource(plugin.menu_resource())
+            .init_resource::<MenuFocus>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<Ticks>()
+            .add_event::<AppExit>()
+            .add_systems(Update, toggle::<Phase>)
+            .add_systems(OnEnter(PauseState::Paused), enter)
+            .add_systems(Update, run::<Phase>.run_if(in_state(PauseState::Paused)))
+            .add_systems(OnExit(PauseState::Paused), exit);
+        crate::add_phase!(app, Phase, Phase::Playing,
+            start => [ ],
+            run => [ tick ],
+            exit => [ ]
+        );
+        app
+    }
+
+    fn press(app: &mut App, key: KeyCode) {
+        let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keyboard.reset_all();
+        keyboard.press(key);
+        app.update();
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .reset_all();
+        app.update();
+    }
+
+    fn paused(app: &App) -> bool {
+        *app.world().resource::<State<PauseState>>().get() == PauseState::Paused
+    }
+
+    #[test]
+    fn test_pause() {
+        let mut app = app(Phase::Playing);
+        app.update();
+        assert_eq!(app.world().resource::<Ticks>().0, 1);
+
+        press(&mut app, KeyCode::Escape);
+        assert!(paused(&app));
+        assert!(app.world().resource::<Time<Virtual>>().is_paused());
+        let ticks = app.world().resource::<Ticks>().0;
+        app.update();
+        assert_eq!(app.world().resource::<Ticks>().0, ticks);
+
+        press(&mut app, KeyCode::Escape);
+        assert!(!paused(&app));
+        assert!(!app.world().resource::<Time<Virtual>>().is_paused());
+        app.update();
+        assert!(app.world().resource::<Ticks>().0 > ticks);
+    }
+
+    #[test]
+    fn test_pause_menu() {
+        let mut app = app(Phase::Playing);
+        press(&mut app, KeyCode::Escape);
+        press(&mut app, KeyCode::KeyM);
+        assert!(!paused(&app));
+        assert_eq!(
+            *app.world().resource::<State<Phase>>().get(),
+            Phase::MainMenu
+        );
+
+        // Menus can't be paused.
+        press(&mut app, KeyCode::Escape);
+        assert!(!paused(&app));
+    }
+}
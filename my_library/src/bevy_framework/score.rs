@@ -0,0 +1,480 @@
+use super::settings::config_dir;
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+use std::path::{Path, PathBuf};
+
+/// The longest name that can be entered for a high score.
+const MAX_NAME_LENGTH: usize = 16;
+
+/// Gamepad buttons that submit a high score name, as "Anonymous" if
+/// nothing was typed.
+pub(crate) const SUBMIT_NAME_BUTTONS: [GamepadButton; 2] =
+    [GamepadButton::East, GamepadButton::Start];
+
+/// The player's score in the current game.
+/// [`GameStatePlugin`](crate::GameStatePlugin) resets it when a new game
+/// starts, and shows it on the game over screen, along with the high
+/// score table if there is a [`HighScorePlugin`].
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Score(u32);
+
+impl Score {
+    /// The number of points scored so far.
+    pub fn get(&self) -> u32 {
+        self.0
+    }
+
+    /// Adds `points` to the score, stopping at `u32::MAX`.
+    pub fn add(&mut self, points: u32) {
+        self.0 = self.0.saturating_add(points);
+    }
+
+    /// Replaces the score with `points`.
+    pub fn set(&mut self, points: u32) {
+        self.0 = points;
+    }
+
+    /// Sets the score back to zero.
+    pub fn reset(&mut self) {
+        self.0 = 0;
+    }
+}
+
+/// Extra text for the game over screen, shown under the score, such as
+/// the other players' scores and who won. Insert it before the game ends;
+/// it is removed when a new game starts.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct GameOverMessage(pub String);
+
+/// An entry in the [`HighScores`] table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HighScore {
+    pub name: String,
+    pub score: u32,
+}
+
+/// The best scores so far, highest first.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HighScores {
+    capacity: usize,
+    entries: Vec<HighScore>,
+}
+
+impl HighScores {
+    /// Creates an empty table that keeps the best `capacity` scores.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn entries(&self) -> &[HighScore] {
+        &self.entries
+    }
+
+    /// Whether `score` would make it into the table. Scores of 0 never do.
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0
+            && (self.entries.len() < self.capacity
+                || self.entries.last().is_some_and(|last| score > last.score))
+    }
+
+    /// Adds a score to the table, returning its position (0 being the
+    /// best), or `None` if it didn't make it in. Ties go below the
+    /// scores that were there first.
+    pub fn insert<S: ToString>(&mut self, name: S, score: u32) -> Option<usize> {
+        if !self.qualifies(score) {
+            return None;
+        }
+        let rank = self
+            .entries
+            .iter()
+            .position(|entry| entry.score < score)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(
+            rank,
+            HighScore {
+                name: name.to_string(),
+                score,
+            },
+        );
+        self.entries.truncate(self.capacity);
+        Some(rank)
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(feature = "serde")]
+impl HighScores {
+    /// Reads a table from a [RON](https://github.com/ron-rs/ron) file.
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::Error::msg(format!("Unable to read {}: {e}", path.display())))?;
+        Ok(ron::from_str(&source)?)
+    }
+
+    /// Writes the table to a RON file, creating its directory if needed.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, text)
+            .map_err(|e| anyhow::Error::msg(format!("Unable to write {}: {e}", path.display())))
+    }
+}
+
+/// Where the high scores are saved.
+#[derive(Resource)]
+#[cfg_attr(not(feature = "serde"), allow(dead_code))]
+pub(crate) struct HighScoreFile(Option<PathBuf>);
+
+/// `HighScorePlugin` adds a [`HighScores`] table, saved as
+/// `high_scores.ron` in a folder named after the game in the user's
+/// config directory. When a game ends with a score good enough for the
+/// table, the game over screen of
+/// [`GameStatePlugin`](crate::GameStatePlugin) asks for the player's name
+/// and adds it. High scores are only saved with the `serde` feature.
+///
+/// ## Example
+///
+/// ```no_run
+/// use bevy::prelude::*;
+/// use my_library::*;
+///
+/// App::new()
+///     .add_plugins(DefaultPlugins)
+///     .add_plugins(HighScorePlugin::new("flappy_dragon"))
+///     .run();
+/// ```
+pub struct HighScorePlugin {
+    path: Option<PathBuf>,
+    capacity: usize,
+}
+
+impl HighScorePlugin {
+    /// Creates the plugin, keeping the ten best scores under `game_name`
+    /// in the user's config directory.
+    pub fn new(game_name: &str) -> Self {
+        Self {
+            path: config_dir().map(|directory| directory.join(game_name).join("high_scores.ron")),
+            capacity: 10,
+        }
+    }
+
+    /// Saves the table to `path` instead.
+    pub fn with_path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets how many scores the table keeps.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// The saved table, if there is one, with this plugin's capacity.
+    fn initial_scores(&self) -> HighScores {
+        let mut scores = self
+            .path
+            .as_ref()
+            .and_then(|path| load(path))
+            .unwrap_or_else(|| HighScores::new(self.capacity));
+        scores.capacity = self.capacity;
+        // The file may have been edited by hand
+        scores
+            .entries
+            .sort_by_key(|entry| std::cmp::Reverse(entry.score));
+        scores.entries.truncate(self.capacity);
+        scores
+    }
+}
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.initial_scores());
+        app.insert_resource(HighScoreFile(self.path.clone()));
+    }
+}
+
+#[cfg(feature = "serde")]
+fn load(path: &Path) -> Option<HighScores> {
+    if !path.exists() {
+        return None;
+    }
+    HighScores::load(path)
+        .inspect_err(|error| warn!("Ignoring saved high scores: {error}"))
+        .ok()
+}
+
+#[cfg(not(feature = "serde"))]
+fn load(_path: &Path) -> Option<HighScores> {
+    None
+}
+
+#[cfg(feature = "serde")]
+fn save(scores: &HighScores, file: &HighScoreFile) {
+    if let Some(path) = &file.0
+        && let Err(error) = scores.save(path)
+    {
+        warn!("Unable to save high scores: {error}");
+    }
+}
+
+#[cfg(not(feature = "serde"))]
+fn save(_scores: &HighScores, _file: &HighScoreFile) {}
+
+/// The state of the game over screen.
+#[derive(Resource, Default)]
+pub(crate) struct GameOverScreen {
+    /// The name being typed, while the player is entering a high score.
+    name: Option<String>,
+    /// The position of the new high score, once it has been entered.
+    rank: Option<usize>,
+}
+
+impl GameOverScreen {
+    /// Whether the player is typing, so menu keys shouldn't act.
+    pub(crate) fn entering_name(&self) -> bool {
+        self.name.is_some()
+    }
+}
+
+/// Whether `key` types or edits a high score name, rather than choosing
+/// from the menu.
+pub(crate) fn is_typing_key(key: KeyCode) -> bool {
+    use KeyCode::*;
+    matches!(
+        key,
+        KeyA | KeyB
+            | KeyC
+            | KeyD
+            | KeyE
+            | KeyF
+            | KeyG
+            | KeyH
+            | KeyI
+            | KeyJ
+            | KeyK
+            | KeyL
+            | KeyM
+            | KeyN
+            | KeyO
+            | KeyP
+            | KeyQ
+            | KeyR
+            | KeyS
+            | KeyT
+            | KeyU
+            | KeyV
+            | KeyW
+            | KeyX
+            | KeyY
+            | KeyZ
+            | Digit0
+            | Digit1
+            | Digit2
+            | Digit3
+            | Digit4
+            | Digit5
+            | Digit6
+            | Digit7
+            | Digit8
+            | Digit9
+            | Space
+            | Minus
+            | Period
+            | Enter
+            | NumpadEnter
+            | Backspace
+            | Delete
+            | ArrowLeft
+            | ArrowRight
+            | Home
+            | End
+    )
+}
+
+pub(crate) fn reset_score(mut score: ResMut<Score>, mut commands: Commands) {
+    score.reset();
+    commands.remove_resource::<GameOverMessage>();
+}
+
+pub(crate) fn start_game_over(
+    score: Res<Score>,
+    high_scores: Option<Res<HighScores>>,
+    mut commands: Commands,
+) {
+    let qualifies = high_scores.is_some_and(|scores| scores.qualifies(score.get()));
+    commands.insert_resource(GameOverScreen {
+        name: qualifies.then(String::new),
+        rank: None,
+    });
+}
+
+pub(crate) fn end_game_over(mut commands: Commands) {
+    commands.remove_resource::<GameOverScreen>();
+}
+
+/// Shows the final score and the high score table, asking for the
+/// player's name if they made it in.
+pub(crate) fn show_game_over(
+    mut egui_context: EguiContexts,
+    score: Res<Score>,
+    message: Option<Res<GameOverMessage>>,
+    high_scores: Option<ResMut<HighScores>>,
+    file: Option<Res<HighScoreFile>>,
+    mut screen: ResMut<GameOverScreen>,
+    gamepads: Query<&Gamepad>,
+) {
+    if high_scores.is_none() && message.is_none() && score.get() == 0 {
+        return;
+    }
+    let mut submitted = None;
+    egui::Window::new("Game Over")
+        .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -32.0])
+        .collapsible(false)
+        .resizable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.vertical_centered(|ui| {
+                ui.heading(format!("Score: {}", score.get()));
+                if let Some(message) = &message {
+                    ui.label(&message.0);
+                }
+            });
+            if let Some(name) = &mut screen.name {
+                ui.label("New high score! Enter your name:");
+                ui.horizontal(|ui| {
+                    let field =
+                        ui.add(egui::TextEdit::singleline(name).char_limit(MAX_NAME_LENGTH));
+                    if !field.has_focus() {
+                        field.request_focus();
+                    }
+                    let entered = ui.input(|input| input.key_pressed(egui::Key::Enter))
+                        || gamepads
+                            .iter()
+                            .any(|gamepad| gamepad.any_just_pressed(SUBMIT_NAME_BUTTONS));
+                    if ui.button("Save").clicked() || entered {
+                        submitted = Some(name.trim().to_string());
+                    }
+                });
+            }
+            if let Some(high_scores) = &high_scores {
+                ui.separator();
+                egui::Grid::new("high_scores")
+                    .num_columns(3)
+                    .spacing([24.0, 4.0])
+                    .show(ui, |ui| {
+                        for (rank, entry) in high_scores.entries().iter().enumerate() {
+                            let text = |text: String| match screen.rank == Some(rank) {
+                                true => egui::RichText::new(text)
+                                    .strong()
+                                    .color(egui::Color32::YELLOW),
+                                false => egui::RichText::new(text),
+                            };
+                            ui.label(text(format!("{}.", rank + 1)));
+                            ui.label(text(entry.name.clone()));
+                            ui.label(text(entry.score.to_string()));
+                            ui.end_row();
+                        }
+                    });
+            }
+        });
+
+    if let Some(name) = submitted
+        && let Some(mut high_scores) = high_scores
+    {
+        let name = match name.is_empty() {
+            true => "Anonymous".to_string(),
+            false => name,
+        };
+        screen.name = None;
+        screen.rank = high_scores.insert(name, score.get());
+        if let Some(file) = file {
+            save(&high_scores, &file);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_high_scores() {
+        let mut scores = HighScores::new(3);
+        assert!(!scores.qualifies(0));
+        assert_eq!(scores.insert("Ann", 10), Some(0));
+        assert_eq!(scores.insert("Bob", 30), Some(0));
+        assert_eq!(scores.insert("Cat", 10), Some(2));
+        // The table is full, so a tie with the lowest score doesn't make it.
+        assert!(!scores.qualifies(10));
+        assert_eq!(scores.insert("Dan", 10), None);
+        assert_eq!(scores.insert("Eve", 20), Some(1));
+        let names: Vec<&str> = scores
+            .entries()
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect();
+        assert_eq!(names, ["Bob", "Eve", "Ann"]);
+    }
+
+    #[test]
+    fn test_score() {
+        let mut score = Score::default();
+        score.add(5);
+        score.add(u32::MAX);
+        assert_eq!(score.get(), u32::MAX);
+        score.reset();
+        assert_eq!(score.get(), 0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir()
+            .join(format!("my_library_high_scores_{}", std::process::id()))
+            .join("high_scores.ron");
+        let plugin = HighScorePlugin::new("test").with_path(&path);
+        assert!(plugin.initial_scores().entries().is_empty());
+
+        let mut scores = plugin.initial_scores();
+        for (name, score) in [("Ann", 10), ("Bob", 30), ("Cat", 20)] {
+            scores.insert(name, score);
+        }
+        save(&scores, &HighScoreFile(Some(path.clone())));
+        assert_eq!(plugin.initial_scores(), scores);
+        // A smaller table keeps the best scores.
+        let smaller = HighScorePlugin::new("test")
+            .with_path(&path)
+            .with_capacity(2);
+        assert_eq!(smaller.initial_scores().entries(), &scores.entries()[..2]);
+        // Out of order entries are sorted before the worst are dropped.
+        std::fs::write(
+            &path,
+            r#"(capacity: 3, entries: [(name: "Ann", score: 10), (name: "Bob", score: 30), (name: "Cat", score: 10), (name: "Dan", score: 20)])"#,
+        )
+        .unwrap();
+        let names: Vec<String> = smaller
+            .initial_scores()
+            .entries()
+            .iter()
+            .map(|entry| entry.name.clone())
+            .collect();
+        assert_eq!(names, ["Bob", "Dan"]);
+        let all = HighScorePlugin::new("test")
+            .with_path(&path)
+            .initial_scores();
+        assert_eq!(all.entries()[2].name, "Ann");
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
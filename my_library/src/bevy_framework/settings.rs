@@ -0,0 +1,495 @@
+use bevy::{
+    audio::Volume,
+    prelude::*,
+    window::{MonitorSelection, PrimaryWindow, VideoModeSelection, WindowMode},
+};
+use std::path::{Path, PathBuf};
+
+/// How long to wait after the last change before saving the settings, so
+/// dragging a slider doesn't write the file every frame.
+#[cfg(feature = "serde")]
+const SAVE_DELAY_SECS: f32 = 0.5;
+
+/// Window sizes offered by the options menu.
+pub(crate) const RESOLUTIONS: [UVec2; 5] = [
+    UVec2::new(1024, 768),
+    UVec2::new(1280, 720),
+    UVec2::new(1280, 1024),
+    UVec2::new(1600, 900),
+    UVec2::new(1920, 1080),
+];
+
+/// How the game's window is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    /// A window without borders, covering the whole screen.
+    BorderlessFullscreen,
+    /// Exclusive fullscreen, at the monitor's current video mode.
+    Fullscreen,
+}
+
+impl DisplayMode {
+    pub(crate) const ALL: [DisplayMode; 3] = [
+        DisplayMode::Windowed,
+        DisplayMode::BorderlessFullscreen,
+        DisplayMode::Fullscreen,
+    ];
+
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            DisplayMode::Windowed => "Windowed",
+            DisplayMode::BorderlessFullscreen => "Borderless Fullscreen",
+            DisplayMode::Fullscreen => "Fullscreen",
+        }
+    }
+}
+
+/// A named game action, such as "Flap", and the key that triggers it.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyBinding {
+    pub action: String,
+    pub key: KeyCode,
+}
+
+/// The player's settings, shared by every game using [`SettingsPlugin`].
+/// Volumes range from 0 to 1, and the master volume scales the music and
+/// sound effect volumes.
+///
+/// Games should read keys from here instead of hard-coding them, so the
+/// player can rebind them:
+///
+/// ```
+/// use bevy::prelude::*;
+/// use my_library::*;
+///
+/// fn flap(keyboard: Res<ButtonInput<KeyCode>>, settings: Res<Settings>) {
+///     if keyboard.pressed(settings.key("Flap")) {
+///         // ...
+///     }
+/// }
+/// ```
+#[derive(Resource, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Settings {
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub display_mode: DisplayMode,
+    /// The window size; `None` keeps the size the game asked for.
+    pub resolution: Option<UVec2>,
+    key_bindings: Vec<KeyBinding>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.0,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            display_mode: DisplayMode::default(),
+            resolution: None,
+            key_bindings: Vec::new(),
+        }
+    }
+}
+
+impl Settings {
+    /// The key bound to `action`.
+    ///
+    /// Panics if the action was never given a key with
+    /// [`SettingsPlugin::with_key_binding`].
+    pub fn key(&self, action: &str) -> KeyCode {
+        self.key_bindings
+            .iter()
+            .find(|binding| binding.action == action)
+            .map(|binding| binding.key)
+            .unwrap_or_else(|| panic!("No key is bound to {action}"))
+    }
+
+    /// Binds `action` to `key`, replacing its current key.
+    pub fn bind<S: ToString>(&mut self, action: S, key: KeyCode) {
+        let action = action.to_string();
+        match self
+            .key_bindings
+            .iter_mut()
+            .find(|binding| binding.action == action)
+        {
+            Some(binding) => binding.key = key,
+            None => self.key_bindings.push(KeyBinding { action, key }),
+        }
+    }
+
+    pub fn key_bindings(&self) -> &[KeyBinding] {
+        &self.key_bindings
+    }
+
+    /// The volume of music, including the master volume.
+    pub fn music_level(&self) -> f32 {
+        self.master_volume * self.music_volume
+    }
+
+    /// The volume of sound effects, including the master volume.
+    pub fn sfx_level(&self) -> f32 {
+        self.master_volume * self.sfx_volume
+    }
+
+    /// Adds the game's actions that aren't in these settings yet, such as
+    /// ones added since the settings were saved, and drops actions the
+    /// game no longer has.
+    fn merge_bindings(&mut self, defaults: &[KeyBinding]) {
+        let saved = std::mem::take(&mut self.key_bindings);
+        self.key_bindings = defaults
+            .iter()
+            .map(|default| {
+                saved
+                    .iter()
+                    .find(|binding| binding.action == default.action)
+                    .unwrap_or(default)
+                    .clone()
+            })
+            .collect();
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Settings {
+    /// Reads settings from a [RON](https://github.com/ron-rs/ron) file.
+    /// Missing fields keep their default values, and volumes outside 0 to
+    /// 1 are clamped.
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::Error::msg(format!("Unable to read {}: {e}", path.display())))?;
+        let mut settings: Self = ron::from_str(&source)?;
+        for volume in [
+            &mut settings.master_volume,
+            &mut settings.music_volume,
+            &mut settings.sfx_volume,
+        ] {
+            // A hand-edited file could hold anything; NaN gets the default.
+            *volume = match volume.is_nan() {
+                true => 1.0,
+                false => volume.clamp(0.0, 1.0),
+            };
+        }
+        Ok(settings)
+    }
+
+    /// Writes the settings to a RON file, creating its directory if
+    /// needed.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, text)
+            .map_err(|e| anyhow::Error::msg(format!("Unable to write {}: {e}", path.display())))
+    }
+}
+
+/// Marks a playing sound as a sound effect, so it follows the sound
+/// effect volume. [`AssetStore::play`](crate::AssetStore::play) adds it
+/// for you.
+#[derive(Component, Default)]
+pub struct SoundEffect;
+
+/// Marks a playing sound as music, so it follows the music volume.
+#[derive(Component, Default)]
+pub struct Music;
+
+/// Where the settings are saved.
+#[derive(Resource)]
+#[cfg_attr(not(feature = "serde"), allow(dead_code))]
+struct SettingsFile(Option<PathBuf>);
+
+/// `SettingsPlugin` adds the [`Settings`] resource, loads it from
+/// `settings.ron` in a folder named after the game in the user's config
+/// directory (such as `~/.config/flappy_dragon` on Linux) and applies it
+/// to the window and audio. Changes are applied straight away and saved.
+/// Settings are only saved with the `serde` feature; without it, every
+/// run starts from the defaults.
+///
+/// Add [`GameStatePlugin::with_settings_menu`](crate::GameStatePlugin::with_settings_menu)
+/// to let the player change the settings.
+///
+/// ## Example
+///
+/// ```no_run
+/// use bevy::prelude::*;
+/// use my_library::*;
+///
+/// App::new()
+///     .add_plugins(DefaultPlugins)
+///     .add_plugins(SettingsPlugin::new("flappy_dragon").with_key_binding("Flap", KeyCode::Space))
+///     .run();
+/// ```
+pub struct SettingsPlugin {
+    path: Option<PathBuf>,
+    defaults: Settings,
+}
+
+impl SettingsPlugin {
+    /// Creates the plugin, saving settings under `game_name` in the user's
+    /// config directory.
+    pub fn new(game_name: &str) -> Self {
+        Self {
+            path: config_dir().map(|directory| directory.join(game_name).join("settings.ron")),
+            defaults: Settings::default(),
+        }
+    }
+
+    /// Saves the settings to `path` instead.
+    pub fn with_path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Adds a rebindable action and its default key.
+    pub fn with_key_binding<S: ToString>(mut self, action: S, key: KeyCode) -> Self {
+        self.defaults.bind(action, key);
+        self
+    }
+
+    /// Sets the settings used when nothing has been saved yet. Their key
+    /// bindings are replaced by ones added with
+    /// [`with_key_binding`](Self::with_key_binding).
+    pub fn with_defaults(mut self, defaults: Settings) -> Self {
+        let bindings = std::mem::take(&mut self.defaults.key_bindings);
+        self.defaults = defaults;
+        self.defaults.key_bindings = bindings;
+        self
+    }
+
+    /// The settings the game starts with: the saved ones, if any, with
+    /// the game's current actions.
+    fn initial_settings(&self) -> Settings {
+        let mut settings = self
+            .path
+            .as_ref()
+            .and_then(|path| load(path))
+            .unwrap_or_else(|| self.defaults.clone());
+        settings.merge_bindings(&self.defaults.key_bindings);
+        settings
+    }
+}
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.initial_settings());
+        app.insert_resource(SettingsFile(self.path.clone()));
+        app.add_systems(
+            PostUpdate,
+            (apply_window_settings, apply_audio_settings).run_if(resource_changed::<Settings>),
+        );
+        #[cfg(feature = "serde")]
+        app.add_systems(Last, save_settings);
+        app.add_observer(start_sound_effect);
+        app.add_observer(start_music);
+    }
+}
+
+/// The user's config directory, following each platform's convention.
+pub(crate) fn config_dir() -> Option<PathBuf> {
+    let var = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty());
+    if cfg!(target_os = "windows") {
+        var("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        var("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        var("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| var("HOME").map(|home| PathBuf::from(home).join(".config")))
+    }
+}
+
+#[cfg(feature = "serde")]
+fn load(path: &Path) -> Option<Settings> {
+    if !path.exists() {
+        return None;
+    }
+    Settings::load(path)
+        .inspect_err(|error| warn!("Ignoring saved settings: {error}"))
+        .ok()
+}
+
+#[cfg(not(feature = "serde"))]
+fn load(_path: &Path) -> Option<Settings> {
+    None
+}
+
+#[cfg(feature = "serde")]
+fn save_settings(
+    settings: Res<Settings>,
+    file: Res<SettingsFile>,
+    time: Res<Time<Real>>,
+    mut exit: EventReader<AppExit>,
+    mut changed_at: Local<Option<f32>>,
+) {
+    if settings.is_changed() && !settings.is_added() {
+        *changed_at = Some(time.elapsed_secs());
+    }
+    let exiting = exit.read().count() > 0;
+    let Some(changed) = *changed_at else {
+        return;
+    };
+    if !exiting && time.elapsed_secs() - changed < SAVE_DELAY_SECS {
+        return;
+    }
+    *changed_at = None;
+    if let Some(path) = &file.0
+        && let Err(error) = settings.save(path)
+    {
+        warn!("Unable to save settings: {error}");
+    }
+}
+
+fn apply_window_settings(
+    settings: Res<Settings>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Ok(mut window) = windows.single_mut() else {
+        return;
+    };
+    window.mode = match settings.display_mode {
+        DisplayMode::Windowed => WindowMode::Windowed,
+        DisplayMode::BorderlessFullscreen => {
+            WindowMode::BorderlessFullscreen(MonitorSelection::Current)
+        }
+        DisplayMode::Fullscreen => {
+            WindowMode::Fullscreen(MonitorSelection::Current, VideoModeSelection::Current)
+        }
+    };
+    if let Some(size) = settings.resolution {
+        window.resolution.set(size.x as f32, size.y as f32);
+    }
+}
+
+fn apply_audio_settings(
+    settings: Res<Settings>,
+    mut global_volume: ResMut<GlobalVolume>,
+    mut sound_effects: Query<&mut AudioSink, (With<SoundEffect>, Without<Music>)>,
+    mut music: Query<&mut AudioSink, With<Music>>,
+) {
+    // The global volume only affects sounds as they start.
+    global_volume.volume = Volume::Linear(settings.master_volume);
+    sound_effects
+        .iter_mut()
+        .for_each(|mut sink| sink.set_volume(Volume::Linear(settings.sfx_level())));
+    music
+        .iter_mut()
+        .for_each(|mut sink| sink.set_volume(Volume::Linear(settings.music_level())));
+}
+
+fn start_sound_effect(
+    trigger: Trigger<OnAdd, SoundEffect>,
+    settings: Res<Settings>,
+    mut playback: Query<&mut PlaybackSettings>,
+) {
+    if let Ok(mut playback) = playback.get_mut(trigger.target()) {
+        playback.volume = Volume::Linear(settings.sfx_volume);
+    }
+}
+
+fn start_music(
+    trigger: Trigger<OnAdd, Music>,
+    settings: Res<Settings>,
+    mut playback: Query<&mut PlaybackSettings>,
+) {
+    if let Ok(mut playback) = playback.get_mut(trigger.target()) {
+        playback.volume = Volume::Linear(settings.music_volume);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_key_bindings() {
+        let plugin = SettingsPlugin::new("test")
+            .with_path(
+                std::env::temp_dir()
+                    .join(format!("my_library_bindings_{}", std::process::id()))
+                    .join("settings.ron"),
+            )
+            .with_key_binding("Flap", KeyCode::Space)
+            .with_key_binding("Dive", KeyCode::KeyS);
+        let mut settings = plugin.initial_settings();
+        assert_eq!(settings.key("Flap"), KeyCode::Space);
+        settings.bind("Flap", KeyCode::ArrowUp);
+        assert_eq!(settings.key("Flap"), KeyCode::ArrowUp);
+        assert_eq!(settings.key_bindings().len(), 2);
+
+        // Saved bindings are kept, new actions get their default key and
+        // old ones are dropped.
+        settings.bind("Roll", KeyCode::KeyR);
+        settings.merge_bindings(&[
+            KeyBinding {
+                action: "Flap".to_string(),
+                key: KeyCode::Space,
+            },
+            KeyBinding {
+                action: "Shoot".to_string(),
+                key: KeyCode::KeyX,
+            },
+        ]);
+        assert_eq!(settings.key("Flap"), KeyCode::ArrowUp);
+        assert_eq!(settings.key("Shoot"), KeyCode::KeyX);
+        assert_eq!(settings.key_bindings().len(), 2);
+    }
+
+    #[test]
+    fn test_volume_levels() {
+        let settings = Settings {
+            master_volume: 0.5,
+            music_volume: 0.5,
+            sfx_volume: 1.0,
+            ..default()
+        };
+        assert_eq!(settings.music_level(), 0.25);
+        assert_eq!(settings.sfx_level(), 0.5);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir()
+            .join(format!("my_library_settings_{}", std::process::id()))
+            .join("settings.ron");
+        let plugin = SettingsPlugin::new("test")
+            .with_path(&path)
+            .with_key_binding("Flap", KeyCode::Space);
+        assert_eq!(plugin.initial_settings().master_volume, 1.0);
+
+        let mut settings = plugin.initial_settings();
+        settings.master_volume = 0.3;
+        settings.display_mode = DisplayMode::BorderlessFullscreen;
+        settings.resolution = Some(UVec2::new(1280, 720));
+        settings.bind("Flap", KeyCode::KeyF);
+        settings.save(&path).unwrap();
+        assert_eq!(plugin.initial_settings(), settings);
+
+        // Volumes out of range are clamped.
+        std::fs::write(
+            &path,
+            "(master_volume: 4.0, music_volume: -1.0, sfx_volume: NaN)",
+        )
+        .unwrap();
+        let loaded = plugin.initial_settings();
+        assert_eq!(
+            (loaded.master_volume, loaded.music_volume, loaded.sfx_volume),
+            (1.0, 0.0, 1.0)
+        );
+
+        // Broken files are ignored.
+        std::fs::write(&path, "not settings").unwrap();
+        assert_eq!(plugin.initial_settings().master_volume, 1.0);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,356 @@
+use super::{
+    DisplayMode, MenuResource, Settings,
+    game_menus::{CHOOSE_KEYS, MenuElement, MenuFocus, just_pressed, move_focus},
+    settings::RESOLUTIONS,
+};
+use bevy::prelude::*;
+use bevy::state::state::FreelyMutableState;
+use bevy_egui::{EguiContexts, egui};
+
+/// The action waiting for a new key, if the player is rebinding one.
+#[derive(Resource, Default)]
+pub(crate) struct Rebinding(Option<String>);
+
+/// A row of the options menu that can be focused.
+#[derive(Clone, Copy, PartialEq)]
+enum Row {
+    Volume(usize),
+    DisplayMode,
+    Resolution,
+    Binding(usize),
+    Back,
+}
+
+const VOLUMES: [&str; 3] = ["Master volume", "Music volume", "Sound effects volume"];
+
+/// How far the left and right keys move a volume.
+const VOLUME_STEP: f32 = 0.1;
+
+pub(crate) fn setup(mut commands: Commands) {
+    commands.insert_resource(MenuFocus::default());
+    commands.insert_resource(Rebinding::default());
+    commands.spawn(Camera2d).insert(MenuElement);
+}
+
+/// The rows that can be focused, top to bottom. The resolution can only
+/// be changed in a window.
+fn rows(settings: &Settings) -> Vec<Row> {
+    let mut rows: Vec<Row> = (0..VOLUMES.len()).map(Row::Volume).collect();
+    rows.push(Row::DisplayMode);
+    if settings.display_mode == DisplayMode::Windowed {
+        rows.push(Row::Resolution);
+    }
+    rows.extend((0..settings.key_bindings().len()).map(Row::Binding));
+    rows.push(Row::Back);
+    rows
+}
+
+fn focused(rows: &[Row], focus: &MenuFocus) -> Row {
+    rows[focus.0.min(rows.len() - 1)]
+}
+
+fn volume_mut(settings: &mut Settings, index: usize) -> &mut f32 {
+    match index {
+        0 => &mut settings.master_volume,
+        1 => &mut settings.music_volume,
+        _ => &mut settings.sfx_volume,
+    }
+}
+
+/// The option `step` places away from `current`, wrapping around.
+fn cycle<O: Copy + PartialEq>(options: &[O], current: O, step: isize) -> O {
+    let index = options
+        .iter()
+        .position(|option| *option == current)
+        .unwrap_or(0);
+    options[(index as isize + step).rem_euclid(options.len() as isize) as usize]
+}
+
+/// Handles the keyboard and gamepad: the arrow keys or D-pad move between
+/// rows and change the focused setting, Enter, Space or South choose the
+/// focused button, and Escape or East return to the main menu.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn run<T>(
+    mut settings: ResMut<Settings>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut state: ResMut<NextState<T>>,
+    menu_resource: Res<MenuResource<T>>,
+    mut focus: ResMut<MenuFocus>,
+    mut rebinding: ResMut<Rebinding>,
+) where
+    T: States + FromWorld + FreelyMutableState,
+{
+    // Edit a copy, so the settings are only marked as changed (and
+    // saved) when something really changes.
+    let mut edited = settings.clone();
+    let mut back = false;
+
+    let pressed = |keys: &[KeyCode], button| just_pressed(&keyboard, &gamepads, keys, button);
+
+    if let Some(action) = rebinding.0.clone() {
+        if let Some(key) = keyboard.get_just_pressed().next() {
+            // Escape cancels.
+            if *key != KeyCode::Escape {
+                edited.bind(action, *key);
+            }
+            rebinding.0 = None;
+        }
+    } else if pressed(&[KeyCode::Escape], GamepadButton::East) {
+        back = true;
+    } else {
+        let rows = rows(&edited);
+        move_focus(&mut focus, rows.len(), &keyboard, &gamepads);
+        let chose = pressed(&CHOOSE_KEYS, GamepadButton::South);
+        let step = match (
+            pressed(&[KeyCode::ArrowLeft], GamepadButton::DPadLeft),
+            pressed(&[KeyCode::ArrowRight], GamepadButton::DPadRight),
+        ) {
+            (true, false) => -1,
+            (false, true) => 1,
+            _ => 0,
+        };
+        // Choosing a setting with a list of options moves to the next one.
+        let change = match chose && step == 0 {
+            true => 1,
+            false => step,
+        };
+        match focused(&rows, &focus) {
+            Row::Volume(index) if step != 0 => {
+                let volume = volume_mut(&mut edited, index);
+                // Round to a step, so that steps stay on round numbers.
+                *volume = ((*volume / VOLUME_STEP).round() + step as f32) * VOLUME_STEP;
+                *volume = volume.clamp(0.0, 1.0);
+            }
+            Row::DisplayMode if change != 0 => {
+                edited.display_mode = cycle(&DisplayMode::ALL, edited.display_mode, change);
+            }
+            Row::Resolution if change != 0 => {
+                let resolutions: Vec<Option<UVec2>> =
+                    std::iter::once(None).chain(RESOLUTIONS.map(Some)).collect();
+                edited.resolution = cycle(&resolutions, edited.resolution, change);
+            }
+            Row::Binding(index) if chose => {
+                rebinding.0 = Some(edited.key_bindings()[index].action.clone());
+            }
+            Row::Back if chose => back = true,
+            _ => {}
+        }
+    }
+
+    if edited != *settings {
+        *settings = edited;
+    }
+    if back {
+        rebinding.0 = None;
+        state.set(menu_resource.menu_state.clone());
+    }
+}
+
+/// Draws the options menu, with the focused row highlighted.
+pub(crate) fn show<T>(
+    mut egui_context: EguiContexts,
+    mut settings: ResMut<Settings>,
+    mut state: ResMut<NextState<T>>,
+    menu_resource: Res<MenuResource<T>>,
+    mut focus: ResMut<MenuFocus>,
+    mut rebinding: ResMut<Rebinding>,
+) where
+    T: States + FromWorld + FreelyMutableState,
+{
+    let mut edited = settings.clone();
+    let mut back = false;
+    let rows = rows(&edited);
+    let current = focused(&rows, &focus);
+
+    egui::CentralPanel::default().show(egui_context.ctx_mut(), |ui| {
+        // Draws a row's label, moving the focus to it when the mouse does.
+        let mut label = |ui: &mut egui::Ui, row: Row, text: &str| {
+            let label = ui.selectable_label(current == row, text);
+            if label.hovered()
+                && ui.input(|input| input.pointer.is_moving())
+                && let Some(index) = rows.iter().position(|other| *other == row)
+            {
+                focus.0 = index;
+            }
+        };
+        ui.vertical_centered(|ui| {
+            ui.heading("Options");
+        });
+        ui.add_space(16.0);
+        egui::Grid::new("settings")
+            .num_columns(2)
+            .spacing([24.0, 8.0])
+            .show(ui, |ui| {
+                ui.strong("Audio");
+                ui.end_row();
+                for (index, text) in VOLUMES.iter().enumerate() {
+                    label(ui, Row::Volume(index), text);
+                    ui.add(
+                        egui::Slider::new(volume_mut(&mut edited, index), 0.0..=1.0)
+                            .show_value(false),
+                    );
+                    ui.end_row();
+                }
+
+                ui.strong("Display");
+                ui.end_row();
+                label(ui, Row::DisplayMode, "Window mode");
+                egui::ComboBox::from_id_salt("display_mode")
+                    .selected_text(edited.display_mode.label())
+                    .show_ui(ui, |ui| {
+                        for mode in DisplayMode::ALL {
+                            ui.selectable_value(&mut edited.display_mode, mode, mode.label());
+                        }
+                    });
+                ui.end_row();
+                label(ui, Row::Resolution, "Resolution");
+                ui.add_enabled_ui(edited.display_mode == DisplayMode::Windowed, |ui| {
+                    egui::ComboBox::from_id_salt("resolution")
+                        .selected_text(resolution_label(edited.resolution))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(
+                                &mut edited.resolution,
+                                None,
+                                resolution_label(None),
+                            );
+                            for size in RESOLUTIONS {
+                                ui.selectable_value(
+                                    &mut edited.resolution,
+                                    Some(size),
+                                    resolution_label(Some(size)),
+                                );
+                            }
+                        });
+                });
+                ui.end_row();
+
+                if !edited.key_bindings().is_empty() {
+                    ui.strong("Controls");
+                    ui.end_row();
+                }
+                for (index, binding) in settings.key_bindings().iter().enumerate() {
+                    label(ui, Row::Binding(index), &binding.action);
+                    let waiting = rebinding.0.as_ref() == Some(&binding.action);
+                    let text = match waiting {
+                        true => "Press a key...".to_string(),
+                        false => format!("{:?}", edited.key(&binding.action)),
+                    };
+                    if ui.button(text).clicked() {
+                        rebinding.0 = Some(binding.action.clone());
+                    }
+                    ui.end_row();
+                }
+            });
+        ui.add_space(16.0);
+        let button = ui.add(egui::Button::new("Back").selected(current == Row::Back));
+        if button.hovered() && ui.input(|input| input.pointer.is_moving()) {
+            focus.0 = rows.len() - 1;
+        }
+        if button.clicked() {
+            back = true;
+        }
+    });
+
+    if edited != *settings {
+        *settings = edited;
+    }
+    if back {
+        rebinding.0 = None;
+        state.set(menu_resource.menu_state.clone());
+    }
+}
+
+fn resolution_label(resolution: Option<UVec2>) -> String {
+    match resolution {
+        Some(size) => format!("{} x {}", size.x, size.y),
+        None => "Game default".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::GameStatePlugin;
+    use bevy::state::app::StatesPlugin;
+
+    #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default, States)]
+    enum Phase {
+        #[default]
+        Loading,
+        MainMenu,
+        Options,
+        Playing,
+        GameOver,
+    }
+
+    fn app() -> App {
+        let plugin = GameStatePlugin::new(Phase::MainMenu, Phase::Playing, Phase::GameOver)
+            .with_settings_menu(Phase::Options);
+        let mut settings = Settings::default();
+        settings.bind("Flap", KeyCode::Space);
+        let mut app = App::new();
+        app.add_plugins(StatesPlugin)
+            .insert_state(Phase::Options)
+            .insert_resource(plugin.menu_resource())
+            .insert_resource(settings)
+            .init_resource::<MenuFocus>()
+            .init_resource::<Rebinding>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .add_systems(Update, run::<Phase>);
+        app
+    }
+
+    fn press(app: &mut App, key: KeyCode) {
+        let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keyboard.reset_all();
+        keyboard.press(key);
+        app.update();
+    }
+
+    fn settings(app: &App) -> &Settings {
+        app.world().resource::<Settings>()
+    }
+
+    #[test]
+    fn test_keyboard_navigation() {
+        let mut app = app();
+        // The master volume has the focus first.
+        press(&mut app, KeyCode::ArrowLeft);
+        press(&mut app, KeyCode::ArrowLeft);
+        assert!((settings(&app).master_volume - 0.8).abs() < 1e-6);
+        press(&mut app, KeyCode::ArrowRight);
+        press(&mut app, KeyCode::ArrowRight);
+        press(&mut app, KeyCode::ArrowRight);
+        assert_eq!(settings(&app).master_volume, 1.0);
+
+        // Down to the window mode, then back through the list of modes.
+        for _ in 0..3 {
+            press(&mut app, KeyCode::ArrowDown);
+        }
+        press(&mut app, KeyCode::ArrowLeft);
+        assert_eq!(settings(&app).display_mode, DisplayMode::Fullscreen);
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(settings(&app).display_mode, DisplayMode::Windowed);
+
+        // Rebind "Flap", below the resolution.
+        press(&mut app, KeyCode::ArrowDown);
+        press(&mut app, KeyCode::ArrowDown);
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::KeyF);
+        assert_eq!(settings(&app).key("Flap"), KeyCode::KeyF);
+
+        // Up from the top wraps around to "Back".
+        for _ in 0..5 {
+            press(&mut app, KeyCode::ArrowUp);
+        }
+        assert_eq!(app.world().resource::<MenuFocus>().0, 0);
+        press(&mut app, KeyCode::ArrowUp);
+        press(&mut app, KeyCode::Space);
+        app.update();
+        assert_eq!(
+            *app.world().resource::<State<Phase>>().get(),
+            Phase::MainMenu
+        );
+    }
+}
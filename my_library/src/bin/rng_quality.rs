@@ -0,0 +1,53 @@
+//! Runs statistical tests against every random number generation backend
+//! and prints a pass/fail report.
+//!
+//! ```text
+//! cargo run -p my_library --release --bin rng_quality -- --samples 1000000 --seed 42
+//! ```
+use my_library::{BackendKind, random_quality::check_backend};
+
+/// Results with a p-value below this are reported as failures.
+const SIGNIFICANCE: f64 = 0.001;
+
+fn main() -> anyhow::Result<()> {
+    let mut samples = 1_000_000;
+    let mut seed: u64 = rand::random();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| anyhow::Error::msg(format!("Missing value for {arg}")))
+        };
+        match arg.as_str() {
+            "--samples" => samples = value()?.parse()?,
+            "--seed" => seed = value()?.parse()?,
+            _ => anyhow::bail!("Unknown argument {arg}, expected --samples or --seed"),
+        }
+    }
+
+    println!("Testing {samples} samples per method, seed {seed}, significance {SIGNIFICANCE}");
+    let mut failures = 0;
+    let mut total = 0;
+    for backend in BackendKind::ALL {
+        println!();
+        for check in check_backend(*backend, seed, samples) {
+            let passed = check.passed(SIGNIFICANCE);
+            println!("{}  {check}", if passed { "PASS" } else { "FAIL" });
+            total += 1;
+            if !passed {
+                failures += 1;
+            }
+        }
+    }
+
+    println!();
+    println!("{failures} of {total} tests failed.");
+    println!(
+        "A perfect generator fails each test with probability {SIGNIFICANCE}; \
+        re-run with another seed before suspecting a backend."
+    );
+    if failures > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -0,0 +1,225 @@
+use rand::Rng;
+
+/// `ChanceAccumulator` implements a "pseudo-random distribution" (PRD):
+/// each miss makes the next attempt more likely to succeed, and a success
+/// resets the chance. Over many attempts it succeeds as often as its
+/// nominal probability, but long losing (and winning) streaks become rare.
+///
+/// After `n` misses in a row the chance of success is `(n + 1) * c`, where
+/// the constant `c` is chosen to give the nominal probability on average.
+///
+/// With the `serde` feature enabled, accumulators (including the current
+/// streak) can be saved and loaded.
+///
+/// ## Example
+///
+/// ```
+/// use my_library::*;
+/// let mut rng = RandomNumberGenerator::new();
+/// // A critical hit one time in four, on average
+/// let mut critical = ChanceAccumulator::new(0.25);
+/// let hits = (0..1000).filter(|_| rng.chance(&mut critical)).count();
+/// println!("{hits} critical hits");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "SavedAccumulator")
+)]
+pub struct ChanceAccumulator {
+    probability: f64,
+    step: f64,
+    misses: u32,
+}
+
+impl ChanceAccumulator {
+    /// Creates an accumulator that succeeds with the specified average
+    /// probability.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `probability` is between 0 and 1.
+    pub fn new(probability: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&probability),
+            "Chance must be between 0 and 1, found {probability}"
+        );
+        Self {
+            probability,
+            step: prd_step(probability),
+            misses: 0,
+        }
+    }
+
+    /// The average probability of success.
+    pub fn probability(&self) -> f64 {
+        self.probability
+    }
+
+    /// The probability that the next attempt succeeds.
+    pub fn current_chance(&self) -> f64 {
+        (self.step * (self.misses + 1) as f64).min(1.0)
+    }
+
+    /// The number of misses since the last success.
+    pub fn misses(&self) -> u32 {
+        self.misses
+    }
+
+    /// Forgets the current streak of misses.
+    pub fn reset(&mut self) {
+        self.misses = 0;
+    }
+
+    /// Makes an attempt, returning `true` on success.
+    pub(crate) fn attempt_with<R: Rng>(&mut self, rng: &mut R) -> bool {
+        let success = rng.random::<f64>() < self.current_chance();
+        if success {
+            self.misses = 0;
+        } else {
+            self.misses = self.misses.saturating_add(1);
+        }
+        success
+    }
+}
+
+/// The saved form of a [`ChanceAccumulator`], checked before it is used.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct SavedAccumulator {
+    probability: f64,
+    step: f64,
+    misses: u32,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<SavedAccumulator> for ChanceAccumulator {
+    type Error = anyhow::Error;
+
+    fn try_from(saved: SavedAccumulator) -> Result<Self, Self::Error> {
+        if !(0.0..=1.0).contains(&saved.probability) {
+            anyhow::bail!(
+                "Chance must be between 0 and 1, found {}",
+                saved.probability
+            );
+        }
+        // The step never exceeds the average probability.
+        if !(0.0..=saved.probability).contains(&saved.step) {
+            anyhow::bail!(
+                "Step {} is out of range for a chance of {}",
+                saved.step,
+                saved.probability
+            );
+        }
+        Ok(Self {
+            probability: saved.probability,
+            step: saved.step,
+            misses: saved.misses,
+        })
+    }
+}
+
+/// The average success rate when the chance grows by `step` per attempt.
+fn average_rate(step: f64) -> f64 {
+    // Expected attempts until success: the sum of n * P(first success at n)
+    let mut expected_attempts = 0.0;
+    let mut no_success_yet = 1.0;
+    let mut n = 1.0;
+    loop {
+        let chance = (step * n).min(1.0);
+        expected_attempts += n * no_success_yet * chance;
+        no_success_yet *= 1.0 - chance;
+        if chance >= 1.0 || no_success_yet < 1e-12 {
+            break;
+        }
+        n += 1.0;
+    }
+    1.0 / expected_attempts
+}
+
+/// Finds the step giving an average success rate of `probability`, by
+/// bisection (the rate increases with the step).
+fn prd_step(probability: f64) -> f64 {
+    if probability <= 0.0 || probability >= 1.0 {
+        return probability;
+    }
+    let (mut low, mut high) = (0.0, probability);
+    for _ in 0..64 {
+        let middle = (low + high) / 2.0;
+        if average_rate(middle) < probability {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    (low + high) / 2.0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn rng() -> rand::rngs::StdRng {
+        rand::rngs::StdRng::seed_from_u64(1)
+    }
+
+    #[test]
+    fn test_known_constant() {
+        // The well-known PRD constant for a 25% chance is about 0.0847
+        assert!((ChanceAccumulator::new(0.25).step - 0.0847).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_average() {
+        for probability in [0.05, 0.25, 0.5, 0.9] {
+            let mut chance = ChanceAccumulator::new(probability);
+            let mut rng = rng();
+            let hits = (0..100_000)
+                .filter(|_| chance.attempt_with(&mut rng))
+                .count();
+            let rate = hits as f64 / 100_000.0;
+            assert!((rate - probability).abs() < 0.01, "{probability}: {rate}");
+        }
+    }
+
+    #[test]
+    fn test_streaks_are_bounded() {
+        // With a 25% chance, 1 / 0.0847 means success by the 12th attempt
+        let mut chance = ChanceAccumulator::new(0.25);
+        let mut rng = rng();
+        for _ in 0..10_000 {
+            chance.attempt_with(&mut rng);
+            assert!(chance.misses() < 12);
+        }
+    }
+
+    #[test]
+    fn test_extremes() {
+        let mut rng = rng();
+        assert!(!ChanceAccumulator::new(0.0).attempt_with(&mut rng));
+        assert!(ChanceAccumulator::new(1.0).attempt_with(&mut rng));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let mut chance = ChanceAccumulator::new(0.25);
+        chance.misses = 3;
+        let text = ron::to_string(&chance).unwrap();
+        let loaded: ChanceAccumulator = ron::from_str(&text).unwrap();
+        assert_eq!(loaded, chance);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_rejects_bad_chances() {
+        let too_likely = "(probability: 1.5, step: 0.5, misses: 0)";
+        assert!(ron::from_str::<ChanceAccumulator>(too_likely).is_err());
+        let not_a_number = "(probability: NaN, step: 0.1, misses: 0)";
+        assert!(ron::from_str::<ChanceAccumulator>(not_a_number).is_err());
+        let big_step = "(probability: 0.25, step: 0.5, misses: 0)";
+        assert!(ron::from_str::<ChanceAccumulator>(big_step).is_err());
+    }
+}
@@ -0,0 +1,520 @@
+use std::{fmt, str::FromStr};
+
+/// The largest number of dice a single group may roll (e.g. `1000d6`).
+const MAX_DICE: u32 = 1_000;
+
+/// How many times a single exploding die may re-roll. This prevents
+/// expressions such as `1d1!` from looping forever.
+pub(crate) const MAX_EXPLOSIONS: u32 = 100;
+
+/// Selects which dice from a group count towards the total.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiceSelection {
+    /// Keep the `n` highest dice (`kh` or `k`).
+    KeepHighest(u32),
+    /// Keep the `n` lowest dice (`kl`).
+    KeepLowest(u32),
+    /// Discard the `n` highest dice (`dh`).
+    DropHighest(u32),
+    /// Discard the `n` lowest dice (`dl`).
+    DropLowest(u32),
+}
+
+/// A group of identical dice, such as the `4d6dl1` in `4d6dl1+2`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DiceGroup {
+    /// The number of dice to roll.
+    pub count: u32,
+    /// The number of sides on each die. `d%` is stored as 100.
+    pub sides: u32,
+    /// If set, every die that rolls its maximum value is rolled again
+    /// and the extra die is added to the group (`!`).
+    pub exploding: bool,
+    /// Optional keep/drop rule applied after all dice are rolled.
+    pub selection: Option<DiceSelection>,
+}
+
+/// One `+`/`-` separated term of a [`DiceExpr`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiceTerm {
+    /// A group of dice; `negative` is set if the group is subtracted.
+    Dice { group: DiceGroup, negative: bool },
+    /// A fixed modifier, carrying its own sign.
+    Constant(i64),
+}
+
+/// `DiceExpr` is a parsed dice expression in common tabletop notation.
+///
+/// Supported syntax:
+///
+/// * `NdS` - roll `N` dice with `S` sides. `N` defaults to 1 (`d20`).
+/// * `d%` - a percentile die (1 to 100).
+/// * `!` - exploding dice: a die showing its maximum is rolled again.
+/// * `khN`/`kN`, `klN` - keep the `N` highest/lowest dice.
+/// * `dhN`, `dlN` - drop the `N` highest/lowest dice.
+/// * `+`/`-` - add or subtract further dice groups or constants.
+///
+/// ## Example
+///
+/// ```
+/// use my_library::*;
+/// let expr: DiceExpr = "4d6dl1+2".parse().unwrap();
+/// let mut rng = RandomNumberGenerator::seeded(1);
+/// let roll = rng.roll(&expr);
+/// assert!(roll.total >= 5 && roll.total <= 20);
+/// assert_eq!(roll.dice.len(), 4);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DiceExpr {
+    terms: Vec<DiceTerm>,
+}
+
+/// The result of rolling a single die.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DieRoll {
+    /// The number of sides on the die.
+    pub sides: u32,
+    /// The face that was rolled.
+    pub value: u32,
+    /// `false` if the die was discarded by a keep/drop rule.
+    pub kept: bool,
+}
+
+/// The result of rolling a [`DiceExpr`]: the total, and every die that
+/// was rolled along the way (including exploded and dropped dice).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiceRoll {
+    /// The result: the kept dice and constants, added up.
+    pub total: i64,
+    /// Every die rolled, in the order they were rolled.
+    pub dice: Vec<DieRoll>,
+}
+
+impl DiceRoll {
+    /// Iterates the dice that counted towards the total.
+    pub fn kept(&self) -> impl Iterator<Item = &DieRoll> {
+        self.dice.iter().filter(|die| die.kept)
+    }
+}
+
+impl DiceExpr {
+    /// The terms that make up the expression, in order.
+    pub fn terms(&self) -> &[DiceTerm] {
+        &self.terms
+    }
+
+    /// Rolls the expression, calling `roll_die(sides)` to obtain each
+    /// individual die result (in the range `1..=sides`).
+    pub(crate) fn roll_with(&self, mut roll_die: impl FnMut(u32) -> u32) -> DiceRoll {
+        let mut total = 0;
+        let mut dice = Vec::new();
+        for term in self.terms.iter() {
+            match term {
+                DiceTerm::Constant(n) => total += n,
+                DiceTerm::Dice { group, negative } => {
+                    let sum = group.roll_with(&mut roll_die, &mut dice);
+                    total += if *negative { -sum } else { sum };
+                }
+            }
+        }
+        DiceRoll { total, dice }
+    }
+}
+
+impl DiceGroup {
+    fn roll_with(&self, roll_die: &mut impl FnMut(u32) -> u32, out: &mut Vec<DieRoll>) -> i64 {
+        let first = out.len();
+        for _ in 0..self.count {
+            let mut value = roll_die(self.sides);
+            out.push(DieRoll {
+                sides: self.sides,
+                value,
+                kept: true,
+            });
+            let mut explosions = 0;
+            while self.exploding && value == self.sides && explosions < MAX_EXPLOSIONS {
+                value = roll_die(self.sides);
+                out.push(DieRoll {
+                    sides: self.sides,
+                    value,
+                    kept: true,
+                });
+                explosions += 1;
+            }
+        }
+
+        let rolled = &mut out[first..];
+        if let Some(selection) = self.selection {
+            // Order the dice from lowest to highest, so we can mark
+            // the discarded ones without disturbing roll order.
+            let mut order: Vec<usize> = (0..rolled.len()).collect();
+            order.sort_by_key(|i| rolled[*i].value);
+            let n = rolled.len();
+            let discard = match selection {
+                DiceSelection::KeepHighest(k) => 0..n.saturating_sub(k as usize),
+                DiceSelection::KeepLowest(k) => (k as usize).min(n)..n,
+                DiceSelection::DropHighest(d) => n.saturating_sub(d as usize)..n,
+                DiceSelection::DropLowest(d) => 0..(d as usize).min(n),
+            };
+            for i in discard {
+                rolled[order[i]].kept = false;
+            }
+        }
+
+        rolled
+            .iter()
+            .filter(|die| die.kept)
+            .map(|die| die.value as i64)
+            .sum()
+    }
+}
+
+impl FromStr for DiceExpr {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Parser::new(s).parse()
+    }
+}
+
+impl fmt::Display for DiceSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiceSelection::KeepHighest(n) => write!(f, "kh{n}"),
+            DiceSelection::KeepLowest(n) => write!(f, "kl{n}"),
+            DiceSelection::DropHighest(n) => write!(f, "dh{n}"),
+            DiceSelection::DropLowest(n) => write!(f, "dl{n}"),
+        }
+    }
+}
+
+impl fmt::Display for DiceGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}d{}", self.count, self.sides)?;
+        if self.exploding {
+            write!(f, "!")?;
+        }
+        if let Some(selection) = self.selection {
+            write!(f, "{selection}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for DiceExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, term) in self.terms.iter().enumerate() {
+            let (negative, text) = match term {
+                DiceTerm::Dice { group, negative } => (*negative, group.to_string()),
+                DiceTerm::Constant(n) => (*n < 0, n.unsigned_abs().to_string()),
+            };
+            match (i, negative) {
+                (0, false) => {}
+                (0, true) => write!(f, "-")?,
+                (_, false) => write!(f, "+")?,
+                (_, true) => write!(f, "-")?,
+            }
+            write!(f, "{text}")?;
+        }
+        Ok(())
+    }
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    chars: Vec<char>,
+    /// Where each of `chars` is in `source`, counted in characters, since
+    /// whitespace is skipped.
+    positions: Vec<usize>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> Self {
+        let (positions, chars) = source
+            .chars()
+            .enumerate()
+            .filter(|(_, c)| !c.is_whitespace())
+            .map(|(i, c)| (i, c.to_ascii_lowercase()))
+            .unzip();
+        Self {
+            source,
+            chars,
+            positions,
+            pos: 0,
+        }
+    }
+
+    fn error(&self, message: &str) -> anyhow::Error {
+        let position = match self.positions.get(self.pos) {
+            Some(position) => *position,
+            None => self.source.chars().count(),
+        };
+        anyhow::Error::msg(format!(
+            "Invalid dice expression `{}`: {message} at position {position}",
+            self.source
+        ))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn number(&mut self) -> Option<anyhow::Result<u32>> {
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return None;
+        }
+        let digits: String = self.chars[start..self.pos].iter().collect();
+        Some(
+            digits
+                .parse()
+                .map_err(|_| self.error(&format!("`{digits}` is too large"))),
+        )
+    }
+
+    fn parse(mut self) -> anyhow::Result<DiceExpr> {
+        if self.chars.is_empty() {
+            return Err(self.error("expression is empty"));
+        }
+        let mut terms = Vec::new();
+        let mut negative = self.eat('-');
+        if !negative {
+            self.eat('+');
+        }
+        loop {
+            terms.push(self.term(negative)?);
+            if self.eat('+') {
+                negative = false;
+            } else if self.eat('-') {
+                negative = true;
+            } else if let Some(c) = self.peek() {
+                return Err(self.error(&format!("unexpected `{c}`")));
+            } else {
+                break;
+            }
+        }
+        Ok(DiceExpr { terms })
+    }
+
+    fn term(&mut self, negative: bool) -> anyhow::Result<DiceTerm> {
+        let count = self.number().transpose()?;
+        if !self.eat('d') {
+            return match count {
+                Some(n) => Ok(DiceTerm::Constant(if negative {
+                    -(n as i64)
+                } else {
+                    n as i64
+                })),
+                None => Err(self.error("expected a number or dice")),
+            };
+        }
+
+        let count = count.unwrap_or(1);
+        if count == 0 {
+            return Err(self.error("cannot roll zero dice"));
+        }
+        if count > MAX_DICE {
+            return Err(self.error(&format!("cannot roll more than {MAX_DICE} dice")));
+        }
+        let sides = if self.eat('%') {
+            100
+        } else {
+            match self.number().transpose()? {
+                Some(0) => return Err(self.error("dice must have at least one side")),
+                Some(sides) => sides,
+                None => return Err(self.error("expected number of sides")),
+            }
+        };
+
+        let mut group = DiceGroup {
+            count,
+            sides,
+            exploding: false,
+            selection: None,
+        };
+        loop {
+            if self.eat('!') {
+                if group.exploding {
+                    return Err(self.error("dice can only explode once"));
+                }
+                if sides == 1 {
+                    return Err(self.error("a one-sided die cannot explode"));
+                }
+                group.exploding = true;
+            } else if let Some(selection) = self.selection(count)? {
+                if group.selection.is_some() {
+                    return Err(self.error("only one keep or drop rule is allowed"));
+                }
+                group.selection = Some(selection);
+            } else {
+                break;
+            }
+        }
+        Ok(DiceTerm::Dice { group, negative })
+    }
+
+    fn selection(&mut self, count: u32) -> anyhow::Result<Option<DiceSelection>> {
+        let selection: fn(u32) -> DiceSelection = match (self.peek(), self.chars.get(self.pos + 1))
+        {
+            (Some('k'), Some('h')) => DiceSelection::KeepHighest,
+            (Some('k'), Some('l')) => DiceSelection::KeepLowest,
+            (Some('d'), Some('h')) => DiceSelection::DropHighest,
+            (Some('d'), Some('l')) => DiceSelection::DropLowest,
+            (Some('k'), _) => {
+                // Plain `k` is shorthand for `kh`
+                self.pos += 1;
+                return self.selection_amount(count, DiceSelection::KeepHighest);
+            }
+            _ => return Ok(None),
+        };
+        self.pos += 2;
+        self.selection_amount(count, selection)
+    }
+
+    fn selection_amount(
+        &mut self,
+        count: u32,
+        selection: fn(u32) -> DiceSelection,
+    ) -> anyhow::Result<Option<DiceSelection>> {
+        let n = match self.number().transpose()? {
+            Some(n) => n,
+            None => return Err(self.error("expected number of dice to keep or drop")),
+        };
+        let selection = selection(n);
+        match selection {
+            DiceSelection::KeepHighest(n) | DiceSelection::KeepLowest(n) if n == 0 => {
+                Err(self.error("must keep at least one die"))
+            }
+            DiceSelection::DropHighest(n) | DiceSelection::DropLowest(n) if n >= count => {
+                Err(self.error(&format!("cannot drop {n} of {count} dice")))
+            }
+            _ => Ok(Some(selection)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn group(expr: &str) -> DiceGroup {
+        match expr.parse::<DiceExpr>().unwrap().terms()[0] {
+            DiceTerm::Dice { group, .. } => group,
+            DiceTerm::Constant(_) => panic!("Expected dice"),
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let expr: DiceExpr = "3d6+2".parse().unwrap();
+        assert_eq!(
+            expr.terms(),
+            &[
+                DiceTerm::Dice {
+                    group: DiceGroup {
+                        count: 3,
+                        sides: 6,
+                        exploding: false,
+                        selection: None,
+                    },
+                    negative: false,
+                },
+                DiceTerm::Constant(2),
+            ]
+        );
+        assert_eq!(group("d%").sides, 100);
+        assert_eq!(group("d20").count, 1);
+        assert_eq!(
+            group("2d20kh1").selection,
+            Some(DiceSelection::KeepHighest(1))
+        );
+        assert_eq!(
+            group("2d20k1").selection,
+            Some(DiceSelection::KeepHighest(1))
+        );
+        assert_eq!(
+            group("4d6dl1").selection,
+            Some(DiceSelection::DropLowest(1))
+        );
+        assert!(group("3d6!").exploding);
+        assert!(group("4D6 ! KL2").exploding);
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for text in ["3d6+2", "1d20kh1-1d4", "-2+1d100", "4d6!dl1"] {
+            let expr: DiceExpr = text.parse().unwrap();
+            assert_eq!(expr.to_string(), text);
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for bad in [
+            "",
+            "d",
+            "3d",
+            "0d6",
+            "3d0",
+            "3d6+",
+            "3x6",
+            "2d6dl2",
+            "2d6kh0",
+            "1d1!",
+            "3d6kh1kl1",
+        ] {
+            assert!(bad.parse::<DiceExpr>().is_err(), "{bad} should not parse");
+        }
+        let err = "3d6+x".parse::<DiceExpr>().unwrap_err();
+        assert!(err.to_string().contains("position 4"));
+        // Positions count the whitespace that was skipped.
+        let err = " 3d6 + x".parse::<DiceExpr>().unwrap_err();
+        assert!(err.to_string().contains("position 7"), "{err}");
+        let err = "3d6 + ".parse::<DiceExpr>().unwrap_err();
+        assert!(err.to_string().contains("position 6"), "{err}");
+    }
+
+    #[test]
+    fn test_keep_and_drop() {
+        let mut faces = [1, 6, 3, 4].into_iter();
+        let roll = "4d6dl1"
+            .parse::<DiceExpr>()
+            .unwrap()
+            .roll_with(|_| faces.next().unwrap());
+        assert_eq!(roll.total, 13);
+        assert_eq!(roll.dice.len(), 4);
+        assert!(!roll.dice[0].kept);
+
+        let mut faces = [4, 17].into_iter();
+        let roll = "2d20kl1+3"
+            .parse::<DiceExpr>()
+            .unwrap()
+            .roll_with(|_| faces.next().unwrap());
+        assert_eq!(roll.total, 7);
+        assert_eq!(roll.kept().count(), 1);
+    }
+
+    #[test]
+    fn test_exploding() {
+        let mut faces = [6, 6, 2, 3].into_iter();
+        let roll = "2d6!"
+            .parse::<DiceExpr>()
+            .unwrap()
+            .roll_with(|_| faces.next().unwrap());
+        assert_eq!(roll.total, 17);
+        assert_eq!(roll.dice.len(), 4);
+    }
+}
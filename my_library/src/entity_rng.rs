@@ -0,0 +1,222 @@
+use crate::{DefaultRng, RandomNumberGenerator, RngBackend};
+use bevy::prelude::*;
+use rand::{
+    Rng, RngCore,
+    distr::uniform::{SampleRange, SampleUniform},
+};
+use std::marker::PhantomData;
+
+/// `RngKey` gives an entity a stable identity for random number
+/// generation. Entities spawned with an `RngKey` receive an [`EntityRng`]
+/// when the [`EntityRngPlugin`] is added.
+///
+/// The key should describe the entity in a way that doesn't depend on
+/// spawn order---a level's spawn point index, or a name such as
+/// `"boss"`---so that the entity receives the same numbers each run.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RngKey(pub u64);
+
+impl RngKey {
+    /// Creates a key from a name.
+    pub fn named(name: &str) -> Self {
+        Self(crate::stream_seed(0, name))
+    }
+}
+
+/// `EntityRng` is a random number generator belonging to a single entity.
+/// It is used from a `Query` rather than through the global resource.
+///
+/// Its sequence depends only on the global seed and the entity's
+/// [`RngKey`], so spawning entities in a different order---or other
+/// entities drawing more numbers---doesn't change it. It uses the same
+/// algorithm as the global generator, without a lock, so it doesn't matter
+/// which thread a system runs on.
+///
+/// Draw numbers with [`EntityRng::range`] and [`EntityRng::next`], or any
+/// [`rand::Rng`](crate::rand::Rng) method.
+///
+/// ## Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use my_library::*;
+///
+/// #[derive(Component)]
+/// struct Enemy;
+///
+/// fn spawn(mut commands: Commands) {
+///     for spawn_point in 0..4 {
+///         commands.spawn((Enemy, RngKey(spawn_point), Transform::default()));
+///     }
+/// }
+///
+/// fn wander(mut query: Query<(&mut Transform, &mut EntityRng), With<Enemy>>) {
+///     for (mut transform, mut rng) in query.iter_mut() {
+///         transform.translation.x += rng.range(-1.0..1.0);
+///     }
+/// }
+///
+/// App::new()
+///     .add_plugins((RandomPlugin::seeded(1), EntityRngPlugin::new()))
+///     .add_systems(Startup, spawn)
+///     .add_systems(Update, wander);
+/// ```
+#[derive(Component, Clone)]
+pub struct EntityRng<R: RngBackend = DefaultRng>(R);
+
+impl<R: RngBackend> EntityRng<R> {
+    /// Derives an entity's generator from the global generator's seed and
+    /// the entity's key.
+    pub fn new(master: &RandomNumberGenerator<R>, key: RngKey) -> Self {
+        let seed = crate::task_seed(crate::stream_seed(master.seed(), "entities"), key.0);
+        Self(master.reseeded_backend(seed))
+    }
+
+    /// Generates a random number within the specified range.
+    pub fn range<T>(&mut self, range: impl SampleRange<T>) -> T
+    where
+        T: SampleUniform + PartialOrd,
+    {
+        self.0.random_range(range)
+    }
+
+    /// Generates a new random number of the requested type.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next<T>(&mut self) -> T
+    where
+        rand::distr::StandardUniform: rand::prelude::Distribution<T>,
+    {
+        self.0.random()
+    }
+}
+
+impl<R: RngBackend> RngCore for EntityRng<R> {
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        self.0.fill_bytes(dst)
+    }
+}
+
+/// `EntityRngPlugin` inserts an [`EntityRng`] into every entity that is
+/// given an [`RngKey`]. Inserting a new key replaces the generator.
+///
+/// The generators are derived from the `RandomNumberGenerator` resource,
+/// so add [`RandomPlugin`](crate::RandomPlugin) (or insert the resource)
+/// as well. If the generator uses another backend, add the plugin for that
+/// backend, e.g. `EntityRngPlugin::<DynamicRng>::default()` alongside
+/// [`RandomPlugin::with_backend`](crate::RandomPlugin::with_backend), and
+/// query `EntityRng<DynamicRng>`.
+pub struct EntityRngPlugin<R: RngBackend = DefaultRng>(PhantomData<R>);
+
+impl EntityRngPlugin {
+    /// Creates the plugin for the default backend.
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<R: RngBackend> Default for EntityRngPlugin<R> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<R: RngBackend> Plugin for EntityRngPlugin<R> {
+    fn build(&self, app: &mut App) {
+        app.add_observer(insert_entity_rng::<R>);
+    }
+}
+
+fn insert_entity_rng<R: RngBackend>(
+    trigger: Trigger<OnInsert, RngKey>,
+    keys: Query<&RngKey>,
+    master: Res<RandomNumberGenerator<R>>,
+    mut commands: Commands,
+) {
+    let entity = trigger.target();
+    if let Ok(key) = keys.get(entity) {
+        commands
+            .entity(entity)
+            .insert(EntityRng::<R>::new(&master, *key));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn draws(app: &mut App, key: RngKey) -> Vec<u32> {
+        let world = app.world_mut();
+        let mut query = world.query::<(&RngKey, &mut EntityRng)>();
+        let (_, rng) = query
+            .iter_mut(world)
+            .find(|(entity_key, _)| **entity_key == key)
+            .unwrap();
+        let rng = rng.into_inner();
+        (0..10).map(|_| rng.next()).collect()
+    }
+
+    fn app(keys: &[u64]) -> App {
+        let mut app = App::new();
+        app.insert_resource(RandomNumberGenerator::seeded(1))
+            .add_plugins(EntityRngPlugin::new());
+        for key in keys {
+            app.world_mut().spawn(RngKey(*key));
+        }
+        app.update();
+        app
+    }
+
+    #[test]
+    fn test_spawn_order_independent() {
+        let mut first = app(&[1, 2, 3]);
+        let mut second = app(&[3, 2, 1]);
+        assert_eq!(draws(&mut first, RngKey(1)), draws(&mut second, RngKey(1)));
+        assert_ne!(draws(&mut first, RngKey(2)), draws(&mut first, RngKey(3)));
+    }
+
+    #[test]
+    fn test_seed_changes_sequence() {
+        let master = (
+            RandomNumberGenerator::seeded(1),
+            RandomNumberGenerator::seeded(2),
+        );
+        let key = RngKey::named("boss");
+        assert_ne!(
+            EntityRng::new(&master.0, key).next::<u64>(),
+            EntityRng::new(&master.1, key).next::<u64>()
+        );
+        assert_ne!(key, RngKey::named("minion"));
+    }
+
+    #[test]
+    fn test_thread_independent() {
+        let master = RandomNumberGenerator::seeded(1);
+        let mut expected = EntityRng::new(&master, RngKey(1));
+        let expected: Vec<u64> = (0..20).map(|_| expected.next()).collect();
+        // Draw half the sequence on one thread, and the rest on another.
+        let mut rng = EntityRng::new(&master, RngKey(1));
+        let mut drawn: Vec<u64> = Vec::new();
+        for _ in 0..2 {
+            let (more, moved) = std::thread::scope(|scope| {
+                scope
+                    .spawn(move || {
+                        let more: Vec<u64> = (0..10).map(|_| rng.next()).collect();
+                        (more, rng)
+                    })
+                    .join()
+                    .unwrap()
+            });
+            drawn.extend(more);
+            rng = moved;
+        }
+        assert_eq!(drawn, expected);
+    }
+}
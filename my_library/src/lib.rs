@@ -5,7 +5,28 @@
 //!
 //! `my_library` includes:
 //!
-//! * Random number generation facilities.
+//! * Random number generation facilities, including independently
+//!   seeded named streams, non-uniform distributions (normal, Poisson...),
+//!   geometric sampling (points in shapes, directions, rotations, colors)
+//!   and helpers for shuffling and sampling collections. The
+//!   `rng_quality` binary checks every backend with statistical tests.
+//!   Calls can be traced, to find where two runs that should match
+//!   diverge.
+//! * Per-entity random number generators, unaffected by spawn order.
+//! * Dice notation parsing and rolling (`3d6+2`, `4d6dl1`, `d%`...), with
+//!   exact probabilities for every total.
+//! * Strategies for push-your-luck dice games such as Pig, from simple
+//!   rules of thumb to optimal play.
+//! * Weighted random tables, such as loot and spawn tables.
+//! * Streak-free randomness: shuffle bags and pseudo-random distribution
+//!   (chances that grow after each miss).
+//! * Poisson-disk sampling, for scattering objects without overlaps.
+//! * Markov-chain name generation, trained on word lists from code or
+//!   text assets.
+//! * Grid maps, with cave, tunnel and dungeon generators, and Wave
+//!   Function Collapse for tile-based levels.
+//! * Coherent noise (Perlin, simplex, value and cellular) with fractal
+//!   layering and domain warping.
 //!
 //! ## Feature Flags
 //!
@@ -16,11 +37,28 @@
 //! * The `locking` feature enables interior mutability inside
 //!   [`RandomNumberGenerator`],
 //!   allowing it to be used as a resource (`Res<RandomNumberGenerator`)
-//!   rather than requiring mutability (`ResMut<RandomNumberGenerator>`)
-//! * You can control which random number generation algorithm is used by
-//!   specifying *one* of:
-//!   * `xorshift` to use the XorShift algorithm.
-//!   * `pcg` to use the PCG algorithm
+//!   rather than requiring mutability (`ResMut<RandomNumberGenerator>`).
+//!   Draws from other threads continue the same sequence, so seeded runs
+//!   reproduce; `RandomNumberGenerator::with_thread_generators` gives
+//!   each thread its own generator instead, so parallel systems don't
+//!   wait for each other.
+//! * [`RandomNumberGenerator`] can use several random number generation
+//!   algorithms ("backends"), chosen at runtime with [`BackendKind`] or
+//!   at compile time by type. `Std`, `ChaCha` and `SplitMix` are always
+//!   available; these features add more (and may be combined):
+//!   * `pcg` adds the PCG algorithm, and makes it the default (enabled by
+//!     default).
+//!   * `xorshift` adds the XorShift algorithm. It is the default if `pcg`
+//!     is disabled.
+//!
+//! ### Serialization
+//!
+//! * The `serde` feature adds `serde` support to library types, including
+//!   [`RandomNumberGenerator`] and its [`RngSnapshot`], [`ShuffleBag`]
+//!   and [`ChanceAccumulator`], allows [`WeightedTable`] to be loaded
+//!   from RON data files, and lets [`SettingsPlugin`] save the player's
+//!   [`Settings`] and [`HighScorePlugin`] save the [`HighScores`]
+//!   between runs.
 
 /// [`RandomNumberGenerator`] wraps the `rand` crate. The `rand` crate
 /// is re-exported for your convenience.
@@ -28,6 +66,11 @@ pub mod rand {
     pub use rand::*;
 }
 
+/// Non-uniform distributions, used by [`RandomNumberGenerator::sample`].
+pub mod rand_distr {
+    pub use rand_distr::*;
+}
+
 pub mod anyhow {
     pub use anyhow::*;
 }
@@ -40,6 +83,38 @@ pub use random::*;
 mod random_locking;
 #[cfg(feature = "locking")]
 pub use random_locking::*;
+mod random_backend;
+pub use random_backend::*;
+mod random_plugin;
+pub mod random_quality;
+pub use random_plugin::*;
+mod random_streams;
+pub use random_streams::*;
+mod random_trace;
+pub use random_trace::*;
+mod entity_rng;
+pub use entity_rng::*;
+
+mod dice;
+pub use dice::*;
+mod probability;
+pub use probability::*;
+mod push_your_luck;
+pub use push_your_luck::*;
+mod weighted_table;
+pub use weighted_table::*;
+mod shuffle_bag;
+pub use shuffle_bag::*;
+mod chance_accumulator;
+pub use chance_accumulator::*;
+mod noise;
+pub use noise::*;
+mod poisson_disk;
+pub use poisson_disk::*;
+mod markov;
+pub use markov::*;
+mod map;
+pub use map::*;
 
 mod bevy_framework;
 pub use bevy_framework::*;
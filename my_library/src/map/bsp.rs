@@ -0,0 +1,167 @@
+use super::{Grid, MapGenerator, Tile};
+use bevy::math::{IRect, IVec2};
+use rand::{Rng, seq::IndexedRandom};
+
+/// `BspDungeon` generates classic dungeons of rectangular rooms joined by
+/// corridors. The map is split in two again and again (binary space
+/// partitioning) until the pieces are small, a room is placed in each
+/// piece, and the two halves of every split are joined by a corridor.
+///
+/// ## Example
+///
+/// ```
+/// use my_library::*;
+/// let dungeon = BspDungeon::new(80, 50).with_room_size(5);
+/// let mut rng = RandomNumberGenerator::seeded(1);
+/// let map = dungeon.generate(&mut rng);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BspDungeon {
+    width: u32,
+    height: u32,
+    min_room: i32,
+    min_area: i32,
+}
+
+impl BspDungeon {
+    /// Creates a dungeon generator for a `width` by `height` map.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            min_room: 4,
+            min_area: 10,
+        }
+    }
+
+    /// Sets the smallest width or height of a room (4 by default).
+    pub fn with_room_size(mut self, min_room: u32) -> Self {
+        self.min_room = min_room.max(1) as i32;
+        self.min_area = self.min_area.max(self.min_room + 2);
+        self
+    }
+
+    /// Sets the smallest area a split may leave on either side (10 by
+    /// default). Larger areas give fewer, larger rooms. It is never less
+    /// than the room size plus a wall on each side.
+    pub fn with_split_size(mut self, min_area: u32) -> Self {
+        self.min_area = (min_area as i32).max(self.min_room + 2);
+        self
+    }
+
+    /// Splits `area`, returning the rooms carved inside it.
+    fn split<R: Rng>(&self, rng: &mut R, area: IRect, map: &mut Grid<Tile>) -> Vec<IRect> {
+        let size = area.size();
+        let (can_split_x, can_split_y) = (size.x >= self.min_area * 2, size.y >= self.min_area * 2);
+        let vertical = match (can_split_x, can_split_y) {
+            (false, false) => return self.place_room(rng, area, map).into_iter().collect(),
+            (true, false) => true,
+            (false, true) => false,
+            // Prefer cutting across the long side, to avoid thin slivers
+            _ if size.x * 4 > size.y * 5 => true,
+            _ if size.y * 4 > size.x * 5 => false,
+            _ => rng.random_bool(0.5),
+        };
+        let (first, second) = if vertical {
+            let cut = rng.random_range(area.min.x + self.min_area..=area.max.x - self.min_area);
+            (
+                IRect::new(area.min.x, area.min.y, cut, area.max.y),
+                IRect::new(cut, area.min.y, area.max.x, area.max.y),
+            )
+        } else {
+            let cut = rng.random_range(area.min.y + self.min_area..=area.max.y - self.min_area);
+            (
+                IRect::new(area.min.x, area.min.y, area.max.x, cut),
+                IRect::new(area.min.x, cut, area.max.x, area.max.y),
+            )
+        };
+        let mut rooms = self.split(rng, first, map);
+        let second = self.split(rng, second, map);
+        if let (Some(from), Some(to)) = (rooms.choose(rng), second.choose(rng)) {
+            let (from, to) = (from.center(), to.center());
+            Self::dig_corridor(rng, from, to, map);
+        }
+        rooms.extend(second);
+        rooms
+    }
+
+    /// Carves a randomly sized room inside `area`, leaving a wall around
+    /// it. Returns `None` if the area is too small.
+    fn place_room<R: Rng>(&self, rng: &mut R, area: IRect, map: &mut Grid<Tile>) -> Option<IRect> {
+        let space = area.size() - 2;
+        if space.x < self.min_room || space.y < self.min_room {
+            return None;
+        }
+        let size = IVec2::new(
+            rng.random_range(self.min_room..=space.x),
+            rng.random_range(self.min_room..=space.y),
+        );
+        let min = IVec2::new(
+            rng.random_range(1..=space.x - size.x + 1),
+            rng.random_range(1..=space.y - size.y + 1),
+        ) + area.min;
+        let room = IRect::from_corners(min, min + size);
+        for y in room.min.y..room.max.y {
+            for x in room.min.x..room.max.x {
+                map[IVec2::new(x, y)] = Tile::Floor;
+            }
+        }
+        Some(room)
+    }
+
+    /// Digs an L-shaped corridor between two points.
+    fn dig_corridor<R: Rng>(rng: &mut R, from: IVec2, to: IVec2, map: &mut Grid<Tile>) {
+        let corner = if rng.random_bool(0.5) {
+            IVec2::new(to.x, from.y)
+        } else {
+            IVec2::new(from.x, to.y)
+        };
+        for (start, end) in [(from, corner), (corner, to)] {
+            let (min, max) = (start.min(end), start.max(end));
+            for y in min.y..=max.y {
+                for x in min.x..=max.x {
+                    map[IVec2::new(x, y)] = Tile::Floor;
+                }
+            }
+        }
+    }
+}
+
+impl MapGenerator for BspDungeon {
+    fn generate<R: Rng>(&self, rng: &mut R) -> Grid<Tile> {
+        let mut map = Grid::new(self.width, self.height, Tile::Wall);
+        let area = IRect::new(0, 0, self.width as i32, self.height as i32);
+        self.split(rng, area, &mut map);
+        map
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::map::test::{assert_connected, assert_walled};
+    use rand::SeedableRng;
+
+    fn rng() -> rand::rngs::StdRng {
+        rand::rngs::StdRng::seed_from_u64(1)
+    }
+
+    #[test]
+    fn test_dungeon() {
+        let mut rng = rng();
+        for _ in 0..20 {
+            let map = BspDungeon::new(80, 50).generate(&mut rng);
+            assert!(map.count(|tile| *tile == Tile::Floor) > 16 * 8);
+            assert_walled(&map);
+            assert_connected(&map);
+        }
+    }
+
+    #[test]
+    fn test_too_small() {
+        let map = BspDungeon::new(5, 5).generate(&mut rng());
+        assert_eq!(map.count(|tile| *tile == Tile::Floor), 0);
+        let map = BspDungeon::new(6, 6).generate(&mut rng());
+        assert_eq!(map.count(|tile| *tile == Tile::Floor), 16);
+    }
+}
@@ -0,0 +1,122 @@
+use super::{Grid, MapGenerator, Tile};
+use bevy::math::IVec2;
+use rand::Rng;
+
+/// `CellularCaves` generates natural-looking caves. The map starts as
+/// random noise, and is then smoothed several times: each tile becomes
+/// wall if most of its eight neighbours are walls, and floor if most are
+/// floors.
+///
+/// ## Example
+///
+/// ```
+/// use my_library::*;
+/// let caves = CellularCaves::new(64, 48).with_fill(0.45).with_iterations(4);
+/// let mut rng = RandomNumberGenerator::seeded(1);
+/// let map = caves.generate(&mut rng);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CellularCaves {
+    width: u32,
+    height: u32,
+    fill: f32,
+    iterations: u32,
+    connected: bool,
+}
+
+impl CellularCaves {
+    /// Creates a cave generator for a `width` by `height` map.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            fill: 0.45,
+            iterations: 5,
+            connected: true,
+        }
+    }
+
+    /// Sets the chance of each tile starting as wall (0.45 by default).
+    /// Higher values give narrower caves.
+    pub fn with_fill(mut self, fill: f32) -> Self {
+        self.fill = fill.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets how many smoothing passes are made (5 by default).
+    pub fn with_iterations(mut self, iterations: u32) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Keeps caves that can't be reached from the largest cave, instead
+    /// of filling them in.
+    pub fn with_disconnected_caves(mut self) -> Self {
+        self.connected = false;
+        self
+    }
+
+    /// Counts the walls around `pos`. Tiles outside the map count as walls.
+    fn walls_around(map: &Grid<Tile>, pos: IVec2) -> usize {
+        (-1..=1)
+            .flat_map(|y| (-1..=1).map(move |x| IVec2::new(x, y)))
+            .filter(|offset| *offset != IVec2::ZERO)
+            .filter(|offset| {
+                map.get(pos + *offset)
+                    .is_none_or(|tile| *tile == Tile::Wall)
+            })
+            .count()
+    }
+}
+
+impl MapGenerator for CellularCaves {
+    fn generate<R: Rng>(&self, rng: &mut R) -> Grid<Tile> {
+        let mut map = Grid::new(self.width, self.height, Tile::Wall);
+        for pos in map.positions() {
+            if !map.is_edge(pos) && rng.random::<f32>() >= self.fill {
+                map[pos] = Tile::Floor;
+            }
+        }
+        for _ in 0..self.iterations {
+            map = map.map(|pos, tile| match Self::walls_around(&map, pos) {
+                _ if map.is_edge(pos) => Tile::Wall,
+                walls if walls > 4 => Tile::Wall,
+                walls if walls < 4 => Tile::Floor,
+                _ => *tile,
+            });
+        }
+        if self.connected {
+            map.keep_largest_region();
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::map::test::{assert_connected, assert_walled};
+    use rand::SeedableRng;
+
+    fn rng() -> rand::rngs::StdRng {
+        rand::rngs::StdRng::seed_from_u64(1)
+    }
+
+    #[test]
+    fn test_caves() {
+        let map = CellularCaves::new(60, 40).generate(&mut rng());
+        assert_eq!(map.size(), bevy::math::UVec2::new(60, 40));
+        let floors = map.count(|tile| *tile == Tile::Floor);
+        assert!(floors > 60 * 40 / 4, "{floors}");
+        assert_walled(&map);
+        assert_connected(&map);
+    }
+
+    #[test]
+    fn test_solid() {
+        let map = CellularCaves::new(20, 20)
+            .with_fill(1.0)
+            .generate(&mut rng());
+        assert_eq!(map.count(|tile| *tile == Tile::Floor), 0);
+    }
+}
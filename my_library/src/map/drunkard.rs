@@ -0,0 +1,116 @@
+use super::{DIRECTIONS, Grid, MapGenerator, Tile};
+use bevy::math::IVec2;
+use rand::{Rng, seq::IndexedRandom};
+
+/// `DrunkardsWalk` carves winding tunnels by sending "drunkards" staggering
+/// through solid rock, turning every tile they visit into floor. The first
+/// starts in the middle of the map, and each one after that starts
+/// somewhere already dug, so the result is always connected.
+///
+/// ## Example
+///
+/// ```
+/// use my_library::*;
+/// let tunnels = DrunkardsWalk::new(64, 48).with_coverage(0.3);
+/// let mut rng = RandomNumberGenerator::seeded(1);
+/// let map = tunnels.generate(&mut rng);
+/// assert!(map.count(|tile| *tile == Tile::Floor) >= 62 * 46 * 3 / 10);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DrunkardsWalk {
+    width: u32,
+    height: u32,
+    coverage: f32,
+    lifetime: u32,
+}
+
+impl DrunkardsWalk {
+    /// Creates a tunnel generator for a `width` by `height` map.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            coverage: 0.4,
+            lifetime: 400,
+        }
+    }
+
+    /// Sets the share of the map (not counting the outer wall) to dig out,
+    /// from 0 to 1 (0.4 by default).
+    pub fn with_coverage(mut self, coverage: f32) -> Self {
+        self.coverage = coverage.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets how many steps each drunkard takes before giving up (400 by
+    /// default). Short lives give a denser, more cave-like map.
+    pub fn with_lifetime(mut self, lifetime: u32) -> Self {
+        self.lifetime = lifetime.max(1);
+        self
+    }
+}
+
+impl MapGenerator for DrunkardsWalk {
+    fn generate<R: Rng>(&self, rng: &mut R) -> Grid<Tile> {
+        let mut map = Grid::new(self.width, self.height, Tile::Wall);
+        if self.width < 3 || self.height < 3 {
+            return map;
+        }
+        let interior = (self.width - 2) * (self.height - 2);
+        let target = ((interior as f32 * self.coverage).ceil() as usize).max(1);
+        let (min, max) = (
+            IVec2::ONE,
+            IVec2::new(self.width as i32 - 2, self.height as i32 - 2),
+        );
+
+        let mut dug = Vec::new();
+        let mut start = (min + max) / 2;
+        while dug.len() < target {
+            let mut pos = start;
+            for _ in 0..self.lifetime {
+                if map[pos] == Tile::Wall {
+                    map[pos] = Tile::Floor;
+                    dug.push(pos);
+                    if dug.len() >= target {
+                        break;
+                    }
+                }
+                pos = (pos + *DIRECTIONS.choose(rng).unwrap()).clamp(min, max);
+            }
+            start = *dug.choose(rng).unwrap();
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::map::test::{assert_connected, assert_walled};
+    use rand::SeedableRng;
+
+    fn rng() -> rand::rngs::StdRng {
+        rand::rngs::StdRng::seed_from_u64(1)
+    }
+
+    #[test]
+    fn test_walk() {
+        let map = DrunkardsWalk::new(40, 30)
+            .with_coverage(0.5)
+            .with_lifetime(50)
+            .generate(&mut rng());
+        assert_eq!(map.count(|tile| *tile == Tile::Floor), 38 * 28 / 2);
+        assert_walled(&map);
+        assert_connected(&map);
+    }
+
+    #[test]
+    fn test_tiny() {
+        let map = DrunkardsWalk::new(2, 2).generate(&mut rng());
+        assert_eq!(map.count(|tile| *tile == Tile::Floor), 0);
+        let map = DrunkardsWalk::new(3, 3)
+            .with_coverage(1.0)
+            .generate(&mut rng());
+        assert_eq!(map.count(|tile| *tile == Tile::Floor), 1);
+    }
+}
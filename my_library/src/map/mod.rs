@@ -0,0 +1,318 @@
+//! Grid maps and procedural level generators, for roguelike-style games.
+//!
+//! A [`Grid`] stores one value per cell. The generators fill a
+//! `Grid<Tile>` with walls and floors:
+//!
+//! * [`CellularCaves`] grows organic caves with a cellular automaton.
+//! * [`DrunkardsWalk`] carves winding tunnels with random walkers.
+//! * [`BspDungeon`] splits the map into rooms joined by corridors.
+//!
+//! For tile-based levels with rules about which tiles may touch, there is
+//! also a Wave Function Collapse solver: see [`WfcModel`].
+//!
+//! Maps are generated with [`MapGenerator::generate`], drawing from any
+//! random number generator, so the same seed produces the same level, and
+//! can be turned into sprites with [`Grid::spawn_sprites`].
+//!
+//! ## Example
+//!
+//! ```
+//! use my_library::*;
+//! let mut rng = RandomNumberGenerator::seeded(1);
+//! let map = CellularCaves::new(80, 50).generate(&mut rng);
+//! assert_eq!(map.width(), 80);
+//! assert!(map.count(|tile| *tile == Tile::Floor) > 0);
+//! ```
+use bevy::math::{IVec2, UVec2};
+use rand::Rng;
+use std::{
+    collections::VecDeque,
+    ops::{Index, IndexMut},
+};
+
+mod bsp;
+mod caves;
+mod drunkard;
+mod spawn;
+mod wfc;
+pub use bsp::*;
+pub use caves::*;
+pub use drunkard::*;
+pub use spawn::*;
+pub use wfc::*;
+
+/// The four orthogonal directions.
+pub(crate) const DIRECTIONS: [IVec2; 4] = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y];
+
+/// A map cell produced by the level generators.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Tile {
+    #[default]
+    Wall,
+    Floor,
+}
+
+/// Something that can generate a map of walls and floors, such as
+/// [`CellularCaves`]. Implement it to make your own generators work
+/// wherever the built-in ones do.
+pub trait MapGenerator {
+    /// Generates a map, drawing random numbers from `rng`.
+    fn generate<R: Rng>(&self, rng: &mut R) -> Grid<Tile>;
+}
+
+/// `Grid` is a rectangular map, storing one `T` for each cell. Cells are
+/// addressed by `IVec2` positions, with `(0, 0)` in the bottom-left
+/// corner.
+///
+/// Indexing a grid (`grid[pos]`) panics if the position is outside it;
+/// [`Grid::get`] returns `None` instead.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Grid<T> {
+    width: u32,
+    height: u32,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Creates a grid, calling `cell` to fill each position.
+    pub fn from_fn(width: u32, height: u32, mut cell: impl FnMut(IVec2) -> T) -> Self {
+        let cells = (0..height as i32)
+            .flat_map(|y| (0..width as i32).map(move |x| IVec2::new(x, y)))
+            .map(&mut cell)
+            .collect();
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn size(&self) -> UVec2 {
+        UVec2::new(self.width, self.height)
+    }
+
+    /// Returns `true` if `pos` is inside the grid.
+    pub fn contains(&self, pos: IVec2) -> bool {
+        pos.x >= 0 && pos.y >= 0 && (pos.x as u32) < self.width && (pos.y as u32) < self.height
+    }
+
+    /// Returns `true` if `pos` is on the outer edge of the grid.
+    pub fn is_edge(&self, pos: IVec2) -> bool {
+        pos.x == 0
+            || pos.y == 0
+            || pos.x == self.width as i32 - 1
+            || pos.y == self.height as i32 - 1
+    }
+
+    fn index_of(&self, pos: IVec2) -> Option<usize> {
+        self.contains(pos)
+            .then(|| pos.y as usize * self.width as usize + pos.x as usize)
+    }
+
+    pub fn get(&self, pos: IVec2) -> Option<&T> {
+        self.index_of(pos).map(|index| &self.cells[index])
+    }
+
+    pub fn get_mut(&mut self, pos: IVec2) -> Option<&mut T> {
+        self.index_of(pos).map(|index| &mut self.cells[index])
+    }
+
+    /// Iterates over every position in the grid, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = IVec2> + use<T> {
+        let width = self.width as i32;
+        (0..self.height as i32).flat_map(move |y| (0..width).map(move |x| IVec2::new(x, y)))
+    }
+
+    /// Iterates over every cell, with its position.
+    pub fn iter(&self) -> impl Iterator<Item = (IVec2, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// Counts the cells that match `filter`.
+    pub fn count(&self, filter: impl Fn(&T) -> bool) -> usize {
+        self.cells.iter().filter(|cell| filter(cell)).count()
+    }
+
+    /// The orthogonal neighbours of `pos` that are inside the grid.
+    pub fn neighbours(&self, pos: IVec2) -> impl Iterator<Item = IVec2> + '_ {
+        DIRECTIONS
+            .iter()
+            .map(move |direction| pos + *direction)
+            .filter(|neighbour| self.contains(*neighbour))
+    }
+
+    /// Finds every position reachable from `start` by orthogonal steps
+    /// through cells that match `passable`. Returns an empty list if
+    /// `start` itself isn't passable.
+    pub fn flood_fill(&self, start: IVec2, passable: impl Fn(&T) -> bool) -> Vec<IVec2> {
+        let mut visited = Grid::new(self.width, self.height, false);
+        let mut found = Vec::new();
+        let mut queue = VecDeque::new();
+        if self.get(start).is_some_and(&passable) {
+            visited[start] = true;
+            queue.push_back(start);
+        }
+        while let Some(pos) = queue.pop_front() {
+            found.push(pos);
+            for neighbour in self.neighbours(pos) {
+                if !visited[neighbour] && passable(&self[neighbour]) {
+                    visited[neighbour] = true;
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+        found
+    }
+
+    /// Creates a new grid by converting each cell.
+    pub fn map<U>(&self, mut convert: impl FnMut(IVec2, &T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.iter().map(|(pos, cell)| convert(pos, cell)).collect(),
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    /// Creates a grid with every cell set to `value`.
+    pub fn new(width: u32, height: u32, value: T) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![value; width as usize * height as usize],
+        }
+    }
+}
+
+impl<T> Index<IVec2> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: IVec2) -> &T {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{pos} is outside a {}x{} grid", self.width, self.height))
+    }
+}
+
+impl<T> IndexMut<IVec2> for Grid<T> {
+    fn index_mut(&mut self, pos: IVec2) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{pos} is outside a {width}x{height} grid"))
+    }
+}
+
+impl Grid<Tile> {
+    /// Turns every floor tile that can't reach the largest open area into
+    /// wall, so that the whole map is connected.
+    pub fn keep_largest_region(&mut self) {
+        let mut seen = Grid::new(self.width, self.height, false);
+        let mut largest = Vec::new();
+        for pos in self.positions() {
+            if seen[pos] || self[pos] != Tile::Floor {
+                continue;
+            }
+            let region = self.flood_fill(pos, |tile| *tile == Tile::Floor);
+            region.iter().for_each(|pos| seen[*pos] = true);
+            if region.len() > largest.len() {
+                largest = region;
+            }
+        }
+        let mut keep = Grid::new(self.width, self.height, false);
+        largest.iter().for_each(|pos| keep[*pos] = true);
+        for pos in self.positions() {
+            if !keep[pos] {
+                self[pos] = Tile::Wall;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Checks that every floor tile can reach every other.
+    pub(crate) fn assert_connected(map: &Grid<Tile>) {
+        let floors = map.count(|tile| *tile == Tile::Floor);
+        let start = map
+            .iter()
+            .find(|(_, tile)| **tile == Tile::Floor)
+            .unwrap()
+            .0;
+        assert_eq!(
+            map.flood_fill(start, |tile| *tile == Tile::Floor).len(),
+            floors
+        );
+    }
+
+    /// Checks that the edges of the map are solid.
+    pub(crate) fn assert_walled(map: &Grid<Tile>) {
+        assert!(
+            map.iter()
+                .filter(|(pos, _)| map.is_edge(*pos))
+                .all(|(_, tile)| *tile == Tile::Wall)
+        );
+    }
+
+    #[test]
+    fn test_reproducibility() {
+        let mut rng = (
+            crate::RandomNumberGenerator::seeded(1),
+            crate::RandomNumberGenerator::seeded(1),
+        );
+        let caves = CellularCaves::new(40, 30);
+        let dungeon = BspDungeon::new(40, 30);
+        assert_eq!(caves.generate(&mut rng.0), caves.generate(&mut rng.1));
+        assert_eq!(dungeon.generate(&mut rng.0), dungeon.generate(&mut rng.1));
+    }
+
+    #[test]
+    fn test_grid() {
+        let mut grid = Grid::from_fn(4, 3, |pos| pos.x + pos.y * 10);
+        assert_eq!(grid.size(), UVec2::new(4, 3));
+        assert_eq!(grid[IVec2::new(3, 2)], 23);
+        assert!(grid.get(IVec2::new(4, 0)).is_none());
+        assert!(grid.get(IVec2::new(0, -1)).is_none());
+        grid[IVec2::new(1, 1)] = 0;
+        assert_eq!(grid.count(|cell| *cell == 0), 2);
+        assert_eq!(grid.neighbours(IVec2::ZERO).count(), 2);
+        assert_eq!(grid.positions().count(), 12);
+        assert!(grid.is_edge(IVec2::new(3, 1)) && !grid.is_edge(IVec2::new(1, 1)));
+    }
+
+    #[test]
+    fn test_regions() {
+        // Two separate rooms; the larger one should survive.
+        let mut map = Grid::from_fn(9, 5, |pos| {
+            if (1..=2).contains(&pos.x) || (4..=7).contains(&pos.x) {
+                Tile::Floor
+            } else {
+                Tile::Wall
+            }
+        });
+        assert_eq!(
+            map.flood_fill(IVec2::new(1, 0), |t| *t == Tile::Floor)
+                .len(),
+            10
+        );
+        assert!(
+            map.flood_fill(IVec2::ZERO, |t| *t == Tile::Floor)
+                .is_empty()
+        );
+        map.keep_largest_region();
+        assert_eq!(map.count(|tile| *tile == Tile::Floor), 20);
+        assert_eq!(map[IVec2::new(1, 0)], Tile::Wall);
+        assert_connected(&map);
+    }
+}
@@ -0,0 +1,94 @@
+use super::Grid;
+use crate::{AssetStore, LoadedAssets};
+use bevy::prelude::*;
+
+/// The cell of a [`Grid`] that a sprite spawned by
+/// [`Grid::spawn_sprites`] represents.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GridPosition(pub IVec2);
+
+impl<T> Grid<T> {
+    /// The world position of the center of a cell, for a grid whose
+    /// bottom-left corner is at `origin`.
+    pub fn world_position(pos: IVec2, tile_size: f32, origin: Vec3) -> Vec3 {
+        origin + ((pos.as_vec2() + 0.5) * tile_size).extend(0.0)
+    }
+
+    /// Spawns a sprite for each cell, with its image chosen by `sprite`:
+    /// return the tag of an image in the [`AssetStore`], or `None` to
+    /// leave the cell empty. Sprites are scaled to `tile_size`, and placed
+    /// with the grid's bottom-left corner at `origin`.
+    ///
+    /// Each sprite is given a [`GridPosition`]. Spawning a large map this
+    /// way creates a lot of entities; a tilemap crate may suit very large
+    /// maps better.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sprite` returns a tag that isn't in the asset store.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bevy::prelude::*;
+    /// use my_library::*;
+    ///
+    /// fn spawn_level(
+    ///     mut commands: Commands,
+    ///     mut rng: ResMut<RandomNumberGenerator>,
+    ///     assets: Res<AssetStore>,
+    ///     loaded_assets: AssetResource,
+    /// ) {
+    ///     let map = DrunkardsWalk::new(40, 30).generate(&mut *rng);
+    ///     let origin = Vec3::new(-320.0, -240.0, 0.0);
+    ///     map.spawn_sprites(16.0, origin, &mut commands, &assets, &loaded_assets, |_, tile| {
+    ///         match tile {
+    ///             Tile::Wall => Some("wall"),
+    ///             Tile::Floor => None,
+    ///         }
+    ///     });
+    /// }
+    /// ```
+    pub fn spawn_sprites<S: AsRef<str>>(
+        &self,
+        tile_size: f32,
+        origin: Vec3,
+        commands: &mut Commands,
+        assets: &AssetStore,
+        loaded_assets: &LoadedAssets,
+        mut sprite: impl FnMut(IVec2, &T) -> Option<S>,
+    ) {
+        for (pos, cell) in self.iter() {
+            let Some(tag) = sprite(pos, cell) else {
+                continue;
+            };
+            let tag = tag.as_ref();
+            let image = assets
+                .get_handle(tag, loaded_assets)
+                .unwrap_or_else(|| panic!("No image tagged {tag} in the asset store"));
+            commands.spawn((
+                Sprite {
+                    image,
+                    custom_size: Some(Vec2::splat(tile_size)),
+                    ..default()
+                },
+                Transform::from_translation(Self::world_position(pos, tile_size, origin)),
+                GridPosition(pos),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_world_position() {
+        let origin = Vec3::new(-100.0, -50.0, 1.0);
+        assert_eq!(
+            Grid::<()>::world_position(IVec2::new(2, 1), 10.0, origin),
+            Vec3::new(-75.0, -35.0, 1.0)
+        );
+    }
+}
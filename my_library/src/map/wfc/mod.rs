@@ -0,0 +1,695 @@
+use super::{DIRECTIONS, Grid};
+use bevy::math::IVec2;
+use rand::Rng;
+use std::collections::HashSet;
+
+mod sample;
+mod task;
+pub use task::*;
+
+/// `WfcModel` describes what Wave Function Collapse may build: the tiles it
+/// can place, how often each should appear, and which tiles may sit next to
+/// each other. Every pair of neighbouring cells in the result follows the
+/// rules.
+///
+/// Build a "simple tiled" model by listing tiles and rules, or learn one
+/// from an example with [`WfcModel::from_sample`] or
+/// [`WfcModel::from_image`]. Run it with [`WfcModel::generate`], or a
+/// few steps at a time with a [`WfcSolver`].
+///
+/// ## Example
+///
+/// ```
+/// use my_library::*;
+/// // Sea next to sand, sand next to grass, but never sea next to grass.
+/// let coast = WfcModel::new()
+///     .with_tile("sea", 3.0)
+///     .with_tile("sand", 1.0)
+///     .with_tile("grass", 3.0)
+///     .with_neighbours("sea", "sea")
+///     .with_neighbours("sand", "sand")
+///     .with_neighbours("grass", "grass")
+///     .with_neighbours("sea", "sand")
+///     .with_neighbours("sand", "grass");
+/// let mut rng = RandomNumberGenerator::seeded(1);
+/// let map = coast.generate(&mut rng, 20, 20).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct WfcModel<T> {
+    tiles: Vec<T>,
+    weights: Vec<f64>,
+    /// `(tile, direction, neighbour)`: `neighbour` may be placed one step in
+    /// `DIRECTIONS[direction]` from `tile`.
+    rules: HashSet<(usize, usize, usize)>,
+}
+
+impl<T: Clone + PartialEq> WfcModel<T> {
+    /// Creates a model with no tiles.
+    pub fn new() -> Self {
+        Self {
+            tiles: Vec::new(),
+            weights: Vec::new(),
+            rules: HashSet::new(),
+        }
+    }
+
+    /// Adds a tile. Weights are relative: a tile with weight 2 is picked
+    /// twice as often as one with weight 1. Adding a tile that is already
+    /// in the model changes its weight.
+    pub fn with_tile(mut self, tile: T, weight: f32) -> Self {
+        let weight = weight.max(f32::EPSILON) as f64;
+        let indices = self.indices(&tile);
+        if indices.is_empty() {
+            self.tiles.push(tile);
+            self.weights.push(weight);
+        }
+        indices
+            .iter()
+            .for_each(|index| self.weights[*index] = weight);
+        self
+    }
+
+    /// Allows `neighbour` to be placed one step in `direction` from
+    /// `tile` (and so `tile` one step back from `neighbour`). `direction`
+    /// is one of `IVec2::X`, `IVec2::NEG_X`, `IVec2::Y` or `IVec2::NEG_Y`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either tile hasn't been added, or `direction` isn't one
+    /// of the four orthogonal steps.
+    pub fn with_neighbour(mut self, tile: T, direction: IVec2, neighbour: T) -> Self {
+        let direction = DIRECTIONS
+            .iter()
+            .position(|step| *step == direction)
+            .unwrap_or_else(|| panic!("{direction} isn't an orthogonal step"));
+        let (tiles, neighbours) = (self.known(&tile), self.known(&neighbour));
+        for tile in tiles.iter() {
+            for neighbour in neighbours.iter() {
+                self.rules.insert((*tile, direction, *neighbour));
+                self.rules.insert((*neighbour, direction ^ 1, *tile));
+            }
+        }
+        self
+    }
+
+    /// Allows `a` and `b` to be placed next to each other, in any
+    /// direction. Use `with_neighbours(a, a)` to let a tile sit next to
+    /// itself.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either tile hasn't been added.
+    pub fn with_neighbours(self, a: T, b: T) -> Self {
+        DIRECTIONS.iter().fold(self, |model, direction| {
+            model.with_neighbour(a.clone(), *direction, b.clone())
+        })
+    }
+
+    /// Every tile the model can place, which may repeat for models
+    /// learned from a sample.
+    pub fn tiles(&self) -> &[T] {
+        &self.tiles
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// Fills a `width` by `height` grid, so that every pair of neighbouring
+    /// tiles follows the rules. Returns `None` if the solver gives up.
+    pub fn generate<R: Rng>(&self, rng: &mut R, width: u32, height: u32) -> Option<Grid<T>> {
+        let mut solver = WfcSolver::new(self, width, height);
+        solver.step(rng, usize::MAX);
+        solver.result()
+    }
+
+    /// Models learned from a sample can hold several patterns that show the
+    /// same tile, so a tile may have more than one index.
+    fn indices(&self, tile: &T) -> Vec<usize> {
+        (0..self.tiles.len())
+            .filter(|index| self.tiles[*index] == *tile)
+            .collect()
+    }
+
+    fn known(&self, tile: &T) -> Vec<usize> {
+        let indices = self.indices(tile);
+        assert!(!indices.is_empty(), "Tile isn't in the model");
+        indices
+    }
+}
+
+impl<T: Clone + PartialEq> Default for WfcModel<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How far a [`WfcSolver`] has got.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WfcStatus {
+    /// Some cells still have more than one possible tile.
+    Running,
+    /// Every cell has a tile, and the result is ready.
+    Done,
+    /// The solver ran into a contradiction it couldn't back out of, or
+    /// backtracked more often than its limit allows.
+    Failed,
+}
+
+/// A choice made by the solver, and the length of the trail before it, so
+/// it can be undone.
+#[derive(Debug, Clone, Copy)]
+struct Decision {
+    trail: usize,
+    cell: usize,
+    tile: usize,
+}
+
+/// `WfcSolver` runs Wave Function Collapse on a grid, a step at a time.
+/// Every cell starts out able to hold any tile. Each step picks the cell
+/// with the fewest options left (the lowest entropy), chooses one of its
+/// tiles at random, and removes the options this rules out from the
+/// surrounding cells. If a cell is left with no options, the solver
+/// backtracks: it undoes its latest choice and tries another.
+///
+/// Drive it with [`WfcSolver::step`], which makes it easy to spread the
+/// work over several frames, or use a [`WfcTask`] to do that for you.
+///
+/// ## Example
+///
+/// ```
+/// use my_library::*;
+/// let model = WfcModel::new()
+///     .with_tile(Tile::Wall, 1.0)
+///     .with_tile(Tile::Floor, 2.0)
+///     .with_neighbours(Tile::Wall, Tile::Wall)
+///     .with_neighbours(Tile::Wall, Tile::Floor)
+///     .with_neighbours(Tile::Floor, Tile::Floor);
+/// let mut solver = WfcSolver::new(&model, 32, 32);
+/// let mut rng = RandomNumberGenerator::seeded(1);
+/// while solver.step(&mut rng, 100) == WfcStatus::Running {
+///     println!("{:.0}% done", solver.progress() * 100.0);
+/// }
+/// let map = solver.result().unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct WfcSolver<T> {
+    tiles: Vec<T>,
+    weights: Vec<f64>,
+    width: u32,
+    height: u32,
+    /// Bitsets are stored in this many `u64` words.
+    words: usize,
+    /// For each tile and direction, the set of tiles allowed next to it.
+    allowed: Vec<u64>,
+    /// For each cell, the set of tiles it may still hold.
+    wave: Vec<u64>,
+    options: Vec<u32>,
+    weight_sums: Vec<f64>,
+    /// For each cell, the sum of `weight * ln(weight)` over its options.
+    log_sums: Vec<f64>,
+    /// The number of cells with exactly one option.
+    decided: usize,
+    /// Every option removed so far, as `(cell, tile)`, in order.
+    trail: Vec<(usize, usize)>,
+    decisions: Vec<Decision>,
+    /// The trail length before the first decision.
+    start: usize,
+    backtracks: u32,
+    max_backtracks: u32,
+    status: WfcStatus,
+}
+
+impl<T: Clone> WfcSolver<T> {
+    /// Creates a solver that fills a `width` by `height` grid with tiles
+    /// from `model`.
+    pub fn new(model: &WfcModel<T>, width: u32, height: u32) -> Self {
+        let count = model.tiles.len();
+        let words = count.div_ceil(64).max(1);
+        let mut allowed = vec![0; count * DIRECTIONS.len() * words];
+        for (tile, direction, neighbour) in model.rules.iter() {
+            allowed[(tile * DIRECTIONS.len() + direction) * words + neighbour / 64] |=
+                1 << (neighbour % 64);
+        }
+        let mut all = vec![0; words];
+        (0..count).for_each(|tile| all[tile / 64] |= 1 << (tile % 64));
+        let cells = width as usize * height as usize;
+        let log_sum = model.weights.iter().map(|w| w * w.ln()).sum();
+
+        let mut solver = Self {
+            tiles: model.tiles.clone(),
+            weights: model.weights.clone(),
+            width,
+            height,
+            words,
+            allowed,
+            wave: all.repeat(cells),
+            options: vec![count as u32; cells],
+            weight_sums: vec![model.weights.iter().sum(); cells],
+            log_sums: vec![log_sum; cells],
+            decided: if count == 1 { cells } else { 0 },
+            trail: Vec::new(),
+            decisions: Vec::new(),
+            start: 0,
+            backtracks: 0,
+            max_backtracks: 1000,
+            status: WfcStatus::Running,
+        };
+        // Tiles that can't have a neighbour on some side can only go on the
+        // edge of the map, so rule them out everywhere else up front.
+        if (count == 0 && cells > 0) || !solver.propagate((0..cells).collect()) {
+            solver.status = WfcStatus::Failed;
+        }
+        solver.start = solver.trail.len();
+        solver.check_done();
+        solver
+    }
+
+    /// Sets how many times the solver may backtrack before giving up
+    /// (1,000 by default). Some models contradict themselves so often that
+    /// starting again with [`WfcSolver::reset`] is quicker than searching.
+    pub fn with_backtrack_limit(mut self, limit: u32) -> Self {
+        self.max_backtracks = limit;
+        self
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn status(&self) -> WfcStatus {
+        self.status
+    }
+
+    /// The share of cells that have been given a tile, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        match self.options.len() {
+            0 => 1.0,
+            cells => self.decided as f32 / cells as f32,
+        }
+    }
+
+    /// How many times the solver has backtracked since it started (or
+    /// was reset).
+    pub fn backtracks(&self) -> u32 {
+        self.backtracks
+    }
+
+    /// The tile at `pos`, if it has been decided. Useful for showing a map
+    /// while it is being generated.
+    pub fn get(&self, pos: IVec2) -> Option<&T> {
+        let cell = self.index_of(pos)?;
+        (self.options[cell] == 1).then(|| &self.tiles[self.tiles_in(cell)[0]])
+    }
+
+    /// The finished map, once the status is [`WfcStatus::Done`].
+    pub fn result(&self) -> Option<Grid<T>> {
+        (self.status == WfcStatus::Done).then(|| {
+            Grid::from_fn(self.width, self.height, |pos| {
+                self.get(pos).unwrap().clone()
+            })
+        })
+    }
+
+    /// Undoes every choice, so the solver can start again. Restrictions
+    /// made with [`WfcSolver::restrict`] are kept.
+    pub fn reset(&mut self) {
+        self.undo(self.start);
+        self.decisions.clear();
+        self.backtracks = 0;
+        if self.status != WfcStatus::Failed || self.options.iter().all(|options| *options > 0) {
+            self.status = WfcStatus::Running;
+        }
+        self.check_done();
+    }
+
+    /// Only allows tiles that match `filter` at `pos`, such as walls
+    /// around the edge of a level. This resets the solver. Returns `false`
+    /// (and sets the status to [`WfcStatus::Failed`]) if the map can no
+    /// longer be completed; positions outside the grid are ignored.
+    pub fn restrict(&mut self, pos: IVec2, filter: impl Fn(&T) -> bool) -> bool {
+        self.reset();
+        let Some(cell) = self.index_of(pos) else {
+            return true;
+        };
+        for tile in self.tiles_in(cell) {
+            if !filter(&self.tiles[tile]) {
+                self.ban(cell, tile);
+            }
+        }
+        if self.options[cell] == 0 || !self.propagate(vec![cell]) {
+            self.status = WfcStatus::Failed;
+        }
+        self.start = self.trail.len();
+        self.check_done();
+        self.status != WfcStatus::Failed
+    }
+
+    /// Decides up to `steps` cells, backtracking where needed, and
+    /// returns the new status. Call it once per frame to spread the work
+    /// of generating a large map over several frames.
+    pub fn step<R: Rng>(&mut self, rng: &mut R, steps: usize) -> WfcStatus {
+        for _ in 0..steps {
+            if self.status != WfcStatus::Running {
+                break;
+            }
+            let cell = self.lowest_entropy(rng);
+            let tile = self.pick_tile(rng, cell);
+            self.decisions.push(Decision {
+                trail: self.trail.len(),
+                cell,
+                tile,
+            });
+            for other in self.tiles_in(cell) {
+                if other != tile {
+                    self.ban(cell, other);
+                }
+            }
+            if !self.propagate(vec![cell]) {
+                self.backtrack();
+            }
+            self.check_done();
+        }
+        self.status
+    }
+
+    fn check_done(&mut self) {
+        if self.status == WfcStatus::Running && self.decided == self.options.len() {
+            self.status = WfcStatus::Done;
+        }
+    }
+
+    fn index_of(&self, pos: IVec2) -> Option<usize> {
+        (pos.x >= 0 && pos.y >= 0 && (pos.x as u32) < self.width && (pos.y as u32) < self.height)
+            .then(|| pos.y as usize * self.width as usize + pos.x as usize)
+    }
+
+    fn tiles_in(&self, cell: usize) -> Vec<usize> {
+        let mut tiles = Vec::with_capacity(self.options[cell] as usize);
+        for word in 0..self.words {
+            let mut bits = self.wave[cell * self.words + word];
+            while bits != 0 {
+                tiles.push(word * 64 + bits.trailing_zeros() as usize);
+                bits &= bits - 1;
+            }
+        }
+        tiles
+    }
+
+    /// Finds the undecided cell with the lowest entropy, breaking ties at
+    /// random. There must be at least one undecided cell.
+    fn lowest_entropy<R: Rng>(&self, rng: &mut R) -> usize {
+        let mut lowest = f64::INFINITY;
+        let mut candidates = Vec::new();
+        for cell in (0..self.options.len()).filter(|cell| self.options[*cell] > 1) {
+            let sum = self.weight_sums[cell];
+            let entropy = sum.ln() - self.log_sums[cell] / sum;
+            if entropy < lowest - 1e-9 {
+                lowest = entropy;
+                candidates.clear();
+            }
+            if entropy <= lowest + 1e-9 {
+                candidates.push(cell);
+            }
+        }
+        candidates[rng.random_range(0..candidates.len())]
+    }
+
+    /// Picks one of the cell's options, favouring heavier tiles.
+    fn pick_tile<R: Rng>(&self, rng: &mut R, cell: usize) -> usize {
+        let tiles = self.tiles_in(cell);
+        let mut roll = rng.random::<f64>() * self.weight_sums[cell];
+        for tile in tiles.iter() {
+            roll -= self.weights[*tile];
+            if roll < 0.0 {
+                return *tile;
+            }
+        }
+        *tiles.last().unwrap()
+    }
+
+    fn ban(&mut self, cell: usize, tile: usize) {
+        let weight = self.weights[tile];
+        self.wave[cell * self.words + tile / 64] &= !(1 << (tile % 64));
+        self.options[cell] -= 1;
+        self.weight_sums[cell] -= weight;
+        self.log_sums[cell] -= weight * weight.ln();
+        match self.options[cell] {
+            1 => self.decided += 1,
+            0 => self.decided -= 1,
+            _ => {}
+        }
+        self.trail.push((cell, tile));
+    }
+
+    /// Restores every option removed since the trail was `length` long.
+    fn undo(&mut self, length: usize) {
+        while self.trail.len() > length {
+            let (cell, tile) = self.trail.pop().unwrap();
+            let weight = self.weights[tile];
+            self.wave[cell * self.words + tile / 64] |= 1 << (tile % 64);
+            match self.options[cell] {
+                1 => self.decided -= 1,
+                0 => self.decided += 1,
+                _ => {}
+            }
+            self.options[cell] += 1;
+            self.weight_sums[cell] += weight;
+            self.log_sums[cell] += weight * weight.ln();
+        }
+    }
+
+    /// Removes options that no longer have a compatible neighbour, spreading
+    /// out from the changed `cells`. Returns `false` if a cell runs out of
+    /// options.
+    fn propagate(&mut self, mut cells: Vec<usize>) -> bool {
+        let words = self.words;
+        let mut support = vec![0; words];
+        while let Some(cell) = cells.pop() {
+            let pos = IVec2::new(
+                (cell % self.width as usize) as i32,
+                (cell / self.width as usize) as i32,
+            );
+            for (direction, step) in DIRECTIONS.iter().enumerate() {
+                let Some(neighbour) = self.index_of(pos + *step) else {
+                    continue;
+                };
+                support.fill(0);
+                for tile in self.tiles_in(cell) {
+                    let rules = (tile * DIRECTIONS.len() + direction) * words;
+                    let allowed = &self.allowed[rules..rules + words];
+                    for (supported, allowed) in support.iter_mut().zip(allowed) {
+                        *supported |= allowed;
+                    }
+                }
+                let mut changed = false;
+                for (word, supported) in support.iter().enumerate() {
+                    let mut removed = self.wave[neighbour * words + word] & !supported;
+                    while removed != 0 {
+                        self.ban(neighbour, word * 64 + removed.trailing_zeros() as usize);
+                        removed &= removed - 1;
+                        changed = true;
+                    }
+                }
+                if self.options[neighbour] == 0 {
+                    return false;
+                }
+                if changed {
+                    cells.push(neighbour);
+                }
+            }
+        }
+        true
+    }
+
+    /// Undoes choices until one can be ruled out without a contradiction.
+    fn backtrack(&mut self) {
+        loop {
+            let Some(decision) = self.decisions.pop() else {
+                self.status = WfcStatus::Failed;
+                return;
+            };
+            self.undo(decision.trail);
+            self.backtracks += 1;
+            if self.backtracks > self.max_backtracks {
+                self.status = WfcStatus::Failed;
+                return;
+            }
+            // The choice led to a contradiction, so it belongs with the
+            // earlier choices' consequences.
+            self.ban(decision.cell, decision.tile);
+            if self.options[decision.cell] > 0 && self.propagate(vec![decision.cell]) {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn rng() -> rand::rngs::StdRng {
+        rand::rngs::StdRng::seed_from_u64(1)
+    }
+
+    fn coast() -> WfcModel<char> {
+        WfcModel::new()
+            .with_tile('~', 3.0)
+            .with_tile('.', 1.0)
+            .with_tile('#', 3.0)
+            .with_neighbours('~', '~')
+            .with_neighbours('.', '.')
+            .with_neighbours('#', '#')
+            .with_neighbours('~', '.')
+            .with_neighbours('.', '#')
+    }
+
+    /// Checks every pair of neighbours against `ok`.
+    fn assert_follows(map: &Grid<char>, ok: impl Fn(char, IVec2, char) -> bool) {
+        for (pos, tile) in map.iter() {
+            for step in DIRECTIONS {
+                if let Some(neighbour) = map.get(pos + step) {
+                    assert!(ok(*tile, step, *neighbour), "{tile}{neighbour} at {pos}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_tiled() {
+        let mut solver = WfcSolver::new(&coast(), 30, 20);
+        assert_eq!(solver.step(&mut rng(), usize::MAX), WfcStatus::Done);
+        let map = solver.result().unwrap();
+        assert_follows(&map, |a, _, b| !matches!((a, b), ('~', '#') | ('#', '~')));
+        assert!(map.count(|tile| *tile == '~') > 0);
+    }
+
+    #[test]
+    fn test_directional() {
+        // Stripes: each column must be followed by the next one to the right.
+        let model = WfcModel::new()
+            .with_tile('a', 1.0)
+            .with_tile('b', 1.0)
+            .with_tile('c', 1.0)
+            .with_neighbour('a', IVec2::X, 'b')
+            .with_neighbour('b', IVec2::X, 'c')
+            .with_neighbour('c', IVec2::X, 'a')
+            .with_neighbour('a', IVec2::Y, 'a')
+            .with_neighbour('b', IVec2::Y, 'b')
+            .with_neighbour('c', IVec2::Y, 'c');
+        let mut solver = WfcSolver::new(&model, 12, 6);
+        solver.step(&mut rng(), usize::MAX);
+        let map = solver.result().unwrap();
+        assert_follows(&map, |a, step, b| match step {
+            IVec2::X => (a as u8 - b'a' + 1) % 3 == b as u8 - b'a',
+            IVec2::NEG_X => (b as u8 - b'a' + 1) % 3 == a as u8 - b'a',
+            _ => a == b,
+        });
+    }
+
+    #[test]
+    fn test_incremental() {
+        let whole = {
+            let mut solver = WfcSolver::new(&coast(), 16, 16);
+            solver.step(&mut rng(), usize::MAX);
+            solver.result().unwrap()
+        };
+        let mut solver = WfcSolver::new(&coast(), 16, 16);
+        let mut rng = rng();
+        let mut last = 0.0;
+        while solver.step(&mut rng, 10) == WfcStatus::Running {
+            assert!(solver.result().is_none());
+            last = solver.progress();
+        }
+        assert!(last > 0.0);
+        assert_eq!(solver.progress(), 1.0);
+        assert_eq!(solver.result().unwrap(), whole);
+    }
+
+    #[test]
+    fn test_contradictions() {
+        // The third tile can't touch anything, so it's ruled out up front.
+        let model = WfcModel::new()
+            .with_tile('x', 1.0)
+            .with_tile('o', 1.0)
+            .with_tile('!', 100.0)
+            .with_neighbours('x', 'o');
+        let mut solver = WfcSolver::new(&model, 8, 8);
+        assert_eq!(solver.step(&mut rng(), usize::MAX), WfcStatus::Done);
+        assert_follows(&solver.result().unwrap(), |a, _, b| a != b && a != '!');
+
+        // With no pair of neighbours allowed, there's no solution at all.
+        let model = WfcModel::new().with_tile('x', 1.0).with_tile('o', 1.0);
+        let mut solver = WfcSolver::new(&model, 4, 4);
+        assert_eq!(solver.step(&mut rng(), usize::MAX), WfcStatus::Failed);
+        let model = WfcModel::<char>::new();
+        assert_eq!(WfcSolver::new(&model, 4, 4).status(), WfcStatus::Failed);
+    }
+
+    #[test]
+    fn test_backtracking() {
+        // Random one-way rules often paint the solver into a corner, so it
+        // has to back out of earlier choices.
+        let mut rng = rng();
+        let mut backtracks = 0;
+        for _ in 0..2000 {
+            let mut rules = HashSet::new();
+            for _ in 0..rng.random_range(8..20) {
+                let tile = (b'a' + rng.random_range(0..5)) as char;
+                let neighbour = (b'a' + rng.random_range(0..5)) as char;
+                rules.insert((tile, DIRECTIONS[rng.random_range(0..4)], neighbour));
+            }
+            let model = rules.iter().fold(
+                "abcde"
+                    .chars()
+                    .fold(WfcModel::new(), |model, tile| model.with_tile(tile, 1.0)),
+                |model, (tile, step, neighbour)| model.with_neighbour(*tile, *step, *neighbour),
+            );
+            let mut solver = WfcSolver::new(&model, 6, 6);
+            if solver.step(&mut rng, usize::MAX) == WfcStatus::Done {
+                backtracks += solver.backtracks();
+                assert_follows(&solver.result().unwrap(), |a, step, b| {
+                    rules.contains(&(a, step, b)) || rules.contains(&(b, -step, a))
+                });
+            }
+        }
+        assert!(backtracks > 0);
+    }
+
+    #[test]
+    fn test_restrict() {
+        let mut solver = WfcSolver::new(&coast(), 10, 10);
+        assert!(solver.restrict(IVec2::new(0, 0), |tile| *tile == '~'));
+        assert!(solver.restrict(IVec2::new(9, 9), |tile| *tile == '#'));
+        solver.step(&mut rng(), usize::MAX);
+        let map = solver.result().unwrap();
+        assert_eq!(map[IVec2::new(0, 0)], '~');
+        assert_eq!(map[IVec2::new(9, 9)], '#');
+
+        solver.reset();
+        assert_eq!(solver.status(), WfcStatus::Running);
+        assert_eq!(solver.get(IVec2::new(0, 0)), Some(&'~'));
+        assert_eq!(solver.get(IVec2::new(5, 5)), None);
+
+        // Sea can't be right next to grass.
+        assert!(!solver.restrict(IVec2::new(1, 0), |tile| *tile == '#'));
+        assert_eq!(solver.status(), WfcStatus::Failed);
+    }
+
+    #[test]
+    fn test_reproducibility() {
+        let mut rng = (
+            crate::RandomNumberGenerator::seeded(1),
+            crate::RandomNumberGenerator::seeded(1),
+        );
+        let mut solver = WfcSolver::new(&coast(), 24, 24);
+        while solver.step(&mut rng.1, 7) == WfcStatus::Running {}
+        assert_eq!(coast().generate(&mut rng.0, 24, 24), solver.result());
+    }
+}
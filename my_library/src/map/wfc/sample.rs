@@ -0,0 +1,172 @@
+use super::{DIRECTIONS, Grid, WfcModel};
+use bevy::{
+    asset::RenderAssetUsages,
+    image::ImageSampler,
+    math::IVec2,
+    prelude::{ColorToPacked, Image},
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use std::{collections::HashMap, hash::Hash};
+
+impl<T: Clone + Eq + Hash> WfcModel<T> {
+    /// Learns an "overlapping" model from an example map. Every
+    /// `pattern_size` by `pattern_size` square in `sample` becomes a
+    /// pattern, weighted by how often it appears, and two patterns may be
+    /// neighbours if they agree where they overlap. Generated maps then
+    /// look locally like the sample: a size of 1 only copies which tiles
+    /// touch, while 3 also copies shapes such as corners and corridors.
+    ///
+    /// The sample doesn't wrap around, so an empty model is returned if
+    /// it is smaller than a pattern.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use my_library::*;
+    /// let sample = Grid::from_fn(8, 8, |pos| {
+    ///     if pos.x % 4 == 0 || pos.y % 4 == 0 { Tile::Floor } else { Tile::Wall }
+    /// });
+    /// let model = WfcModel::from_sample(&sample, 2);
+    /// let mut rng = RandomNumberGenerator::seeded(1);
+    /// let map = model.generate(&mut rng, 30, 30);
+    /// ```
+    pub fn from_sample(sample: &Grid<T>, pattern_size: u32) -> Self {
+        let size = pattern_size.max(1) as i32;
+        let mut patterns: Vec<Vec<T>> = Vec::new();
+        let mut index = HashMap::new();
+        let mut model = Self::new();
+        for y in 0..=sample.height() as i32 - size {
+            for x in 0..=sample.width() as i32 - size {
+                let pattern: Vec<T> = (0..size)
+                    .flat_map(|dy| (0..size).map(move |dx| IVec2::new(x + dx, y + dy)))
+                    .map(|pos| sample[pos].clone())
+                    .collect();
+                let id = *index.entry(pattern.clone()).or_insert_with(|| {
+                    model.tiles.push(pattern[0].clone());
+                    model.weights.push(0.0);
+                    patterns.push(pattern);
+                    patterns.len() - 1
+                });
+                model.weights[id] += 1.0;
+            }
+        }
+        for (a, first) in patterns.iter().enumerate() {
+            for (b, second) in patterns.iter().enumerate() {
+                for (direction, step) in DIRECTIONS.iter().enumerate() {
+                    if overlaps_agree(first, second, *step, size) {
+                        model.rules.insert((a, direction, b));
+                    }
+                }
+            }
+        }
+        model
+    }
+}
+
+/// Checks whether `second`, placed one step in `step` from `first`,
+/// matches it wherever they overlap.
+fn overlaps_agree<T: PartialEq>(first: &[T], second: &[T], step: IVec2, size: i32) -> bool {
+    (0..size)
+        .flat_map(|y| (0..size).map(move |x| IVec2::new(x, y)))
+        .filter(|pos| {
+            let other = *pos - step;
+            other.cmpge(IVec2::ZERO).all() && other.cmplt(IVec2::splat(size)).all()
+        })
+        .all(|pos| {
+            let other = pos - step;
+            first[(pos.y * size + pos.x) as usize] == second[(other.y * size + other.x) as usize]
+        })
+}
+
+impl WfcModel<[u8; 4]> {
+    /// Learns an overlapping model from the pixels of an image, such as
+    /// one added with [`AssetManager::add_image`](crate::AssetManager::add_image).
+    /// See [`WfcModel::from_sample`].
+    pub fn from_image(image: &Image, pattern_size: u32) -> anyhow::Result<Self> {
+        Ok(Self::from_sample(&Grid::from_image(image)?, pattern_size))
+    }
+}
+
+impl Grid<[u8; 4]> {
+    /// Reads the sRGBA colour of every pixel in an image. The top row of
+    /// the image becomes the top row of the grid.
+    pub fn from_image(image: &Image) -> anyhow::Result<Self> {
+        let (width, height) = (image.width(), image.height());
+        let mut pixels = Vec::with_capacity(width as usize * height as usize);
+        for y in (0..height).rev() {
+            for x in 0..width {
+                pixels.push(image.get_color_at(x, y)?.to_srgba().to_u8_array());
+            }
+        }
+        let mut pixels = pixels.into_iter();
+        Ok(Self::from_fn(width, height, |_| pixels.next().unwrap()))
+    }
+
+    /// Creates a pixel-art image, one pixel per cell, for example to show
+    /// a map generated from an image sample.
+    pub fn to_image(&self) -> Image {
+        let mut data = Vec::with_capacity(self.cells.len() * 4);
+        for y in (0..self.height as i32).rev() {
+            for x in 0..self.width as i32 {
+                data.extend_from_slice(&self[IVec2::new(x, y)]);
+            }
+        }
+        let mut image = Image::new(
+            Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        image.sampler = ImageSampler::nearest();
+        image
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Tile, WfcSolver, WfcStatus};
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_patterns() {
+        // Vertical stripes, two columns of wall then one of floor.
+        let sample = Grid::from_fn(6, 4, |pos| match pos.x % 3 {
+            2 => Tile::Floor,
+            _ => Tile::Wall,
+        });
+        let model = WfcModel::from_sample(&sample, 3);
+        assert_eq!(model.tiles().len(), 3);
+        assert_eq!(WfcModel::from_sample(&sample, 7).tiles().len(), 0);
+
+        let mut solver = WfcSolver::new(&model, 12, 12);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        assert_eq!(solver.step(&mut rng, usize::MAX), WfcStatus::Done);
+        let map = solver.result().unwrap();
+        for (pos, tile) in map.iter() {
+            if pos.y > 0 {
+                assert_eq!(*tile, map[pos - IVec2::Y]);
+            }
+            // Every floor column is followed by two walls.
+            if *tile == Tile::Floor && pos.x < 10 {
+                assert_eq!(map[pos + IVec2::X], Tile::Wall);
+                assert_eq!(map[pos + IVec2::X * 2], Tile::Wall);
+            }
+        }
+    }
+
+    #[test]
+    fn test_image_round_trip() {
+        let grid = Grid::from_fn(3, 2, |pos| [pos.x as u8 * 100, pos.y as u8 * 100, 7, 255]);
+        let image = grid.to_image();
+        assert_eq!(&image.data.as_ref().unwrap()[..4], &[0, 100, 7, 255]);
+        assert_eq!(Grid::from_image(&image).unwrap(), grid);
+        let model = WfcModel::from_image(&image, 1).unwrap();
+        assert_eq!(model.tiles().len(), 6);
+    }
+}
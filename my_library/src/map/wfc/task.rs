@@ -0,0 +1,341 @@
+use super::{WfcModel, WfcSolver, WfcStatus};
+use crate::{
+    AssetStore, DefaultRng, Grid, LoadedAssets, RandomNumberGenerator, RngBackend, SystemRng,
+    bevy_assets::LoadingProgress,
+};
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task, TaskPool, block_on, futures_lite::future},
+};
+use std::{hash::Hash, marker::PhantomData};
+
+enum TaskState<T> {
+    /// Waiting for a sample image to finish loading.
+    Waiting {
+        image_tag: String,
+        pattern_size: u32,
+        width: u32,
+        height: u32,
+        convert: fn([u8; 4]) -> T,
+    },
+    /// Learning the model from the sample in the background, since large
+    /// samples take a while.
+    Learning {
+        model: Task<WfcModel<T>>,
+        width: u32,
+        height: u32,
+    },
+    Running(Box<WfcSolver<T>>),
+    /// The task can never succeed, and an error has been logged.
+    Failed,
+}
+
+/// `WfcTask` runs Wave Function Collapse in the background, a few steps
+/// each frame, so generating a large map doesn't freeze the game. Spawn it
+/// on an entity and add a [`WfcPlugin`] for the same tile type.
+///
+/// While a task is running, the loading screen of a
+/// [`GameStatePlugin`](crate::GameStatePlugin) shows its progress and
+/// waits for it to finish. If the solver fails, it is reset and tries
+/// again. Tasks that can never succeed, because the sample image can't be
+/// read or the rules contradict each other, log an error and stop with
+/// [`WfcStatus::Failed`].
+///
+/// ## Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use my_library::*;
+///
+/// // AssetManager::new().add_image("sample", "cave_sample.png")?
+/// fn start(mut commands: Commands) {
+///     // Black pixels become walls, and anything else floor.
+///     commands.spawn(WfcTask::from_image("Digging caves", "sample", 3, 80, 50, |pixel| {
+///         match pixel {
+///             [0, 0, 0, _] => Tile::Wall,
+///             _ => Tile::Floor,
+///         }
+///     }));
+/// }
+///
+/// fn spawn_level(tasks: Query<&WfcTask<Tile>>) {
+///     let map = tasks.single().unwrap().result().unwrap();
+/// }
+/// ```
+#[derive(Component)]
+pub struct WfcTask<T: Send + Sync + 'static> {
+    label: String,
+    steps_per_frame: usize,
+    state: TaskState<T>,
+}
+
+impl<T: Clone + Eq + Hash + Send + Sync + 'static> WfcTask<T> {
+    /// Creates a task that runs `solver`. `label` is shown on the loading
+    /// screen.
+    pub fn new<S: ToString>(label: S, solver: WfcSolver<T>) -> Self {
+        Self {
+            label: label.to_string(),
+            steps_per_frame: 50,
+            state: TaskState::Running(Box::new(solver)),
+        }
+    }
+
+    /// Creates a task that learns an overlapping model from an image in
+    /// the [`AssetStore`], once it has loaded, and uses it to generate a
+    /// `width` by `height` map. `convert` turns each sRGBA pixel into a
+    /// tile. See [`WfcModel::from_sample`]. The model is learned in the
+    /// background, as large samples take a while.
+    pub fn from_image<S: ToString>(
+        label: S,
+        image_tag: S,
+        pattern_size: u32,
+        width: u32,
+        height: u32,
+        convert: fn([u8; 4]) -> T,
+    ) -> Self {
+        Self {
+            label: label.to_string(),
+            steps_per_frame: 50,
+            state: TaskState::Waiting {
+                image_tag: image_tag.to_string(),
+                pattern_size,
+                width,
+                height,
+                convert,
+            },
+        }
+    }
+
+    /// Sets how many cells are decided each frame (50 by default).
+    pub fn with_steps_per_frame(mut self, steps: usize) -> Self {
+        self.steps_per_frame = steps.max(1);
+        self
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// The solver, once the task has started.
+    pub fn solver(&self) -> Option<&WfcSolver<T>> {
+        match &self.state {
+            TaskState::Running(solver) => Some(solver),
+            _ => None,
+        }
+    }
+
+    pub fn status(&self) -> WfcStatus {
+        match &self.state {
+            TaskState::Running(solver) => solver.status(),
+            TaskState::Failed => WfcStatus::Failed,
+            _ => WfcStatus::Running,
+        }
+    }
+
+    /// The share of the map that has been generated, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        self.solver().map_or(0.0, |solver| solver.progress())
+    }
+
+    /// The finished map, once the task is done.
+    pub fn result(&self) -> Option<Grid<T>> {
+        self.solver().and_then(|solver| solver.result())
+    }
+
+    /// Starts learning the model once the sample image is available, and
+    /// builds the solver once it has been learned.
+    fn start(
+        &mut self,
+        assets: Option<&AssetStore>,
+        loaded_assets: &LoadedAssets,
+        images: &Assets<Image>,
+    ) {
+        match &mut self.state {
+            TaskState::Waiting {
+                image_tag,
+                pattern_size,
+                width,
+                height,
+                convert,
+            } => {
+                let Some(image) = assets
+                    .and_then(|assets| assets.get_handle::<Image>(image_tag, loaded_assets))
+                    .and_then(|handle| images.get(&handle))
+                else {
+                    return;
+                };
+                let sample = match Grid::from_image(image) {
+                    Ok(sample) => sample.map(|_, pixel| convert(*pixel)),
+                    Err(error) => {
+                        error!(
+                            "{}: can't read {image_tag} as a sample: {error}",
+                            self.label
+                        );
+                        self.state = TaskState::Failed;
+                        return;
+                    }
+                };
+                let pattern_size = *pattern_size;
+                let model = AsyncComputeTaskPool::get_or_init(TaskPool::default)
+                    .spawn(async move { WfcModel::from_sample(&sample, pattern_size) });
+                self.state = TaskState::Learning {
+                    model,
+                    width: *width,
+                    height: *height,
+                };
+            }
+            TaskState::Learning {
+                model,
+                width,
+                height,
+            } => {
+                if let Some(model) = block_on(future::poll_once(model)) {
+                    self.state =
+                        TaskState::Running(Box::new(WfcSolver::new(&model, *width, *height)));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `WfcPlugin` runs every [`WfcTask`] with tiles of type `T`. Tasks draw
+/// from their own [`SystemRng`] stream, so other systems drawing numbers
+/// don't change the maps they generate. They wait until there is a
+/// [`RandomNumberGenerator`] resource, so add
+/// [`RandomPlugin`](crate::RandomPlugin) as well. If the generator uses
+/// another backend, name it: `WfcPlugin::<Tile, DynamicRng>::default()`
+/// works with [`RandomPlugin::with_backend`](crate::RandomPlugin::with_backend).
+///
+/// ## Example
+///
+/// ```no_run
+/// use bevy::prelude::*;
+/// use my_library::*;
+///
+/// App::new()
+///     .add_plugins(DefaultPlugins)
+///     .add_plugins(RandomPlugin::new())
+///     .add_plugins(WfcPlugin::<Tile>::new())
+///     .run();
+/// ```
+pub struct WfcPlugin<T, R: RngBackend = DefaultRng>(PhantomData<(T, R)>);
+
+impl<T> WfcPlugin<T> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T, R: RngBackend> Default for WfcPlugin<T, R> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: Clone + Eq + Hash + Send + Sync + 'static, R: RngBackend> Plugin for WfcPlugin<T, R> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LoadingProgress>();
+        // Run before Update, so the loading screen sees new tasks on the
+        // frame they start.
+        app.add_systems(
+            PreUpdate,
+            run_wfc_tasks::<T, R>.run_if(resource_exists::<RandomNumberGenerator<R>>),
+        );
+        app.add_observer(forget_wfc_task::<T>);
+    }
+}
+
+fn run_wfc_tasks<T: Clone + Eq + Hash + Send + Sync + 'static, R: RngBackend>(
+    mut tasks: Query<(Entity, &mut WfcTask<T>)>,
+    mut rng: SystemRng<R>,
+    mut progress: ResMut<LoadingProgress>,
+    assets: Option<Res<AssetStore>>,
+    loaded_assets: Res<LoadedAssets>,
+    images: Res<Assets<Image>>,
+) {
+    for (entity, mut task) in tasks.iter_mut() {
+        if task.status() == WfcStatus::Done || matches!(task.state, TaskState::Failed) {
+            progress.finish(entity);
+            continue;
+        }
+        task.start(assets.as_deref(), &loaded_assets, &images);
+        let (label, steps) = (task.label.clone(), task.steps_per_frame);
+        if let TaskState::Running(solver) = &mut task.state
+            && solver.step(rng.get(), steps) == WfcStatus::Failed
+        {
+            warn!(
+                "{label} failed after {} backtracks, restarting",
+                solver.backtracks()
+            );
+            solver.reset();
+            if solver.status() == WfcStatus::Failed {
+                error!("{label} can never succeed: its rules contradict each other");
+                task.state = TaskState::Failed;
+            }
+        }
+        match task.status() {
+            WfcStatus::Running => progress.report(entity, &label, task.progress()),
+            _ => progress.finish(entity),
+        }
+    }
+}
+
+fn forget_wfc_task<T: Send + Sync + 'static>(
+    trigger: Trigger<OnRemove, WfcTask<T>>,
+    mut progress: ResMut<LoadingProgress>,
+) {
+    progress.finish(trigger.target());
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Tile;
+
+    #[test]
+    fn test_task() {
+        let model = WfcModel::new()
+            .with_tile(Tile::Wall, 1.0)
+            .with_tile(Tile::Floor, 1.0)
+            .with_neighbours(Tile::Wall, Tile::Floor)
+            .with_neighbours(Tile::Floor, Tile::Floor);
+        let mut app = App::new();
+        app.init_resource::<Assets<Image>>()
+            .init_resource::<LoadedAssets>()
+            .add_plugins(WfcPlugin::<Tile>::new());
+        let task = WfcTask::new("Level", WfcSolver::new(&model, 20, 20)).with_steps_per_frame(100);
+        let entity = app.world_mut().spawn(task).id();
+
+        // Tasks wait for a generator.
+        app.update();
+        assert!(app.world().resource::<LoadingProgress>().tasks().is_empty());
+        app.insert_resource(RandomNumberGenerator::seeded(1));
+        app.update();
+        let progress = app.world().resource::<LoadingProgress>();
+        assert_eq!(progress.tasks().len(), 1);
+        assert!(progress.tasks()[0].1 > 0.0);
+        for _ in 0..10 {
+            app.update();
+        }
+        assert!(app.world().resource::<LoadingProgress>().tasks().is_empty());
+        let task = app.world().get::<WfcTask<Tile>>(entity).unwrap();
+        assert_eq!(task.result().unwrap().size(), UVec2::new(20, 20));
+    }
+
+    #[test]
+    fn test_impossible_task() {
+        let mut app = App::new();
+        app.init_resource::<Assets<Image>>()
+            .init_resource::<LoadedAssets>()
+            .insert_resource(RandomNumberGenerator::seeded(1))
+            .add_plugins(WfcPlugin::<Tile>::new());
+        // A model with no tiles can't fill any cells.
+        let task = WfcTask::new("Level", WfcSolver::new(&WfcModel::<Tile>::new(), 4, 4));
+        let entity = app.world_mut().spawn(task).id();
+        app.update();
+        assert!(app.world().resource::<LoadingProgress>().tasks().is_empty());
+        let task = app.world().get::<WfcTask<Tile>>(entity).unwrap();
+        assert_eq!(task.status(), WfcStatus::Failed);
+    }
+}
@@ -0,0 +1,271 @@
+use crate::WeightedTable;
+use bevy::platform::collections::{HashMap, HashSet};
+use rand::Rng;
+use std::{collections::BTreeMap, hash::Hash};
+
+/// `MarkovChain` learns which item tends to follow each run of `order`
+/// items in its training sequences, and generates new sequences with the
+/// same local structure. Train it on the letters of words to make names,
+/// or on the words of sentences to make text.
+///
+/// Most games will want [`NameGenerator`], which wraps a chain of
+/// characters. Sequences are generated with [`MarkovChain::generate`].
+///
+/// ## Example
+///
+/// ```
+/// use my_library::*;
+/// let mut chain = MarkovChain::new(1);
+/// chain.train("the cat sat on the mat".split_whitespace());
+/// chain.train("the dog sat on the cat".split_whitespace());
+/// let mut rng = RandomNumberGenerator::seeded(1);
+/// let sentence = chain.generate(&mut rng, 20).unwrap();
+/// assert_eq!(sentence[0], "the");
+/// ```
+#[derive(Debug, Clone)]
+pub struct MarkovChain<T> {
+    order: usize,
+    /// How often each item (or the end, `None`) followed each context.
+    /// `BTreeMap` keeps the table order, and so the results, reproducible.
+    counts: HashMap<Vec<Option<T>>, BTreeMap<Option<T>, u32>>,
+    tables: HashMap<Vec<Option<T>>, WeightedTable<Option<T>>>,
+}
+
+impl<T: Clone + Ord + Hash> MarkovChain<T> {
+    /// Creates an empty chain that looks back `order` items. Higher orders
+    /// copy the training data more closely; 2 or 3 suits most names.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is zero.
+    pub fn new(order: usize) -> Self {
+        assert!(order > 0, "Markov chains must have an order of at least 1");
+        Self {
+            order,
+            counts: HashMap::new(),
+            tables: HashMap::new(),
+        }
+    }
+
+    /// The number of items the chain looks back.
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// Returns `true` if the chain hasn't been trained.
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    /// Learns from one sequence. Empty sequences are ignored.
+    pub fn train(&mut self, sequence: impl IntoIterator<Item = T>) {
+        let items: Vec<Option<T>> = sequence.into_iter().map(Some).collect();
+        if items.is_empty() {
+            return;
+        }
+        // Pad the start with `None`s, so the first items have a context
+        let mut context = vec![None; self.order];
+        let mut changed = HashSet::new();
+        for next in items.into_iter().chain(std::iter::once(None)) {
+            let counts = self.counts.entry(context.clone()).or_default();
+            *counts.entry(next.clone()).or_default() += 1;
+            changed.insert(context.clone());
+            context.remove(0);
+            context.push(next);
+        }
+        for context in changed {
+            let table = WeightedTable::from_weights(
+                self.counts[&context]
+                    .iter()
+                    .map(|(item, count)| (item.clone(), *count as f64)),
+            );
+            self.tables.insert(context, table);
+        }
+    }
+
+    /// Generates a sequence, giving up (and returning `None`) if the chain
+    /// is untrained, or the sequence grows longer than `max_length`.
+    pub fn generate<R: Rng>(&self, rng: &mut R, max_length: usize) -> Option<Vec<T>> {
+        let mut context = vec![None; self.order];
+        let mut sequence = Vec::new();
+        loop {
+            // Every context reached was seen in training, unless untrained
+            let next = self.tables.get(&context)?.sample_with(rng)?.clone();
+            match next {
+                Some(item) if sequence.len() < max_length => {
+                    sequence.push(item.clone());
+                    context.remove(0);
+                    context.push(Some(item));
+                }
+                Some(_) => return None,
+                None => return Some(sequence),
+            }
+        }
+    }
+}
+
+/// `NameGenerator` makes up names (for characters, planets, levels...)
+/// that sound like the words it was trained on, using a [`MarkovChain`]
+/// of letters.
+///
+/// Names are generated with [`NameGenerator::generate`], so the same seed
+/// produces the same names. Training words can come from
+/// code, or from a text file loaded as a [`WordList`](crate::WordList).
+///
+/// ## Example
+///
+/// ```
+/// use my_library::*;
+/// let names = NameGenerator::new(2)
+///     .with_length(4, 8)
+///     .with_words(["aldor", "belmar", "caldor", "dunmar", "elmor", "falmar"])
+///     .with_forbidden(["badword"]);
+/// let mut rng = RandomNumberGenerator::seeded(1);
+/// let name = names.generate(&mut rng).unwrap();
+/// assert!((4..=8).contains(&name.chars().count()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct NameGenerator {
+    chain: MarkovChain<char>,
+    min_length: usize,
+    max_length: usize,
+    forbidden: HashSet<String>,
+    attempts: u32,
+}
+
+impl NameGenerator {
+    /// Creates an untrained generator that looks back `order` letters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is zero.
+    pub fn new(order: usize) -> Self {
+        Self {
+            chain: MarkovChain::new(order),
+            min_length: 1,
+            max_length: 20,
+            forbidden: HashSet::new(),
+            attempts: 100,
+        }
+    }
+
+    /// Only generates names with between `min` and `max` letters
+    /// (inclusive). The defaults are 1 and 20.
+    pub fn with_length(mut self, min: usize, max: usize) -> Self {
+        self.min_length = min;
+        self.max_length = max;
+        self
+    }
+
+    /// Trains the generator on a list of words.
+    pub fn with_words<S: AsRef<str>>(mut self, words: impl IntoIterator<Item = S>) -> Self {
+        self.train(words);
+        self
+    }
+
+    /// Never generates any of these names. Matching ignores case.
+    pub fn with_forbidden<S: AsRef<str>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        self.forbidden
+            .extend(names.into_iter().map(|name| name.as_ref().to_lowercase()));
+        self
+    }
+
+    /// How many names to try before giving up, when names keep breaking
+    /// the rules. The default is 100.
+    pub fn with_attempts(mut self, attempts: u32) -> Self {
+        self.attempts = attempts;
+        self
+    }
+
+    /// Trains the generator on more words. Surrounding whitespace is
+    /// ignored.
+    pub fn train<S: AsRef<str>>(&mut self, words: impl IntoIterator<Item = S>) {
+        for word in words {
+            self.chain.train(word.as_ref().trim().chars());
+        }
+    }
+
+    /// The underlying chain of letters.
+    pub fn chain(&self) -> &MarkovChain<char> {
+        &self.chain
+    }
+
+    /// Generates a name, or `None` if no acceptable name was found (for
+    /// example, if the generator hasn't been trained).
+    pub fn generate<R: Rng>(&self, rng: &mut R) -> Option<String> {
+        (0..self.attempts).find_map(|_| {
+            let name: String = self
+                .chain
+                .generate(rng, self.max_length)?
+                .into_iter()
+                .collect();
+            let length = name.chars().count();
+            (length >= self.min_length && !self.forbidden.contains(&name.to_lowercase()))
+                .then_some(name)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::RandomNumberGenerator;
+    use rand::SeedableRng;
+
+    fn rng() -> rand::rngs::StdRng {
+        rand::rngs::StdRng::seed_from_u64(1)
+    }
+
+    #[test]
+    fn test_single_word() {
+        // With only one word to learn from, there's only one thing to say
+        let names = NameGenerator::new(2).with_words(["banana"]);
+        let mut rng = rng();
+        let name = names.generate(&mut rng).unwrap();
+        assert!(name.starts_with("ba") && name.ends_with("na"), "{name}");
+    }
+
+    #[test]
+    fn test_untrained() {
+        assert!(NameGenerator::new(2).generate(&mut rng()).is_none());
+        let chain = MarkovChain::<u32>::new(1);
+        assert!(chain.is_empty());
+        assert!(chain.generate(&mut rng(), 10).is_none());
+    }
+
+    #[test]
+    fn test_rules() {
+        let names = NameGenerator::new(1)
+            .with_length(3, 5)
+            .with_words(["abcdefgh", "ab", "abc", "ba", "cab"])
+            .with_forbidden(["ABC"]);
+        let mut rng = rng();
+        for _ in 0..1000 {
+            let name = names.generate(&mut rng).unwrap();
+            assert!((3..=5).contains(&name.len()), "{name}");
+            assert_ne!(name, "abc");
+        }
+        let impossible = NameGenerator::new(1)
+            .with_words(["abc"])
+            .with_forbidden(["abc"]);
+        assert!(impossible.generate(&mut rng).is_none());
+    }
+
+    #[test]
+    fn test_words() {
+        let mut chain = MarkovChain::new(2);
+        chain.train("the cat sat on the mat".split_whitespace());
+        let sentence = chain.generate(&mut rng(), 100).unwrap();
+        assert_eq!(sentence, ["the", "cat", "sat", "on", "the", "mat"]);
+    }
+
+    #[test]
+    fn test_reproducibility() {
+        let mut rng = (
+            RandomNumberGenerator::seeded(1),
+            RandomNumberGenerator::seeded(1),
+        );
+        let names = NameGenerator::new(2).with_words(["aldor", "belmar", "caldor", "elmor"]);
+        (0..100).for_each(|_| assert_eq!(names.generate(&mut rng.0), names.generate(&mut rng.1)));
+    }
+}
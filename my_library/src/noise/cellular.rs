@@ -0,0 +1,151 @@
+use super::{Noise, PermutationTable, hash_to_unit};
+use bevy::math::{Vec2, Vec3};
+
+/// Which value [`Cellular`] noise returns for a point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CellularReturn {
+    /// Distance to the nearest feature point (F1).
+    Distance,
+    /// Distance to the second-nearest feature point (F2).
+    Distance2,
+    /// F2 - F1, which draws the edges between cells.
+    Distance2Sub,
+    /// A random value shared by every point in the nearest cell, producing
+    /// flat Voronoi regions.
+    CellValue,
+}
+
+/// Cellular (Worley) noise. Each grid cell contains a randomly placed
+/// feature point, and the noise is derived from the distances to the
+/// nearest feature points.
+#[derive(Clone, Debug)]
+pub struct Cellular {
+    table: PermutationTable,
+    return_type: CellularReturn,
+    jitter: f32,
+}
+
+impl Cellular {
+    /// Creates a cellular noise generator with the specified seed,
+    /// returning the distance to the nearest feature point.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            table: PermutationTable::new(seed),
+            return_type: CellularReturn::Distance,
+            jitter: 1.0,
+        }
+    }
+
+    /// Selects the value the noise returns.
+    pub fn with_return(mut self, return_type: CellularReturn) -> Self {
+        self.return_type = return_type;
+        self
+    }
+
+    /// Sets how far feature points may stray from the center of their
+    /// cell, from 0.0 (a regular grid) to 1.0 (anywhere in the cell).
+    pub fn with_jitter(mut self, jitter: f32) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Offsets a feature point within its cell, from a hash.
+    fn offset(&self, hash: usize) -> f32 {
+        0.5 + (hash as f32 / 255.0 - 0.5) * self.jitter
+    }
+
+    /// Turns the nearest distances (and nearest cell hash) into a result.
+    fn finish(&self, f1: f32, f2: f32, nearest: usize) -> f32 {
+        let n = match self.return_type {
+            CellularReturn::Distance => f1 * 2.0 - 1.0,
+            CellularReturn::Distance2 => f2 * 2.0 - 1.0,
+            CellularReturn::Distance2Sub => (f2 - f1) * 2.0 - 1.0,
+            CellularReturn::CellValue => hash_to_unit(nearest),
+        };
+        n.clamp(-1.0, 1.0)
+    }
+}
+
+/// Tracks the nearest and second-nearest feature points.
+struct Nearest {
+    f1: f32,
+    f2: f32,
+    hash: usize,
+}
+
+impl Nearest {
+    fn new() -> Self {
+        Self {
+            f1: f32::MAX,
+            f2: f32::MAX,
+            hash: 0,
+        }
+    }
+
+    fn add(&mut self, distance: f32, hash: usize) {
+        if distance < self.f1 {
+            self.f2 = self.f1;
+            self.f1 = distance;
+            self.hash = hash;
+        } else if distance < self.f2 {
+            self.f2 = distance;
+        }
+    }
+}
+
+impl Noise for Cellular {
+    fn get_1d(&self, x: f32) -> f32 {
+        let cell = x.floor() as i32;
+        let mut nearest = Nearest::new();
+        for dx in -1..=1 {
+            let hash = self.table.hash1(cell.wrapping_add(dx));
+            let feature = cell as f32 + dx as f32 + self.offset(hash);
+            nearest.add((feature - x).abs(), hash);
+        }
+        self.finish(nearest.f1, nearest.f2, nearest.hash)
+    }
+
+    fn get_2d(&self, point: Vec2) -> f32 {
+        let (i, j) = (point.x.floor() as i32, point.y.floor() as i32);
+        let mut nearest = Nearest::new();
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let hash = self.table.hash2(i.wrapping_add(dx), j.wrapping_add(dy));
+                let feature = Vec2::new(
+                    i as f32 + dx as f32 + self.offset(hash),
+                    j as f32 + dy as f32 + self.offset(self.table.rehash(hash)),
+                );
+                nearest.add(feature.distance(point), hash);
+            }
+        }
+        self.finish(nearest.f1, nearest.f2, nearest.hash)
+    }
+
+    fn get_3d(&self, point: Vec3) -> f32 {
+        let (i, j, k) = (
+            point.x.floor() as i32,
+            point.y.floor() as i32,
+            point.z.floor() as i32,
+        );
+        let mut nearest = Nearest::new();
+        for dz in -1..=1 {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let hash = self.table.hash3(
+                        i.wrapping_add(dx),
+                        j.wrapping_add(dy),
+                        k.wrapping_add(dz),
+                    );
+                    let second = self.table.rehash(hash);
+                    let feature = Vec3::new(
+                        i as f32 + dx as f32 + self.offset(hash),
+                        j as f32 + dy as f32 + self.offset(second),
+                        k as f32 + dz as f32 + self.offset(self.table.rehash(second)),
+                    );
+                    nearest.add(feature.distance(point), hash);
+                }
+            }
+        }
+        self.finish(nearest.f1, nearest.f2, nearest.hash)
+    }
+}
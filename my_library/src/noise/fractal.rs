@@ -0,0 +1,184 @@
+use super::Noise;
+use bevy::math::{Vec2, Vec3};
+
+/// Shifts each octave, so that octaves don't all line up at the origin.
+const OCTAVE_OFFSET: Vec3 = Vec3::new(31.7, 17.3, 11.9);
+
+/// Offsets for the second and third warp axes, decorrelating them from
+/// the first.
+const WARP_OFFSET_Y: Vec3 = Vec3::new(5.2, 1.3, 7.7);
+const WARP_OFFSET_Z: Vec3 = Vec3::new(9.1, 3.4, 2.8);
+
+/// How [`Fractal`] noise combines its octaves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FractalType {
+    /// Fractional Brownian motion: a plain sum of octaves. Good for
+    /// rolling terrain and clouds.
+    Fbm,
+    /// Inverted absolute values, giving sharp ridges such as mountain
+    /// ranges.
+    Ridged,
+    /// Absolute values, giving billowy turbulence such as fire and smoke.
+    Turbulence,
+}
+
+/// `Fractal` layers several octaves of another noise source, each at a
+/// higher frequency and lower amplitude than the last, to add detail.
+#[derive(Clone, Debug)]
+pub struct Fractal<N> {
+    source: N,
+    kind: FractalType,
+    octaves: usize,
+    lacunarity: f32,
+    gain: f32,
+}
+
+impl<N: Noise> Fractal<N> {
+    /// Creates a fractal with 4 octaves, each double the frequency
+    /// (lacunarity) and half the amplitude (gain) of the last.
+    pub fn new(source: N, kind: FractalType) -> Self {
+        Self {
+            source,
+            kind,
+            octaves: 4,
+            lacunarity: 2.0,
+            gain: 0.5,
+        }
+    }
+
+    /// Fractional Brownian motion of the source noise.
+    pub fn fbm(source: N) -> Self {
+        Self::new(source, FractalType::Fbm)
+    }
+
+    /// Ridged fractal of the source noise.
+    pub fn ridged(source: N) -> Self {
+        Self::new(source, FractalType::Ridged)
+    }
+
+    /// Turbulence fractal of the source noise.
+    pub fn turbulence(source: N) -> Self {
+        Self::new(source, FractalType::Turbulence)
+    }
+
+    /// Sets the number of octaves (at least 1).
+    pub fn with_octaves(mut self, octaves: usize) -> Self {
+        self.octaves = octaves.max(1);
+        self
+    }
+
+    /// Sets the frequency multiplier between octaves.
+    pub fn with_lacunarity(mut self, lacunarity: f32) -> Self {
+        self.lacunarity = lacunarity;
+        self
+    }
+
+    /// Sets the amplitude multiplier between octaves.
+    pub fn with_gain(mut self, gain: f32) -> Self {
+        self.gain = gain;
+        self
+    }
+
+    /// Sums the octaves, calling `sample(octave, frequency)` for each.
+    fn combine(&self, sample: impl Fn(f32, f32) -> f32) -> f32 {
+        let mut frequency = 1.0;
+        let mut amplitude = 1.0;
+        let mut total = 0.0;
+        let mut max = 0.0;
+        for octave in 0..self.octaves {
+            let n = sample(octave as f32, frequency);
+            total += amplitude
+                * match self.kind {
+                    FractalType::Fbm => n,
+                    FractalType::Ridged => (1.0 - n.abs()).powi(2),
+                    FractalType::Turbulence => n.abs(),
+                };
+            max += amplitude;
+            frequency *= self.lacunarity;
+            amplitude *= self.gain;
+        }
+        let n = total / max;
+        match self.kind {
+            FractalType::Fbm => n,
+            // Ridged and turbulence are 0..1, so rescale them
+            FractalType::Ridged | FractalType::Turbulence => n * 2.0 - 1.0,
+        }
+        .clamp(-1.0, 1.0)
+    }
+}
+
+impl<N: Noise> Noise for Fractal<N> {
+    fn get_1d(&self, x: f32) -> f32 {
+        self.combine(|octave, frequency| {
+            self.source.get_1d(x * frequency + octave * OCTAVE_OFFSET.x)
+        })
+    }
+
+    fn get_2d(&self, point: Vec2) -> f32 {
+        self.combine(|octave, frequency| {
+            self.source
+                .get_2d(point * frequency + octave * OCTAVE_OFFSET.truncate())
+        })
+    }
+
+    fn get_3d(&self, point: Vec3) -> f32 {
+        self.combine(|octave, frequency| {
+            self.source
+                .get_3d(point * frequency + octave * OCTAVE_OFFSET)
+        })
+    }
+}
+
+/// `DomainWarp` distorts the input coordinates of one noise source with
+/// another before sampling, producing swirling, organic shapes.
+///
+/// ## Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use my_library::*;
+/// let warped = DomainWarp::new(Fractal::fbm(Perlin::new(1)), Simplex::new(2), 0.75);
+/// let n = warped.get_2d(Vec2::new(3.5, 1.25));
+/// assert!((-1.0..=1.0).contains(&n));
+/// ```
+#[derive(Clone, Debug)]
+pub struct DomainWarp<N, W> {
+    source: N,
+    warp: W,
+    amplitude: f32,
+}
+
+impl<N: Noise, W: Noise> DomainWarp<N, W> {
+    /// Samples `source` at coordinates offset by `warp`, scaled by
+    /// `amplitude`.
+    pub fn new(source: N, warp: W, amplitude: f32) -> Self {
+        Self {
+            source,
+            warp,
+            amplitude,
+        }
+    }
+}
+
+impl<N: Noise, W: Noise> Noise for DomainWarp<N, W> {
+    fn get_1d(&self, x: f32) -> f32 {
+        self.source.get_1d(x + self.warp.get_1d(x) * self.amplitude)
+    }
+
+    fn get_2d(&self, point: Vec2) -> f32 {
+        let offset = Vec2::new(
+            self.warp.get_2d(point),
+            self.warp.get_2d(point + WARP_OFFSET_Y.truncate()),
+        );
+        self.source.get_2d(point + offset * self.amplitude)
+    }
+
+    fn get_3d(&self, point: Vec3) -> f32 {
+        let offset = Vec3::new(
+            self.warp.get_3d(point),
+            self.warp.get_3d(point + WARP_OFFSET_Y),
+            self.warp.get_3d(point + WARP_OFFSET_Z),
+        );
+        self.source.get_3d(point + offset * self.amplitude)
+    }
+}
@@ -0,0 +1,216 @@
+//! Coherent (smooth) noise generators, for terrain, caves, wobbling
+//! effects and other procedural content.
+//!
+//! Every generator implements [`Noise`], producing values in the range
+//! `-1.0..=1.0` for 1D, 2D and 3D inputs. Nearby inputs produce similar
+//! outputs, unlike values drawn directly from a
+//! [`RandomNumberGenerator`](crate::RandomNumberGenerator).
+//!
+//! Generators are seeded with a `u64`, which can be drawn from a
+//! [`RandomNumberGenerator`](crate::RandomNumberGenerator) so that noise
+//! is reproducible for a given game seed. The same seed produces the same
+//! noise regardless of the random number algorithm feature selected.
+//!
+//! ## Example
+//!
+//! ```
+//! use bevy::prelude::*;
+//! use my_library::*;
+//! let mut rng = RandomNumberGenerator::seeded(1);
+//! let terrain = Fractal::fbm(Perlin::new(rng.next())).with_octaves(5);
+//! let height = terrain.get_2d(Vec2::new(1.5, 2.25));
+//! assert!((-1.0..=1.0).contains(&height));
+//! ```
+use bevy::math::{Vec2, Vec3};
+use rand::{SeedableRng, seq::SliceRandom};
+
+mod cellular;
+mod fractal;
+mod perlin;
+mod simplex;
+mod value;
+pub use cellular::*;
+pub use fractal::*;
+pub use perlin::*;
+pub use simplex::*;
+pub use value::*;
+
+/// A source of coherent noise.
+pub trait Noise {
+    /// Samples the noise along a line.
+    fn get_1d(&self, x: f32) -> f32;
+
+    /// Samples the noise on a plane.
+    fn get_2d(&self, point: Vec2) -> f32;
+
+    /// Samples the noise in a volume.
+    fn get_3d(&self, point: Vec3) -> f32;
+}
+
+/// A shuffled table of the numbers 0 to 255, repeated twice so that
+/// lookups can be chained without wrapping. Used to hash lattice
+/// coordinates.
+#[derive(Clone)]
+pub(crate) struct PermutationTable {
+    perm: [u8; 512],
+}
+
+impl PermutationTable {
+    pub(crate) fn new(seed: u64) -> Self {
+        // ChaCha is portable and stable, so noise doesn't change with the
+        // selected random number generation feature.
+        let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(seed);
+        let mut values: Vec<u8> = (0..=255).collect();
+        values.shuffle(&mut rng);
+        let mut perm = [0; 512];
+        for (i, value) in perm.iter_mut().enumerate() {
+            *value = values[i & 255];
+        }
+        Self { perm }
+    }
+
+    pub(crate) fn hash1(&self, x: i32) -> usize {
+        self.perm[(x & 255) as usize] as usize
+    }
+
+    pub(crate) fn hash2(&self, x: i32, y: i32) -> usize {
+        self.perm[self.hash1(x) + (y & 255) as usize] as usize
+    }
+
+    pub(crate) fn hash3(&self, x: i32, y: i32, z: i32) -> usize {
+        self.perm[self.hash2(x, y) + (z & 255) as usize] as usize
+    }
+
+    /// A second, independent hash of an existing hash value.
+    pub(crate) fn rehash(&self, hash: usize) -> usize {
+        self.perm[hash + 1] as usize
+    }
+}
+
+impl std::fmt::Debug for PermutationTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("PermutationTable")
+    }
+}
+
+/// Perlin's quintic fade curve, `6t^5 - 15t^4 + 10t^3`.
+pub(crate) fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+pub(crate) fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + t * (b - a)
+}
+
+/// Maps a hash (0 to 255) into the range `-1.0..=1.0`.
+pub(crate) fn hash_to_unit(hash: usize) -> f32 {
+    hash as f32 / 127.5 - 1.0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn generators(seed: u64) -> Vec<Box<dyn Noise>> {
+        vec![
+            Box::new(Perlin::new(seed)),
+            Box::new(Simplex::new(seed)),
+            Box::new(Value::new(seed)),
+            Box::new(Cellular::new(seed)),
+            Box::new(Cellular::new(seed).with_return(CellularReturn::Distance2Sub)),
+            Box::new(Cellular::new(seed).with_return(CellularReturn::CellValue)),
+            Box::new(Fractal::fbm(Perlin::new(seed))),
+            Box::new(Fractal::ridged(Simplex::new(seed))),
+            Box::new(Fractal::turbulence(Value::new(seed))),
+            Box::new(DomainWarp::new(
+                Perlin::new(seed),
+                Simplex::new(seed + 1),
+                0.5,
+            )),
+        ]
+    }
+
+    fn points() -> impl Iterator<Item = Vec3> {
+        (0..2_000).map(|i| {
+            let i = i as f32;
+            Vec3::new(i * 0.137 - 100.0, i * 0.291 - 200.0, i * 0.073 - 50.0)
+        })
+    }
+
+    #[test]
+    fn test_bounds() {
+        for noise in generators(1) {
+            for p in points() {
+                for n in [
+                    noise.get_1d(p.x),
+                    noise.get_2d(p.truncate()),
+                    noise.get_3d(p),
+                ] {
+                    assert!((-1.0..=1.0).contains(&n), "{n} out of range");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_large_coordinates() {
+        // Lattice cells past `i32::MAX` mustn't overflow.
+        assert!(Perlin::new(1).get_1d(3.0e9).is_finite());
+        for noise in generators(1) {
+            for x in [3.0e9, -3.0e9, 1.0e20] {
+                for n in [
+                    noise.get_1d(x),
+                    noise.get_2d(Vec2::splat(x)),
+                    noise.get_3d(Vec3::splat(x)),
+                ] {
+                    assert!((-1.0..=1.0).contains(&n), "{n} out of range at {x}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_reproducible() {
+        let (a, b, c) = (generators(1), generators(1), generators(2));
+        for ((a, b), c) in a.iter().zip(b.iter()).zip(c.iter()) {
+            let differs = points().any(|p| a.get_3d(p) != c.get_3d(p));
+            assert!(differs);
+            for p in points() {
+                assert_eq!(a.get_1d(p.x), b.get_1d(p.x));
+                assert_eq!(a.get_2d(p.truncate()), b.get_2d(p.truncate()));
+                assert_eq!(a.get_3d(p), b.get_3d(p));
+            }
+        }
+    }
+
+    #[test]
+    fn test_smooth() {
+        let smooth: Vec<Box<dyn Noise>> = vec![
+            Box::new(Perlin::new(1)),
+            Box::new(Simplex::new(1)),
+            Box::new(Value::new(1)),
+            Box::new(Fractal::fbm(Perlin::new(1))),
+        ];
+        let step = Vec3::splat(0.001);
+        for noise in smooth {
+            for p in points() {
+                assert!((noise.get_1d(p.x) - noise.get_1d(p.x + step.x)).abs() < 0.05);
+                assert!(
+                    (noise.get_2d(p.truncate()) - noise.get_2d((p + step).truncate())).abs() < 0.05
+                );
+                assert!((noise.get_3d(p) - noise.get_3d(p + step)).abs() < 0.05);
+            }
+        }
+    }
+
+    #[test]
+    fn test_perlin_lattice_is_zero() {
+        let perlin = Perlin::new(1);
+        for i in -10..10 {
+            let i = i as f32;
+            assert_eq!(perlin.get_1d(i), 0.0);
+            assert_eq!(perlin.get_2d(Vec2::new(i, -i)), 0.0);
+            assert_eq!(perlin.get_3d(Vec3::new(i, 2.0 * i, -i)), 0.0);
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use super::{Noise, PermutationTable, fade, lerp};
+use bevy::math::{Vec2, Vec3};
+use std::f32::consts::{FRAC_1_SQRT_2, SQRT_2};
+
+/// Gradient directions for 2D Perlin noise.
+const GRADIENTS_2D: [Vec2; 8] = [
+    Vec2::new(1.0, 0.0),
+    Vec2::new(-1.0, 0.0),
+    Vec2::new(0.0, 1.0),
+    Vec2::new(0.0, -1.0),
+    Vec2::new(FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+    Vec2::new(-FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+    Vec2::new(FRAC_1_SQRT_2, -FRAC_1_SQRT_2),
+    Vec2::new(-FRAC_1_SQRT_2, -FRAC_1_SQRT_2),
+];
+
+/// Ken Perlin's 12 cube-edge gradients, padded to 16 to avoid a modulo.
+pub(crate) const GRADIENTS_3D: [Vec3; 16] = [
+    Vec3::new(1.0, 1.0, 0.0),
+    Vec3::new(-1.0, 1.0, 0.0),
+    Vec3::new(1.0, -1.0, 0.0),
+    Vec3::new(-1.0, -1.0, 0.0),
+    Vec3::new(1.0, 0.0, 1.0),
+    Vec3::new(-1.0, 0.0, 1.0),
+    Vec3::new(1.0, 0.0, -1.0),
+    Vec3::new(-1.0, 0.0, -1.0),
+    Vec3::new(0.0, 1.0, 1.0),
+    Vec3::new(0.0, -1.0, 1.0),
+    Vec3::new(0.0, 1.0, -1.0),
+    Vec3::new(0.0, -1.0, -1.0),
+    Vec3::new(1.0, 1.0, 0.0),
+    Vec3::new(0.0, -1.0, 1.0),
+    Vec3::new(-1.0, 1.0, 0.0),
+    Vec3::new(0.0, -1.0, -1.0),
+];
+
+/// Improved Perlin gradient noise.
+///
+/// Perlin noise is zero at every integer coordinate, so avoid sampling
+/// it only at whole numbers.
+#[derive(Clone, Debug)]
+pub struct Perlin {
+    table: PermutationTable,
+}
+
+impl Perlin {
+    /// Creates a Perlin noise generator with the specified seed.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            table: PermutationTable::new(seed),
+        }
+    }
+
+    fn gradient_1d(hash: usize, x: f32) -> f32 {
+        // Gradients between 1 and 2, either positive or negative
+        let gradient = 1.0 + (hash & 7) as f32 / 7.0;
+        if hash & 8 == 0 {
+            gradient * x
+        } else {
+            -gradient * x
+        }
+    }
+}
+
+impl Noise for Perlin {
+    fn get_1d(&self, x: f32) -> f32 {
+        let x0 = x.floor();
+        let i = x0 as i32;
+        let t = x - x0;
+        let n0 = Self::gradient_1d(self.table.hash1(i), t);
+        let n1 = Self::gradient_1d(self.table.hash1(i.wrapping_add(1)), t - 1.0);
+        lerp(n0, n1, fade(t)).clamp(-1.0, 1.0)
+    }
+
+    fn get_2d(&self, point: Vec2) -> f32 {
+        let cell = point.floor();
+        let (i, j) = (cell.x as i32, cell.y as i32);
+        let t = point - cell;
+        let corner = |dx: i32, dy: i32| {
+            let gradient =
+                GRADIENTS_2D[self.table.hash2(i.wrapping_add(dx), j.wrapping_add(dy)) & 7];
+            gradient.dot(t - Vec2::new(dx as f32, dy as f32))
+        };
+        let u = fade(t.x);
+        let v = fade(t.y);
+        let n = lerp(
+            lerp(corner(0, 0), corner(1, 0), u),
+            lerp(corner(0, 1), corner(1, 1), u),
+            v,
+        );
+        (n * SQRT_2).clamp(-1.0, 1.0)
+    }
+
+    fn get_3d(&self, point: Vec3) -> f32 {
+        let cell = point.floor();
+        let (i, j, k) = (cell.x as i32, cell.y as i32, cell.z as i32);
+        let t = point - cell;
+        let corner = |dx: i32, dy: i32, dz: i32| {
+            let gradient = GRADIENTS_3D[self.table.hash3(
+                i.wrapping_add(dx),
+                j.wrapping_add(dy),
+                k.wrapping_add(dz),
+            ) & 15];
+            gradient.dot(t - Vec3::new(dx as f32, dy as f32, dz as f32))
+        };
+        let u = fade(t.x);
+        let v = fade(t.y);
+        let w = fade(t.z);
+        let n = lerp(
+            lerp(
+                lerp(corner(0, 0, 0), corner(1, 0, 0), u),
+                lerp(corner(0, 1, 0), corner(1, 1, 0), u),
+                v,
+            ),
+            lerp(
+                lerp(corner(0, 0, 1), corner(1, 0, 1), u),
+                lerp(corner(0, 1, 1), corner(1, 1, 1), u),
+                v,
+            ),
+            w,
+        );
+        n.clamp(-1.0, 1.0)
+    }
+}
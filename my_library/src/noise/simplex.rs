@@ -0,0 +1,142 @@
+use super::{Noise, PermutationTable, perlin::GRADIENTS_3D};
+use bevy::math::{Vec2, Vec3};
+
+// Skewing factors, which map the simplex grid onto a square/cube grid
+const F2: f32 = 0.366_025_42; // (sqrt(3) - 1) / 2
+const G2: f32 = 0.211_324_87; // (3 - sqrt(3)) / 6
+const F3: f32 = 1.0 / 3.0;
+const G3: f32 = 1.0 / 6.0;
+
+/// Simplex noise, following Stefan Gustavson's reference implementation.
+/// Simplex noise has fewer directional artifacts than
+/// [`Perlin`](super::Perlin) noise, and is cheaper in higher dimensions.
+#[derive(Clone, Debug)]
+pub struct Simplex {
+    table: PermutationTable,
+}
+
+impl Simplex {
+    /// Creates a simplex noise generator with the specified seed.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            table: PermutationTable::new(seed),
+        }
+    }
+
+    fn gradient_1d(hash: usize, x: f32) -> f32 {
+        // Gradients between 1 and 8, either positive or negative
+        let gradient = 1.0 + (hash & 7) as f32;
+        if hash & 8 == 0 {
+            gradient * x
+        } else {
+            -gradient * x
+        }
+    }
+}
+
+impl Noise for Simplex {
+    fn get_1d(&self, x: f32) -> f32 {
+        let x0 = x.floor();
+        let i = x0 as i32;
+        let corner = |offset: f32, hash: usize| {
+            let d = x - x0 - offset;
+            let t = 1.0 - d * d;
+            let t2 = t * t;
+            t2 * t2 * Self::gradient_1d(hash, d)
+        };
+        let n = corner(0.0, self.table.hash1(i)) + corner(1.0, self.table.hash1(i.wrapping_add(1)));
+        (n * 0.395).clamp(-1.0, 1.0)
+    }
+
+    fn get_2d(&self, point: Vec2) -> f32 {
+        // Find the simplex cell containing the point
+        let s = (point.x + point.y) * F2;
+        let cell = (point + s).floor();
+        let (i, j) = (cell.x as i32, cell.y as i32);
+        let t = (cell.x + cell.y) * G2;
+        let d0 = point - (cell - t);
+
+        // Which of the two triangles in the square are we in?
+        let (i1, j1) = if d0.x > d0.y { (1, 0) } else { (0, 1) };
+        let d1 = d0 - Vec2::new(i1 as f32, j1 as f32) + G2;
+        let d2 = d0 - 1.0 + 2.0 * G2;
+
+        let corner = |d: Vec2, hash: usize| {
+            let t = 0.5 - d.length_squared();
+            if t < 0.0 {
+                0.0
+            } else {
+                let gradient = GRADIENTS_3D[hash % 12];
+                let t2 = t * t;
+                t2 * t2 * (gradient.x * d.x + gradient.y * d.y)
+            }
+        };
+        let n = corner(d0, self.table.hash2(i, j))
+            + corner(d1, self.table.hash2(i.wrapping_add(i1), j.wrapping_add(j1)))
+            + corner(d2, self.table.hash2(i.wrapping_add(1), j.wrapping_add(1)));
+        (70.0 * n).clamp(-1.0, 1.0)
+    }
+
+    fn get_3d(&self, point: Vec3) -> f32 {
+        // Find the simplex cell containing the point
+        let s = (point.x + point.y + point.z) * F3;
+        let cell = (point + s).floor();
+        let (i, j, k) = (cell.x as i32, cell.y as i32, cell.z as i32);
+        let t = (cell.x + cell.y + cell.z) * G3;
+        let d0 = point - (cell - t);
+
+        // Which of the six tetrahedra are we in?
+        let (o1, o2) = if d0.x >= d0.y {
+            if d0.y >= d0.z {
+                ((1, 0, 0), (1, 1, 0))
+            } else if d0.x >= d0.z {
+                ((1, 0, 0), (1, 0, 1))
+            } else {
+                ((0, 0, 1), (1, 0, 1))
+            }
+        } else if d0.y < d0.z {
+            ((0, 0, 1), (0, 1, 1))
+        } else if d0.x < d0.z {
+            ((0, 1, 0), (0, 1, 1))
+        } else {
+            ((0, 1, 0), (1, 1, 0))
+        };
+        let offset = |o: (i32, i32, i32)| Vec3::new(o.0 as f32, o.1 as f32, o.2 as f32);
+        let d1 = d0 - offset(o1) + G3;
+        let d2 = d0 - offset(o2) + 2.0 * G3;
+        let d3 = d0 - 1.0 + 3.0 * G3;
+
+        let corner = |d: Vec3, hash: usize| {
+            let t = 0.6 - d.length_squared();
+            if t < 0.0 {
+                0.0
+            } else {
+                let t2 = t * t;
+                t2 * t2 * GRADIENTS_3D[hash % 12].dot(d)
+            }
+        };
+        let n = corner(d0, self.table.hash3(i, j, k))
+            + corner(
+                d1,
+                self.table.hash3(
+                    i.wrapping_add(o1.0),
+                    j.wrapping_add(o1.1),
+                    k.wrapping_add(o1.2),
+                ),
+            )
+            + corner(
+                d2,
+                self.table.hash3(
+                    i.wrapping_add(o2.0),
+                    j.wrapping_add(o2.1),
+                    k.wrapping_add(o2.2),
+                ),
+            )
+            + corner(
+                d3,
+                self.table
+                    .hash3(i.wrapping_add(1), j.wrapping_add(1), k.wrapping_add(1)),
+            );
+        (32.0 * n).clamp(-1.0, 1.0)
+    }
+}
@@ -0,0 +1,74 @@
+use super::{Noise, PermutationTable, fade, hash_to_unit, lerp};
+use bevy::math::{Vec2, Vec3};
+
+/// Value noise: random values at each integer coordinate, smoothly
+/// interpolated in between. Cheaper than [`Perlin`](super::Perlin), but
+/// blockier.
+#[derive(Clone, Debug)]
+pub struct Value {
+    table: PermutationTable,
+}
+
+impl Value {
+    /// Creates a value noise generator with the specified seed.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            table: PermutationTable::new(seed),
+        }
+    }
+}
+
+impl Noise for Value {
+    fn get_1d(&self, x: f32) -> f32 {
+        let x0 = x.floor();
+        let i = x0 as i32;
+        lerp(
+            hash_to_unit(self.table.hash1(i)),
+            hash_to_unit(self.table.hash1(i.wrapping_add(1))),
+            fade(x - x0),
+        )
+    }
+
+    fn get_2d(&self, point: Vec2) -> f32 {
+        let cell = point.floor();
+        let (i, j) = (cell.x as i32, cell.y as i32);
+        let t = point - cell;
+        let corner = |dx: i32, dy: i32| {
+            hash_to_unit(self.table.hash2(i.wrapping_add(dx), j.wrapping_add(dy)))
+        };
+        let u = fade(t.x);
+        lerp(
+            lerp(corner(0, 0), corner(1, 0), u),
+            lerp(corner(0, 1), corner(1, 1), u),
+            fade(t.y),
+        )
+    }
+
+    fn get_3d(&self, point: Vec3) -> f32 {
+        let cell = point.floor();
+        let (i, j, k) = (cell.x as i32, cell.y as i32, cell.z as i32);
+        let t = point - cell;
+        let corner = |dx: i32, dy: i32, dz: i32| {
+            hash_to_unit(self.table.hash3(
+                i.wrapping_add(dx),
+                j.wrapping_add(dy),
+                k.wrapping_add(dz),
+            ))
+        };
+        let u = fade(t.x);
+        let v = fade(t.y);
+        lerp(
+            lerp(
+                lerp(corner(0, 0, 0), corner(1, 0, 0), u),
+                lerp(corner(0, 1, 0), corner(1, 1, 0), u),
+                v,
+            ),
+            lerp(
+                lerp(corner(0, 0, 1), corner(1, 0, 1), u),
+                lerp(corner(0, 1, 1), corner(1, 1, 1), u),
+                v,
+            ),
+            fade(t.z),
+        )
+    }
+}
@@ -0,0 +1,376 @@
+use crate::{AxisAlignedBoundingBox, Rect2D};
+use bevy::prelude::*;
+use rand::Rng;
+use std::{
+    collections::HashMap,
+    f32::consts::{SQRT_2, TAU},
+};
+
+/// `PoissonDisk` scatters points over a region so that no two are closer
+/// than a minimum spacing, while still looking random ("blue noise"). It
+/// is useful for placing objects that must not start out overlapping.
+///
+/// Points are generated with Bridson's algorithm, by passing the sampler
+/// to [`RandomNumberGenerator::poisson_disk`](crate::RandomNumberGenerator::poisson_disk).
+///
+/// ## Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use my_library::*;
+/// let region = Rect2D::new(Vec2::new(-512.0, -384.0), Vec2::new(512.0, 384.0));
+/// let sampler = PoissonDisk::new(region, 16.0).with_limit(100);
+/// let mut rng = RandomNumberGenerator::new();
+/// let positions = rng.poisson_disk(&sampler);
+/// assert_eq!(positions.len(), 100);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PoissonDisk {
+    region: Rect2D,
+    spacing: f32,
+    attempts: u32,
+    limit: Option<usize>,
+    points: Vec<Vec2>,
+    obstacles: Vec<Rect2D>,
+    footprint: Vec2,
+}
+
+impl PoissonDisk {
+    /// Creates a sampler that fills `region` with points at least
+    /// `spacing` apart.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `spacing` is not positive and finite.
+    pub fn new(region: Rect2D, spacing: f32) -> Self {
+        assert!(
+            spacing.is_finite() && spacing > 0.0,
+            "Poisson disk spacing must be positive, found {spacing}"
+        );
+        Self {
+            region,
+            spacing,
+            attempts: 30,
+            limit: None,
+            points: Vec::new(),
+            obstacles: Vec::new(),
+            footprint: Vec2::ZERO,
+        }
+    }
+
+    /// Sets how many candidates are tried around each point before giving
+    /// up on it (30 by default). Fewer attempts are faster, but leave more
+    /// gaps.
+    pub fn with_attempts(mut self, attempts: u32) -> Self {
+        self.attempts = attempts.max(1);
+        self
+    }
+
+    /// Stops after `limit` points. Sampling starts from a random point and
+    /// grows outward, so a few points are placed near each other rather
+    /// than across the whole region. Fewer points are returned if the
+    /// region is full.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Adds existing points, such as the positions of objects that have
+    /// already been placed. New points keep at least `spacing` away from
+    /// them, but they are not returned.
+    pub fn with_points<I: IntoIterator<Item = Vec2>>(mut self, points: I) -> Self {
+        self.points.extend(points);
+        self
+    }
+
+    /// Adds rectangles that new points (and their footprint) must not
+    /// overlap.
+    pub fn with_obstacles<I: IntoIterator<Item = Rect2D>>(mut self, obstacles: I) -> Self {
+        self.obstacles.extend(obstacles);
+        self
+    }
+
+    /// Adds the bounding boxes of existing entities as obstacles, typically
+    /// from a `Query<(&Transform, &AxisAlignedBoundingBox)>`.
+    pub fn with_bounding_boxes<'a, I>(self, boxes: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a Transform, &'a AxisAlignedBoundingBox)>,
+    {
+        self.with_obstacles(
+            boxes
+                .into_iter()
+                .map(|(transform, bbox)| bbox.as_rect(transform.translation.truncate())),
+        )
+    }
+
+    /// Sets the size of the object that will be placed at each point, so
+    /// that it doesn't overlap obstacles. By default points are treated as
+    /// having no size.
+    pub fn with_footprint(mut self, width: f32, height: f32) -> Self {
+        self.footprint = Vec2::new(width, height) / 2.0;
+        self
+    }
+
+    /// Generates the points, using Bridson's algorithm.
+    pub(crate) fn sample_with<R: Rng>(&self, rng: &mut R) -> Vec<Vec2> {
+        let mut grid = Grid::new(self.region, self.spacing);
+        self.points.iter().for_each(|point| grid.insert(*point));
+
+        let mut samples = Vec::new();
+        let mut active = Vec::new();
+        let mut misses = 0;
+        while self.limit.is_none_or(|limit| samples.len() < limit) {
+            if active.is_empty() {
+                // Start (or restart, after filling one area) from a random
+                // point, until the region appears to be full.
+                if misses >= self.attempts {
+                    break;
+                }
+                let (min, max) = (self.region.min(), self.region.max());
+                let candidate = Vec2::new(
+                    rng.random_range(min.x..=max.x),
+                    rng.random_range(min.y..=max.y),
+                );
+                if self.is_valid(candidate, &grid) {
+                    grid.insert(candidate);
+                    samples.push(candidate);
+                    active.push(candidate);
+                    misses = 0;
+                } else {
+                    misses += 1;
+                }
+                continue;
+            }
+
+            let index = rng.random_range(0..active.len());
+            let origin = active[index];
+            let found = (0..self.attempts)
+                .map(|_| {
+                    // Uniform over the annulus between spacing and 2 * spacing
+                    let (inner, outer) = (self.spacing.powi(2), (2.0 * self.spacing).powi(2));
+                    let distance = rng.random_range(inner..outer).sqrt();
+                    origin + Vec2::from_angle(rng.random_range(0.0..TAU)) * distance
+                })
+                .find(|candidate| self.is_valid(*candidate, &grid));
+            match found {
+                Some(candidate) => {
+                    grid.insert(candidate);
+                    samples.push(candidate);
+                    active.push(candidate);
+                }
+                None => {
+                    active.swap_remove(index);
+                }
+            }
+        }
+        samples
+    }
+
+    fn is_valid(&self, candidate: Vec2, grid: &Grid) -> bool {
+        if !self.region.contains(candidate) {
+            return false;
+        }
+        let footprint = Rect2D::new(candidate - self.footprint, candidate + self.footprint);
+        if self
+            .obstacles
+            .iter()
+            .any(|obstacle| obstacle.intersect(&footprint))
+        {
+            return false;
+        }
+        grid.nearby(candidate)
+            .all(|point| point.distance_squared(candidate) >= self.spacing.powi(2))
+    }
+}
+
+/// The most cells a [`Grid`] allocates up front. Regions needing more
+/// (a huge region, or a tiny spacing) only store the cells in use.
+const MAX_DENSE_CELLS: usize = 1 << 20;
+
+/// The points in each cell of a [`Grid`].
+enum Cells {
+    /// Every cell, row by row.
+    Dense(Vec<Vec<Vec2>>),
+    /// Only the cells holding points.
+    Sparse(HashMap<(usize, usize), Vec<Vec2>>),
+}
+
+/// A background grid for finding nearby points quickly. Cells are small
+/// enough that only points within two cells can be closer than `spacing`.
+struct Grid {
+    origin: Vec2,
+    cell_size: f32,
+    columns: usize,
+    rows: usize,
+    cells: Cells,
+}
+
+impl Grid {
+    fn new(region: Rect2D, spacing: f32) -> Self {
+        let cell_size = spacing / SQRT_2;
+        let size = region.size();
+        let columns = (size.x / cell_size).ceil().max(1.0) as usize;
+        let rows = (size.y / cell_size).ceil().max(1.0) as usize;
+        let cells = match columns.checked_mul(rows) {
+            Some(count) if count <= MAX_DENSE_CELLS => Cells::Dense(vec![Vec::new(); count]),
+            _ => Cells::Sparse(HashMap::new()),
+        };
+        Self {
+            origin: region.min(),
+            cell_size,
+            columns,
+            rows,
+            cells,
+        }
+    }
+
+    /// The cell containing a point. Points outside the region are placed
+    /// in the nearest edge cell.
+    fn cell(&self, point: Vec2) -> (usize, usize) {
+        let cell = ((point - self.origin) / self.cell_size).floor();
+        (
+            (cell.x.max(0.0) as usize).min(self.columns - 1),
+            (cell.y.max(0.0) as usize).min(self.rows - 1),
+        )
+    }
+
+    fn insert(&mut self, point: Vec2) {
+        let (x, y) = self.cell(point);
+        match &mut self.cells {
+            Cells::Dense(cells) => cells[y * self.columns + x].push(point),
+            Cells::Sparse(cells) => cells.entry((x, y)).or_default().push(point),
+        }
+    }
+
+    fn points(&self, x: usize, y: usize) -> &[Vec2] {
+        match &self.cells {
+            Cells::Dense(cells) => &cells[y * self.columns + x],
+            Cells::Sparse(cells) => cells.get(&(x, y)).map_or(&[], Vec::as_slice),
+        }
+    }
+
+    fn nearby(&self, point: Vec2) -> impl Iterator<Item = &Vec2> {
+        let (x, y) = self.cell(point);
+        let columns = x.saturating_sub(2)..=x.saturating_add(2).min(self.columns - 1);
+        let rows = y.saturating_sub(2)..=y.saturating_add(2).min(self.rows - 1);
+        rows.flat_map(move |y| columns.clone().flat_map(move |x| self.points(x, y).iter()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn rng() -> rand::rngs::StdRng {
+        rand::rngs::StdRng::seed_from_u64(1)
+    }
+
+    fn region() -> Rect2D {
+        Rect2D::new(Vec2::new(-100.0, -50.0), Vec2::new(100.0, 50.0))
+    }
+
+    fn assert_spaced(points: &[Vec2], others: &[Vec2], spacing: f32) {
+        for (i, a) in points.iter().enumerate() {
+            for b in points[i + 1..].iter().chain(others.iter()) {
+                assert!(a.distance(*b) >= spacing, "{a} is too close to {b}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_spacing() {
+        let points = PoissonDisk::new(region(), 10.0).sample_with(&mut rng());
+        assert!(points.iter().all(|point| region().contains(*point)));
+        assert_spaced(&points, &[], 10.0);
+        // A 200x100 region should comfortably fit this many points
+        assert!(points.len() > 100, "{}", points.len());
+    }
+
+    #[test]
+    fn test_limit() {
+        let points = PoissonDisk::new(region(), 10.0)
+            .with_limit(20)
+            .sample_with(&mut rng());
+        assert_eq!(points.len(), 20);
+        let points = PoissonDisk::new(region(), 60.0)
+            .with_limit(1000)
+            .sample_with(&mut rng());
+        assert!(points.len() < 1000);
+    }
+
+    #[test]
+    fn test_limit_stops_early() {
+        struct Counting(rand::rngs::StdRng, usize);
+        impl rand::RngCore for Counting {
+            fn next_u32(&mut self) -> u32 {
+                self.1 += 1;
+                self.0.next_u32()
+            }
+            fn next_u64(&mut self) -> u64 {
+                self.1 += 1;
+                self.0.next_u64()
+            }
+            fn fill_bytes(&mut self, dst: &mut [u8]) {
+                self.1 += 1;
+                self.0.fill_bytes(dst)
+            }
+        }
+        let mut rng = Counting(rng(), 0);
+        let points = PoissonDisk::new(region(), 1.0)
+            .with_limit(1)
+            .sample_with(&mut rng);
+        assert_eq!(points.len(), 1);
+        // Just the first point's coordinates
+        assert_eq!(rng.1, 2);
+    }
+
+    #[test]
+    fn test_existing_points() {
+        let existing: Vec<Vec2> = (-9..=9).map(|x| Vec2::new(x as f32 * 10.0, 0.0)).collect();
+        let points = PoissonDisk::new(region(), 10.0)
+            .with_points(existing.iter().copied())
+            .sample_with(&mut rng());
+        assert!(!points.is_empty());
+        assert_spaced(&points, &existing, 10.0);
+    }
+
+    #[test]
+    fn test_obstacles() {
+        let obstacle = Rect2D::new(Vec2::new(-50.0, -50.0), Vec2::new(50.0, 50.0));
+        let points = PoissonDisk::new(region(), 5.0)
+            .with_obstacles([obstacle])
+            .with_footprint(4.0, 4.0)
+            .sample_with(&mut rng());
+        assert!(!points.is_empty());
+        for point in points {
+            let footprint = Rect2D::new(point - Vec2::splat(2.0), point + Vec2::splat(2.0));
+            assert!(!footprint.intersect(&obstacle), "{point}");
+        }
+        // Both sides of the obstacle should be filled
+        let blocked = PoissonDisk::new(region(), 5.0).with_obstacles([obstacle]);
+        let points = blocked.sample_with(&mut rng());
+        assert!(points.iter().any(|point| point.x < 0.0));
+        assert!(points.iter().any(|point| point.x > 0.0));
+    }
+
+    #[test]
+    fn test_huge_region() {
+        // Far too many cells to allocate, so only the used ones are stored.
+        let huge = Rect2D::new(Vec2::splat(-1.0e6), Vec2::splat(1.0e6));
+        let points = PoissonDisk::new(huge, 4.0)
+            .with_limit(50)
+            .sample_with(&mut rng());
+        assert_eq!(points.len(), 50);
+        assert_spaced(&points, &[], 4.0);
+    }
+
+    #[test]
+    fn test_reproducibility() {
+        let sampler = PoissonDisk::new(region(), 10.0);
+        assert_eq!(
+            sampler.sample_with(&mut rng()),
+            sampler.sample_with(&mut rng())
+        );
+    }
+}
@@ -0,0 +1,595 @@
+use crate::{DiceExpr, DiceGroup, DiceSelection, DiceTerm};
+use rand::Rng;
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    sync::{Arc, LazyLock, Mutex, MutexGuard, PoisonError},
+};
+
+/// Outcomes less likely than this are left out of distributions. Exploding
+/// dice can roll forever in theory, so the chance of the very longest
+/// chains has to be cut off somewhere.
+const NEGLIGIBLE: f64 = 1e-18;
+
+/// The most totals a distribution, or any table used to work one out, may
+/// have. `1d4000000000` is far beyond it.
+pub const MAX_DISTRIBUTION_TOTALS: u64 = 10_000_000;
+
+/// The most steps working out a distribution may take, roughly one per
+/// pair of chances multiplied together. `100d20kh50` takes about 2 x 10^8;
+/// `1000d1000kh999` is far beyond it.
+pub const MAX_DISTRIBUTION_STEPS: u64 = 1_000_000_000;
+
+/// How many distributions are remembered. The cache starts again once it
+/// is full.
+const CACHE_SIZE: usize = 64;
+
+/// Distributions that have already been worked out, by expression.
+static CACHE: LazyLock<DistributionCache> = LazyLock::new(Default::default);
+
+/// Remembers up to [`CACHE_SIZE`] distributions, by expression.
+#[derive(Default)]
+struct DistributionCache(Mutex<HashMap<DiceExpr, Arc<DiceDistribution>>>);
+
+impl DistributionCache {
+    /// Locks the cache. It only ever holds finished distributions, so it
+    /// is still usable if another thread panicked while holding it.
+    fn lock(&self) -> MutexGuard<'_, HashMap<DiceExpr, Arc<DiceDistribution>>> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn get(&self, expr: &DiceExpr) -> Option<Arc<DiceDistribution>> {
+        self.lock().get(expr).cloned()
+    }
+
+    fn insert(&self, expr: &DiceExpr, distribution: Arc<DiceDistribution>) {
+        let mut cache = self.lock();
+        if cache.len() >= CACHE_SIZE {
+            cache.clear();
+        }
+        cache.insert(expr.clone(), distribution);
+    }
+}
+
+/// Keeps track of the work done on a distribution, so that expressions too
+/// large to work out fail straight away instead of running out of time or
+/// memory.
+struct Budget<'a> {
+    expr: &'a DiceExpr,
+    steps: u64,
+}
+
+impl Budget<'_> {
+    /// Records `steps` more steps of work, building a table of `totals`
+    /// entries.
+    fn spend(&mut self, steps: u64, totals: u64) -> anyhow::Result<()> {
+        self.steps = self.steps.saturating_add(steps);
+        if self.steps > MAX_DISTRIBUTION_STEPS || totals > MAX_DISTRIBUTION_TOTALS {
+            return Err(anyhow::Error::msg(format!(
+                "{} is too large to work out exactly; estimate it with DiceExpr::simulate instead",
+                self.expr
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// `DiceDistribution` gives the chance of every total a [`DiceExpr`] can
+/// roll. It is worked out exactly (not by rolling lots of dice), so AI
+/// players can weigh their options precisely. Totals less likely than 1 in
+/// 10^18, such as the longest runs of exploding dice, are left out.
+///
+/// Get one with [`DiceExpr::distribution`], or estimate one by rolling
+/// with [`DiceExpr::simulate`].
+///
+/// ## Example
+///
+/// ```
+/// use my_library::*;
+/// let expr: DiceExpr = "2d6".parse().unwrap();
+/// let odds = expr.distribution().unwrap();
+/// assert!((odds.chance(7) - 6.0 / 36.0).abs() < 1e-12);
+/// assert!((odds.mean() - 7.0).abs() < 1e-12);
+/// println!("{:.1}% chance of 10 or more", odds.chance_at_least(10) * 100.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiceDistribution {
+    min: i64,
+    /// The chance of each total, starting from `min`.
+    chances: Vec<f64>,
+}
+
+impl DiceDistribution {
+    fn constant(value: i64) -> Self {
+        Self {
+            min: value,
+            chances: vec![1.0],
+        }
+    }
+
+    /// A die with faces `1..=faces`.
+    fn uniform(faces: u32, budget: &mut Budget) -> anyhow::Result<Self> {
+        budget.spend(faces as u64, faces as u64)?;
+        Ok(Self {
+            min: 1,
+            chances: vec![1.0 / faces as f64; faces as usize],
+        })
+    }
+
+    /// Counts how often each total appears, such as the totals of many
+    /// simulated rolls.
+    pub(crate) fn from_totals(totals: impl IntoIterator<Item = i64>) -> Self {
+        let mut counts = HashMap::new();
+        totals
+            .into_iter()
+            .for_each(|total| *counts.entry(total).or_insert(0u64) += 1);
+        let (Some(min), Some(max)) = (counts.keys().min(), counts.keys().max()) else {
+            return Self::constant(0);
+        };
+        let rolls: u64 = counts.values().sum();
+        Self {
+            min: *min,
+            chances: (*min..=*max)
+                .map(|total| counts.get(&total).copied().unwrap_or(0) as f64 / rolls as f64)
+                .collect(),
+        }
+    }
+
+    /// The lowest total that can be rolled.
+    pub fn min(&self) -> i64 {
+        self.min
+    }
+
+    /// The highest total that can be rolled.
+    pub fn max(&self) -> i64 {
+        self.min + self.chances.len() as i64 - 1
+    }
+
+    /// The chance of rolling exactly `total`, from 0 to 1.
+    pub fn chance(&self, total: i64) -> f64 {
+        usize::try_from(total - self.min)
+            .ok()
+            .and_then(|index| self.chances.get(index))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// The chance of rolling `total` or more.
+    pub fn chance_at_least(&self, total: i64) -> f64 {
+        self.iter()
+            .filter(|(value, _)| *value >= total)
+            .map(|(_, chance)| chance)
+            .sum()
+    }
+
+    /// The chance of rolling `total` or less.
+    pub fn chance_at_most(&self, total: i64) -> f64 {
+        self.iter()
+            .filter(|(value, _)| *value <= total)
+            .map(|(_, chance)| chance)
+            .sum()
+    }
+
+    /// The average total.
+    pub fn mean(&self) -> f64 {
+        self.iter()
+            .map(|(value, chance)| value as f64 * chance)
+            .sum()
+    }
+
+    /// The standard deviation of the total: how far from the mean rolls
+    /// typically land.
+    pub fn std_dev(&self) -> f64 {
+        let mean = self.mean();
+        self.iter()
+            .map(|(value, chance)| (value as f64 - mean).powi(2) * chance)
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    /// Iterates over every total and its chance, from lowest to highest.
+    pub fn iter(&self) -> impl Iterator<Item = (i64, f64)> + '_ {
+        self.chances
+            .iter()
+            .enumerate()
+            .map(|(index, chance)| (self.min + index as i64, *chance))
+    }
+
+    /// The largest difference between the two distributions' chances of
+    /// rolling any one total. Use it to check a simulation against the
+    /// exact answer.
+    pub fn max_difference(&self, other: &DiceDistribution) -> f64 {
+        (self.min.min(other.min)..=self.max().max(other.max()))
+            .map(|total| (self.chance(total) - other.chance(total)).abs())
+            .fold(0.0, f64::max)
+    }
+
+    /// The distribution of the sum of a roll from each.
+    fn add(&self, other: &DiceDistribution, budget: &mut Budget) -> anyhow::Result<Self> {
+        let (a, b) = (self.chances.len() as u64, other.chances.len() as u64);
+        budget.spend(a.saturating_mul(b), a + b - 1)?;
+        let mut chances = vec![0.0; self.chances.len() + other.chances.len() - 1];
+        for (i, a) in self.chances.iter().enumerate() {
+            for (j, b) in other.chances.iter().enumerate() {
+                chances[i + j] += a * b;
+            }
+        }
+        Ok(Self {
+            min: self.min + other.min,
+            chances,
+        }
+        .trim())
+    }
+
+    /// The distribution of the sum of `count` rolls.
+    fn repeat(&self, count: u32, budget: &mut Budget) -> anyhow::Result<Self> {
+        let mut result = Self::constant(0);
+        let mut square = self.clone();
+        let mut count = count;
+        while count > 0 {
+            if count & 1 == 1 {
+                result = result.add(&square, budget)?;
+            }
+            count >>= 1;
+            if count > 0 {
+                square = square.add(&square, budget)?;
+            }
+        }
+        Ok(result)
+    }
+
+    fn negate(&self) -> Self {
+        Self {
+            min: -self.max(),
+            chances: self.chances.iter().rev().copied().collect(),
+        }
+    }
+
+    fn shift(mut self, by: i64) -> Self {
+        self.min += by;
+        self
+    }
+
+    /// Drops negligible chances from both ends.
+    fn trim(mut self) -> Self {
+        let end = self
+            .chances
+            .iter()
+            .rposition(|chance| *chance >= NEGLIGIBLE)
+            .map_or(1, |index| index + 1);
+        self.chances.truncate(end);
+        let start = self
+            .chances
+            .iter()
+            .position(|chance| *chance >= NEGLIGIBLE)
+            .unwrap_or(0);
+        self.chances.drain(..start);
+        self.min += start as i64;
+        self
+    }
+}
+
+impl DiceExpr {
+    /// Works out the exact chance of every total this expression can
+    /// roll. Recent results are cached, so asking again for the same
+    /// expression is cheap.
+    ///
+    /// Large keep/drop groups, such as `100d20kh50`, take a while to work
+    /// out the first time. Returns an error for expressions that would take
+    /// more than [`MAX_DISTRIBUTION_STEPS`] steps or build tables of more
+    /// than [`MAX_DISTRIBUTION_TOTALS`] totals; estimate those with
+    /// [`DiceExpr::simulate`] instead.
+    pub fn distribution(&self) -> anyhow::Result<Arc<DiceDistribution>> {
+        self.distribution_with(&CACHE)
+    }
+
+    fn distribution_with(
+        &self,
+        cache: &DistributionCache,
+    ) -> anyhow::Result<Arc<DiceDistribution>> {
+        if let Some(cached) = cache.get(self) {
+            return Ok(cached);
+        }
+        let budget = &mut Budget {
+            expr: self,
+            steps: 0,
+        };
+        let mut distribution = DiceDistribution::constant(0);
+        for term in self.terms() {
+            distribution = match term {
+                DiceTerm::Constant(n) => distribution.shift(*n),
+                DiceTerm::Dice {
+                    group,
+                    negative: false,
+                } => distribution.add(&group.distribution(budget)?, budget)?,
+                DiceTerm::Dice {
+                    group,
+                    negative: true,
+                } => distribution.add(&group.distribution(budget)?.negate(), budget)?,
+            };
+        }
+        let distribution = Arc::new(distribution);
+        cache.insert(self, distribution.clone());
+        Ok(distribution)
+    }
+
+    /// Rolls the dice `rolls` times, and counts how often each total came
+    /// up. Compare the result with the exact [`DiceExpr::distribution`] to
+    /// check it (or your dice rules) by simulation, or use it instead for
+    /// expressions too large to work out exactly.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::*;
+    /// let dice: DiceExpr = "4d6dl1".parse().unwrap();
+    /// let mut rng = RandomNumberGenerator::seeded(1);
+    /// let simulated = dice.simulate(&mut rng, 100_000);
+    /// assert!(simulated.max_difference(&dice.distribution().unwrap()) < 0.01);
+    /// ```
+    pub fn simulate<R: Rng>(&self, rng: &mut R, rolls: u32) -> DiceDistribution {
+        DiceDistribution::from_totals(
+            (0..rolls).map(|_| self.roll_with(|sides| rng.random_range(1..=sides)).total),
+        )
+    }
+}
+
+/// Which of a group's final dice count: the highest or lowest `count`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct Keep {
+    highest: bool,
+    count: u32,
+}
+
+impl DiceGroup {
+    fn distribution(&self, budget: &mut Budget) -> anyhow::Result<DiceDistribution> {
+        // Each exploding die is a run of maximum rolls followed by one
+        // that isn't, so the group is some number of extra maximum dice
+        // plus one "final" die per die rolled. Maximum dice always sort
+        // above final dice, which keeps keep/drop rules simple.
+        let (faces, extra) = if self.exploding {
+            (self.sides - 1, self.explosions(budget)?)
+        } else {
+            (self.sides, DiceDistribution::constant(0))
+        };
+        let mut finals = HashMap::new();
+        let mut chances = Vec::new();
+        for (extra, chance) in extra.iter() {
+            let (maximums, keep) = self.kept(extra as u32);
+            let finals = match finals.entry(keep) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(keep_dice(self.count, faces, keep, budget)?),
+            };
+            let offset = maximums as i64 * self.sides as i64;
+            budget.spend(
+                finals.chances.len() as u64,
+                (finals.max() + offset + 1) as u64,
+            )?;
+            for (total, final_chance) in finals.iter() {
+                let index = (total + offset) as usize;
+                if chances.len() <= index {
+                    chances.resize(index + 1, 0.0);
+                }
+                chances[index] += chance * final_chance;
+            }
+        }
+        Ok(DiceDistribution { min: 0, chances }.trim())
+    }
+
+    /// The distribution of the number of extra dice that explosions add.
+    fn explosions(&self, budget: &mut Budget) -> anyhow::Result<DiceDistribution> {
+        let explode = 1.0 / self.sides as f64;
+        let mut chances = Vec::new();
+        let mut chain = 1.0;
+        while chain >= NEGLIGIBLE && chances.len() <= crate::dice::MAX_EXPLOSIONS as usize {
+            chances.push(chain * (1.0 - explode));
+            chain *= explode;
+        }
+        DiceDistribution { min: 0, chances }.repeat(self.count, budget)
+    }
+
+    /// With `extra` maximum dice from explosions, how many of them count,
+    /// and which final dice count.
+    fn kept(&self, extra: u32) -> (u32, Keep) {
+        let count = self.count;
+        let (highest, lowest) = (true, false);
+        let keep = |highest, count| Keep { highest, count };
+        match self.selection {
+            None => (extra, keep(highest, count)),
+            Some(DiceSelection::KeepHighest(n)) => {
+                let maximums = extra.min(n);
+                (maximums, keep(highest, (n - maximums).min(count)))
+            }
+            Some(DiceSelection::KeepLowest(n)) => {
+                let finals = n.min(count);
+                (extra.min(n - finals), keep(lowest, finals))
+            }
+            Some(DiceSelection::DropHighest(n)) => {
+                let dropped = extra.min(n);
+                (
+                    extra - dropped,
+                    keep(lowest, count.saturating_sub(n - dropped)),
+                )
+            }
+            Some(DiceSelection::DropLowest(n)) => {
+                let dropped = n.min(count);
+                (
+                    extra - extra.min(n - dropped),
+                    keep(highest, count - dropped),
+                )
+            }
+        }
+    }
+}
+
+/// The distribution of the sum of the highest (or lowest) `keep.count` of
+/// `count` dice with faces `1..=faces`.
+fn keep_dice(
+    count: u32,
+    faces: u32,
+    keep: Keep,
+    budget: &mut Budget,
+) -> anyhow::Result<DiceDistribution> {
+    if keep.count == 0 || faces == 0 {
+        return Ok(DiceDistribution::constant(0));
+    }
+    if keep.count >= count {
+        return DiceDistribution::uniform(faces, budget)?.repeat(count, budget);
+    }
+    // Go through the faces from the best kept to the worst, deciding how
+    // many dice show each one. `placed[n][sum]` is the chance that `n`
+    // dice show the faces seen so far, adding `sum` to the kept total,
+    // and the rest show faces still to come.
+    let (n, kept) = (count as usize, keep.count as usize);
+    let ln_factorial: Vec<f64> = (0..=n)
+        .scan(0.0, |sum, i| {
+            *sum += (i.max(1) as f64).ln();
+            Some(*sum)
+        })
+        .collect();
+    let max_sum = kept * faces as usize;
+    let table = (n as u64 + 1) * (max_sum as u64 + 1);
+    budget.spend(
+        (faces as u64)
+            .saturating_mul(table)
+            .saturating_mul(n as u64 + 1),
+        table,
+    )?;
+    let mut placed = vec![vec![0.0; max_sum + 1]; n + 1];
+    placed[0][0] = 1.0;
+    for (index, face) in (1..=faces as usize).enumerate() {
+        let face = if keep.highest {
+            faces as usize + 1 - face
+        } else {
+            face
+        };
+        // Each remaining die shows this face with chance 1 / faces left.
+        let faces_left = (faces as usize - index) as f64;
+        let mut next = vec![vec![0.0; max_sum + 1]; n + 1];
+        for done in 0..=n {
+            let left = n - done;
+            for sum in 0..=max_sum {
+                let chance = placed[done][sum];
+                if chance == 0.0 {
+                    continue;
+                }
+                for here in 0..=left {
+                    let binomial = if faces_left == 1.0 {
+                        if here == left { 1.0 } else { 0.0 }
+                    } else {
+                        (ln_factorial[left]
+                            - ln_factorial[here]
+                            - ln_factorial[left - here]
+                            - here as f64 * faces_left.ln()
+                            + (left - here) as f64 * ((faces_left - 1.0) / faces_left).ln())
+                        .exp()
+                    };
+                    if binomial < NEGLIGIBLE {
+                        continue;
+                    }
+                    let counted = here.min(kept.saturating_sub(done));
+                    next[done + here][sum + counted * face] += chance * binomial;
+                }
+            }
+        }
+        placed = next;
+    }
+    Ok(DiceDistribution {
+        min: 0,
+        chances: placed.swap_remove(n),
+    }
+    .trim())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn odds(expr: &str) -> Arc<DiceDistribution> {
+        expr.parse::<DiceExpr>().unwrap().distribution().unwrap()
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{a} != {b}");
+    }
+
+    #[test]
+    fn test_sums() {
+        let two = odds("2d6");
+        assert_eq!((two.min(), two.max()), (2, 12));
+        assert_close(two.chance(7), 6.0 / 36.0);
+        assert_close(two.chance(13), 0.0);
+        assert_close(two.chance_at_least(11), 3.0 / 36.0);
+        assert_close(two.chance_at_most(12), 1.0);
+        assert_close(odds("3d6").mean(), 10.5);
+        assert_close(odds("1d6").std_dev(), (35.0f64 / 12.0).sqrt());
+        assert_close(odds("1000d6").mean(), 3500.0);
+
+        let difference = odds("1d4-1d4+3");
+        assert_eq!((difference.min(), difference.max()), (0, 6));
+        assert_close(difference.chance(3), 4.0 / 16.0);
+        assert_close(odds("-2").chance(-2), 1.0);
+    }
+
+    #[test]
+    fn test_selection() {
+        // 2d20 keeping the highest (advantage) rolls 20 in 39 of 400 cases.
+        assert_close(odds("2d20kh1").chance(20), 39.0 / 400.0);
+        assert_close(odds("2d20kl1").chance(20), 1.0 / 400.0);
+        assert_close(odds("2d20dh1").chance(1), 39.0 / 400.0);
+        // The classic ability score roll.
+        assert_close(odds("4d6dl1").mean(), 15869.0 / 1296.0);
+        assert_close(odds("4d6dl1").chance(18), 21.0 / 1296.0);
+        assert_close(odds("4d6kh3").chance(3), 1.0 / 1296.0);
+        let total: f64 = odds("10d8kh4").iter().map(|(_, chance)| chance).sum();
+        assert_close(total, 1.0);
+    }
+
+    #[test]
+    fn test_exploding() {
+        // Each d6! averages 3.5 * 6 / 5.
+        assert_close(odds("1d6!").mean(), 4.2);
+        assert_close(odds("3d6!").mean(), 12.6);
+        assert_close(odds("1d6!").chance(6), 0.0);
+        assert_close(odds("1d6!").chance(8), 1.0 / 36.0);
+        // 2d4!kh1 is 4 or more whenever either die explodes.
+        assert_close(odds("2d4!kh1").chance_at_least(4), 1.0 - 9.0 / 16.0);
+        // Every die ends on a roll that isn't 4, and the lowest of those counts.
+        assert_close(odds("2d4!kl1").chance(1), 5.0 / 9.0);
+    }
+
+    #[test]
+    fn test_cache() {
+        // A cache of its own, so other tests can't empty it mid-test.
+        let cache = DistributionCache::default();
+        let expr: DiceExpr = "5d10dl2".parse().unwrap();
+        let first = expr.distribution_with(&cache).unwrap();
+        assert!(Arc::ptr_eq(
+            &first,
+            &expr.distribution_with(&cache).unwrap()
+        ));
+        for sides in 1..=CACHE_SIZE as u32 * 2 {
+            let expr: DiceExpr = format!("1d{sides}").parse().unwrap();
+            expr.distribution_with(&cache).unwrap();
+        }
+        assert!(cache.lock().len() <= CACHE_SIZE);
+    }
+
+    #[test]
+    fn test_simulation() {
+        let mut rng = crate::RandomNumberGenerator::seeded(1);
+        for text in ["3d6", "4d6!kh3", "2d6!dl1-1d4"] {
+            let dice: DiceExpr = text.parse().unwrap();
+            let simulated = dice.simulate(&mut rng, 200_000);
+            let difference = simulated.max_difference(&dice.distribution().unwrap());
+            assert!(difference < 0.005, "{text}: {difference}");
+        }
+    }
+
+    #[test]
+    fn test_too_large() {
+        for expr in ["1d4000000000", "1000d1000kh999", "1000d100000"] {
+            let expr: DiceExpr = expr.parse().unwrap();
+            assert!(expr.distribution().is_err(), "{expr}");
+        }
+    }
+}
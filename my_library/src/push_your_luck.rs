@@ -0,0 +1,425 @@
+use crate::{DiceDistribution, DiceExpr};
+use bevy::log;
+use rand::Rng;
+use std::sync::{Arc, OnceLock};
+
+/// Solving stops once no chance of winning changes by more than this.
+const TOLERANCE: f64 = 1e-12;
+
+/// Solving gives up on a combined score after this many passes, even if
+/// the chances are still changing.
+const MAX_PASSES: usize = 10_000;
+
+/// How a computer player decides whether to roll again in a
+/// [`PushYourLuck`] game.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PushStrategy {
+    /// Hold once the turn total reaches this amount, like the classic
+    /// "hold at 20" Pig player.
+    HoldAt(u32),
+    /// Roll while that raises the expected turn total, holding at
+    /// [`PushYourLuck::hold_threshold`]. This scores the most points per
+    /// turn, but ignores the state of the game.
+    MostPoints,
+    /// Roll whenever that gives a better chance of winning the game,
+    /// assuming both players play this way. `risk` is added to the chance
+    /// of winning by rolling before the two are compared: 0 plays
+    /// perfectly, positive values push their luck more, and negative
+    /// values play it safe. Try values around ±0.05.
+    BestOdds { risk: f64 },
+}
+
+/// The chance of winning the game by rolling again or by holding, from
+/// [`PushYourLuck::odds`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RollOrHold {
+    pub roll: f64,
+    pub hold: f64,
+}
+
+/// `PushYourLuck` analyses two-player "push your luck" dice games such as
+/// Pig. On their turn, a player keeps rolling and adding the roll to their
+/// turn total, until they either hold (banking the turn total) or roll a
+/// "bust" face (losing it). The first to reach the target score wins.
+///
+/// It works out the expected value of rolling again, and (by solving the
+/// whole game) each player's chance of winning from any position. The
+/// solution is worked out the first time it is needed and kept, so
+/// create the game once, for example in a resource.
+///
+/// ## Example
+///
+/// ```
+/// use my_library::*;
+/// let pig = PushYourLuck::pig();
+/// assert_eq!(pig.hold_threshold(), 20);
+///
+/// // With 15 points banked in a tight game, and 12 in hand...
+/// let odds = pig.odds(15, 20, 12);
+/// println!("{:.1}% if you roll, {:.1}% if you hold", odds.roll * 100.0, odds.hold * 100.0);
+/// let roll = pig.should_roll(PushStrategy::BestOdds { risk: 0.0 }, 15, 20, 12);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PushYourLuck {
+    die: DiceExpr,
+    distribution: Arc<DiceDistribution>,
+    bust_faces: Vec<i64>,
+    /// The totals that add to the turn, and their chances.
+    faces: Vec<(u32, f64)>,
+    bust: f64,
+    target: u32,
+    /// The chance that the player to move wins, for every score, opponent
+    /// score and turn total.
+    win_chances: OnceLock<Vec<f64>>,
+}
+
+impl PushYourLuck {
+    /// Creates a game played with `die` (which may be any dice
+    /// expression), first to `target` points. Add bust faces with
+    /// [`PushYourLuck::with_bust`]. Totals of zero or less also count as
+    /// busts, as they can't add to the turn.
+    ///
+    /// Returns an error if the die is too large to work out its
+    /// [`distribution`](DiceExpr::distribution).
+    pub fn new(die: DiceExpr, target: u32) -> anyhow::Result<Self> {
+        let mut game = Self {
+            distribution: die.distribution()?,
+            die,
+            bust_faces: Vec::new(),
+            faces: Vec::new(),
+            bust: 0.0,
+            target: target.max(1),
+            win_chances: OnceLock::new(),
+        };
+        game.count_faces();
+        Ok(game)
+    }
+
+    /// The classic game of Pig: a single d6, busting on a 1, first to 100.
+    pub fn pig() -> Self {
+        Self::new("1d6".parse().unwrap(), 100).unwrap().with_bust(1)
+    }
+
+    /// Makes rolling `total` end the turn and lose the turn total.
+    pub fn with_bust(mut self, total: i64) -> Self {
+        self.bust_faces.push(total);
+        self.win_chances = OnceLock::new();
+        self.count_faces();
+        self
+    }
+
+    fn count_faces(&mut self) {
+        let (bust, faces): (Vec<_>, Vec<_>) = self
+            .distribution
+            .iter()
+            .filter(|(_, chance)| *chance > 0.0)
+            .partition(|(total, _)| self.is_bust(*total));
+        self.bust = bust.iter().map(|(_, chance)| chance).sum();
+        self.faces = faces
+            .into_iter()
+            .map(|(total, chance)| (total as u32, chance))
+            .collect();
+    }
+
+    fn is_bust(&self, total: i64) -> bool {
+        total <= 0 || self.bust_faces.contains(&total)
+    }
+
+    /// The score a player needs to win.
+    pub fn target(&self) -> u32 {
+        self.target
+    }
+
+    /// The chance that a roll busts.
+    pub fn bust_chance(&self) -> f64 {
+        self.bust
+    }
+
+    /// How much one more roll adds to the turn total on average, given the
+    /// turn total so far. It is negative when rolling is expected to lose
+    /// points.
+    pub fn expected_gain(&self, turn_total: u32) -> f64 {
+        let gain: f64 = self
+            .faces
+            .iter()
+            .map(|(total, chance)| *total as f64 * chance)
+            .sum();
+        gain - self.bust * turn_total as f64
+    }
+
+    /// The turn total at which rolling again stops being worth it on
+    /// average. For Pig, this is the famous 20.
+    pub fn hold_threshold(&self) -> u32 {
+        if self.bust == 0.0 {
+            return self.target;
+        }
+        // Rolling gains on average while the turn total is below this.
+        let even = self.expected_gain(0) / self.bust;
+        ((even - 1e-9).ceil().max(0.0) as u32).min(self.target)
+    }
+
+    /// The chance that a player wins with `score` banked and `turn_total`
+    /// in hand, against an opponent with `opponent_score`, if they roll
+    /// again or hold, and both players then play as well as possible.
+    ///
+    /// The first call solves the whole game, which can take a moment for
+    /// targets in the hundreds; call [`PushYourLuck::solve`] up front (for
+    /// example while loading) to avoid a pause mid-game.
+    pub fn odds(&self, score: u32, opponent_score: u32, turn_total: u32) -> RollOrHold {
+        if score >= self.target {
+            return RollOrHold {
+                roll: 1.0,
+                hold: 1.0,
+            };
+        }
+        if opponent_score >= self.target {
+            return RollOrHold {
+                roll: 0.0,
+                hold: 0.0,
+            };
+        }
+        let chances = self.win_chances();
+        let (score, opponent, turn) =
+            (score as usize, opponent_score as usize, turn_total as usize);
+        RollOrHold {
+            roll: self.roll_chance(chances, score, opponent, turn),
+            hold: self.hold_chance(chances, score, opponent, turn),
+        }
+    }
+
+    /// Decides whether a player using `strategy` rolls again. Every turn
+    /// starts with a roll, and nobody rolls once they have enough points
+    /// to win.
+    pub fn should_roll(
+        &self,
+        strategy: PushStrategy,
+        score: u32,
+        opponent_score: u32,
+        turn_total: u32,
+    ) -> bool {
+        if turn_total == 0 {
+            return true;
+        }
+        if score + turn_total >= self.target {
+            return false;
+        }
+        match strategy {
+            PushStrategy::HoldAt(total) => turn_total < total,
+            PushStrategy::MostPoints => turn_total < self.hold_threshold(),
+            PushStrategy::BestOdds { risk } => {
+                let odds = self.odds(score, opponent_score, turn_total);
+                odds.roll + risk > odds.hold
+            }
+        }
+    }
+
+    /// Solves the game now, instead of on the first call to
+    /// [`odds`](Self::odds). This takes a moment for long games, so call
+    /// it while the game is loading.
+    pub fn solve(&self) {
+        self.win_chances();
+    }
+
+    /// The chance that the player to move wins from every position.
+    fn win_chances(&self) -> &[f64] {
+        self.win_chances.get_or_init(|| self.find_win_chances())
+    }
+
+    fn index(&self, score: usize, opponent: usize, turn: usize) -> usize {
+        let target = self.target as usize;
+        (score * target + opponent) * target + turn
+    }
+
+    /// The chance of winning for the player to move, counting positions
+    /// where they already have enough points as won.
+    fn win_chance(&self, chances: &[f64], score: usize, opponent: usize, turn: usize) -> f64 {
+        if score + turn >= self.target as usize {
+            1.0
+        } else {
+            chances[self.index(score, opponent, turn)]
+        }
+    }
+
+    fn roll_chance(&self, chances: &[f64], score: usize, opponent: usize, turn: usize) -> f64 {
+        let bust = self.bust * (1.0 - self.win_chance(chances, opponent, score, 0));
+        let rolls: f64 = self
+            .faces
+            .iter()
+            .map(|(total, chance)| {
+                chance * self.win_chance(chances, score, opponent, turn + *total as usize)
+            })
+            .sum();
+        bust + rolls
+    }
+
+    fn hold_chance(&self, chances: &[f64], score: usize, opponent: usize, turn: usize) -> f64 {
+        match score + turn >= self.target as usize {
+            true => 1.0,
+            false => 1.0 - self.win_chance(chances, opponent, score + turn, 0),
+        }
+    }
+
+    /// Solves the game by value iteration. Positions only lead to ones
+    /// with the same or a higher combined score, so each combined score
+    /// is solved in turn, from the highest down. If a combined score hasn't
+    /// settled after [`MAX_PASSES`] passes, a warning is logged and the
+    /// chances so far are used.
+    fn find_win_chances(&self) -> Vec<f64> {
+        let target = self.target as usize;
+        let mut chances = vec![0.0; target * target * target];
+        for combined in (0..=2 * (target - 1)).rev() {
+            let scores = combined.saturating_sub(target - 1)..=combined.min(target - 1);
+            let mut change: f64 = 0.0;
+            for _ in 0..MAX_PASSES {
+                change = 0.0;
+                for score in scores.clone() {
+                    let opponent = combined - score;
+                    for turn in (0..target - score).rev() {
+                        let roll = self.roll_chance(&chances, score, opponent, turn);
+                        let chance = match turn {
+                            0 => roll,
+                            _ => roll.max(self.hold_chance(&chances, score, opponent, turn)),
+                        };
+                        let index = self.index(score, opponent, turn);
+                        change = change.max((chance - chances[index]).abs());
+                        chances[index] = chance;
+                    }
+                }
+                if change < TOLERANCE {
+                    break;
+                }
+            }
+            if change >= TOLERANCE {
+                log::warn!(
+                    "Chances of winning with a combined score of {combined} still changed by {change:e} after {MAX_PASSES} passes"
+                );
+            }
+        }
+        chances
+    }
+
+    /// Plays `games` games between two strategies, and returns the share
+    /// won by the first, which always starts. Use it to check
+    /// [`PushYourLuck::odds`] by simulation, or to compare strategies.
+    pub fn simulate<R: Rng>(&self, rng: &mut R, strategies: [PushStrategy; 2], games: u32) -> f64 {
+        let wins = (0..games)
+            .filter(|_| self.play(rng, strategies) == 0)
+            .count();
+        wins as f64 / games.max(1) as f64
+    }
+
+    /// Plays a whole game, returning the index of the winning strategy.
+    fn play<R: Rng>(&self, rng: &mut R, strategies: [PushStrategy; 2]) -> usize {
+        assert!(
+            !self.faces.is_empty(),
+            "Every roll busts, so nobody can win"
+        );
+        let mut scores = [0; 2];
+        let mut player = 0;
+        loop {
+            let mut turn = 0;
+            while self.should_roll(strategies[player], scores[player], scores[1 - player], turn) {
+                let roll = self
+                    .die
+                    .roll_with(|sides| rng.random_range(1..=sides))
+                    .total;
+                if self.is_bust(roll) {
+                    turn = 0;
+                    break;
+                }
+                turn += roll as u32;
+            }
+            scores[player] += turn;
+            if scores[player] >= self.target {
+                return player;
+            }
+            player = 1 - player;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_expected_gain() {
+        let pig = PushYourLuck::pig();
+        assert!((pig.bust_chance() - 1.0 / 6.0).abs() < 1e-12);
+        assert!(pig.expected_gain(19) > 0.0);
+        assert!(pig.expected_gain(20).abs() < 1e-9);
+        assert_eq!(pig.hold_threshold(), 20);
+
+        // Two dice, busting on a 7: hold around 35.
+        let two = PushYourLuck::new("2d6".parse().unwrap(), 100)
+            .unwrap()
+            .with_bust(7);
+        assert_eq!(two.hold_threshold(), 35);
+        let safe = PushYourLuck::new("1d6".parse().unwrap(), 50).unwrap();
+        assert_eq!(safe.hold_threshold(), 50);
+    }
+
+    #[test]
+    fn test_too_large() {
+        assert!(PushYourLuck::new("1d4000000000".parse().unwrap(), 100).is_err());
+    }
+
+    #[test]
+    fn test_strategies() {
+        let pig = PushYourLuck::new("1d6".parse().unwrap(), 30)
+            .unwrap()
+            .with_bust(1);
+        assert!(pig.should_roll(PushStrategy::HoldAt(10), 0, 0, 0));
+        assert!(pig.should_roll(PushStrategy::HoldAt(10), 0, 0, 9));
+        assert!(!pig.should_roll(PushStrategy::HoldAt(10), 0, 0, 10));
+        assert!(!pig.should_roll(PushStrategy::HoldAt(10), 25, 0, 5));
+        assert!(!pig.should_roll(PushStrategy::MostPoints, 0, 0, 20));
+
+        // In a short game, rolling is almost always worth it.
+        let best = PushStrategy::BestOdds { risk: 0.0 };
+        assert!(pig.should_roll(best, 0, 28, 15));
+        assert!(pig.should_roll(best, 20, 0, 8));
+        assert!(!pig.should_roll(PushStrategy::BestOdds { risk: -1.0 }, 20, 0, 8));
+
+        let odds = pig.odds(0, 0, 0);
+        assert!(odds.roll > 0.5 && odds.roll < 0.6, "{odds:?}");
+        assert_eq!(pig.odds(30, 0, 0).hold, 1.0);
+        assert_eq!(pig.odds(0, 30, 5).roll, 0.0);
+    }
+
+    #[test]
+    fn test_simulation() {
+        let mut rng = crate::RandomNumberGenerator::seeded(1);
+        let game = PushYourLuck::new("1d6".parse().unwrap(), 30)
+            .unwrap()
+            .with_bust(1);
+        let best = PushStrategy::BestOdds { risk: 0.0 };
+        let expected = game.odds(0, 0, 0).roll;
+        let simulated = game.simulate(&mut rng, [best, best], 40_000);
+        assert!(
+            (simulated - expected).abs() < 0.01,
+            "{simulated} vs {expected}"
+        );
+        // Playing for the win beats playing for points.
+        let points = PushStrategy::MostPoints;
+        assert!(game.simulate(&mut rng, [best, points], 20_000) > 0.5);
+        assert!(game.simulate(&mut rng, [points, best], 20_000) < 0.5);
+    }
+
+    #[test]
+    fn test_pig_solution() {
+        // Neller and Presser found the first player wins 53.06% of optimal
+        // games of Pig.
+        let pig = PushYourLuck::pig();
+        let first = pig.odds(0, 0, 0).roll;
+        assert!((first - 0.5306).abs() < 0.0001, "{first}");
+
+        // Optimal players hold sooner when ahead, and keep rolling when far
+        // behind.
+        let best = PushStrategy::BestOdds { risk: 0.0 };
+        assert!(pig.should_roll(best, 0, 0, 20));
+        assert!(!pig.should_roll(best, 0, 0, 21));
+        assert!(!pig.should_roll(best, 50, 0, 15));
+        assert!(pig.should_roll(best, 0, 90, 40));
+    }
+}
@@ -1,19 +1,48 @@
-use rand::{Rng, SeedableRng, distr::uniform::SampleRange};
+use crate::{
+    BackendKind, ChanceAccumulator, DefaultRng, DiceExpr, DiceRoll, DynamicRng, PoissonDisk,
+    Rect2D, RngBackend, RngTrace, ShuffleBag, WeightedTable,
+    random_trace::{
+        TraceScope, describe, describe_option, describe_pick, describe_picks, describe_state,
+    },
+};
+use bevy::prelude::{Color, Quat, Vec2, Vec3};
+use rand::{
+    Rng, RngCore,
+    distr::{
+        uniform::{SampleRange, SampleUniform},
+        weighted::Weight,
+    },
+    seq::{IndexedRandom, IteratorRandom, SliceRandom},
+};
+use rand_distr::{Beta, Binomial, Distribution, Exp, Normal, Poisson, Triangular};
+use std::{any::type_name, f32::consts::TAU, panic::Location};
 
-#[cfg(all(not(feature = "pcg"), not(feature = "xorshift")))]
-type RngCore = rand::prelude::StdRng;
-#[cfg(feature = "pcg")]
-type RngCore = rand_pcg::Pcg64Mcg;
-#[cfg(feature = "xorshift")]
-type RngCore = rand_xorshift::XorShiftRng;
+/// `RngSnapshot` captures the exact internal state of a
+/// [`RandomNumberGenerator`]. Restoring a snapshot continues the random
+/// sequence from the point at which it was taken, which is useful for
+/// save games, replays and rollback.
+///
+/// With the `serde` feature enabled, snapshots (and the generator itself)
+/// can be serialized. Snapshots of a [`DynamicRng`] generator record which
+/// backend they were taken from, and restoring one switches the generator
+/// to that backend.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RngSnapshot<R = DefaultRng> {
+    seed: u64,
+    rng: R,
+}
 
 /// `RandomNumberGenerator` holds random number generation state, and offers
 /// random number generation services to your program.
 ///
 /// `RandomNumberGenerator` defaults to using the
 /// [PCG](https://crates.io/crates/rand_pcg)
-/// algorithm. You can specify `xorshift` as a feature flag to use it
-/// instead.
+/// algorithm. Other algorithms ("backends") can be fixed at compile time
+/// by naming the backend type, e.g. `RandomNumberGenerator::<ChaCha8Rng>`,
+/// or chosen at runtime with [`RandomNumberGenerator::with_backend`],
+/// which creates a `RandomNumberGenerator<DynamicRng>`. See
+/// [`BackendKind`] for the available algorithms.
 ///
 /// By default, `RandomNumberGenerator` requires mutability---it
 /// is shared in Bevy with `ResMut<RandomNumberGenerator>`. If
@@ -21,6 +50,9 @@ type RngCore = rand_xorshift::XorShiftRng;
 /// `Res<RandomNumberGenerator>` instead), specify the `locking`
 /// feature flag.
 ///
+/// Calls can be recorded for debugging with
+/// [`RandomNumberGenerator::enable_tracing`]; see [`RngTrace`].
+///
 /// ## Example
 ///
 ///
@@ -31,17 +63,17 @@ type RngCore = rand_xorshift::XorShiftRng;
 /// println!("{random_number}");
 /// ```
 #[derive(bevy::prelude::Resource)]
-pub struct RandomNumberGenerator {
-    rng: RngCore,
+pub struct RandomNumberGenerator<R: RngBackend = DefaultRng> {
+    rng: R,
+    seed: u64,
+    trace: Option<RngTrace>,
 }
 
 impl RandomNumberGenerator {
     /// Creates a default `RandomNumberGenerator`, with a randomly
     /// selected starting seed.
     pub fn new() -> Self {
-        Self {
-            rng: RngCore::from_os_rng(),
-        }
+        Self::seeded(rand::random())
     }
 
     /// Creates a new `RandomNumberGenerator`, with a user-specified random seed.
@@ -61,11 +93,247 @@ impl RandomNumberGenerator {
     /// assert_eq!(results.0, results.1);
     /// ```
     pub fn seeded(seed: u64) -> Self {
+        Self::from_seed(seed)
+    }
+}
+
+impl RandomNumberGenerator<DynamicRng> {
+    /// Creates a new `RandomNumberGenerator` using the specified backend.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::{BackendKind, RandomNumberGenerator};
+    /// let mut rng = RandomNumberGenerator::with_backend(BackendKind::ChaCha, 1);
+    /// assert_eq!(rng.backend(), BackendKind::ChaCha);
+    /// ```
+    pub fn with_backend(backend: BackendKind, seed: u64) -> Self {
+        Self::from_state(seed, DynamicRng::new(backend, seed))
+    }
+
+    /// The backend this generator is using.
+    pub fn backend(&self) -> BackendKind {
+        self.rng.kind()
+    }
+}
+
+impl<R: RngBackend> RandomNumberGenerator<R> {
+    /// Creates a generator with a specific backend type, and a randomly
+    /// selected seed.
+    pub fn from_entropy() -> Self {
+        Self::from_seed(rand::random())
+    }
+
+    /// Creates a generator with a specific backend type and seed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::{RandomNumberGenerator, SplitMix64};
+    /// let mut sparks = RandomNumberGenerator::<SplitMix64>::from_seed(1);
+    /// let angle = sparks.range(0.0..360.0);
+    /// ```
+    pub fn from_seed(seed: u64) -> Self {
+        Self::from_state(seed, R::seeded(seed))
+    }
+
+    /// Creates a backend using the same algorithm as this generator.
+    pub(crate) fn reseeded_backend(&self, seed: u64) -> R {
+        self.rng.reseeded(seed)
+    }
+
+    /// Creates a generator with the same backend as this one.
+    fn reseeded(&self, seed: u64) -> Self {
+        Self::from_state(seed, self.rng.reseeded(seed))
+    }
+
+    pub(crate) fn from_state(seed: u64, rng: R) -> Self {
         Self {
-            rng: RngCore::seed_from_u64(seed),
+            rng,
+            seed,
+            trace: None,
+        }
+    }
+
+    /// The seed this generator was created with. Creating a generator with
+    /// [`RandomNumberGenerator::seeded`] and the same seed will reproduce
+    /// its results.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Derives an independent, named generator from this generator's seed.
+    ///
+    /// The stream depends only on the seed and the name---not on how many
+    /// numbers have been generated so far---so gameplay randomness kept in
+    /// its own stream is unaffected by adding or removing calls elsewhere
+    /// (for example, cosmetic effects).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::RandomNumberGenerator;
+    /// let mut rng = RandomNumberGenerator::seeded(1);
+    /// let mut walls = rng.stream("walls");
+    /// let _: u32 = rng.next();
+    /// let mut walls_again = rng.stream("walls");
+    /// assert_eq!(walls.next::<u32>(), walls_again.next::<u32>());
+    /// ```
+    pub fn stream(&self, name: &str) -> Self {
+        self.reseeded(crate::stream_seed(self.seed, name))
+    }
+
+    /// Creates a new generator, seeded from the next number in this
+    /// generator's sequence. Forks are reproducible, but---unlike
+    /// [`RandomNumberGenerator::stream`]---they depend on how many numbers
+    /// have already been drawn.
+    #[track_caller]
+    pub fn fork(&mut self) -> Self {
+        self.traced(
+            "fork",
+            (),
+            |_| String::new(),
+            |this, _| {
+                let seed = this.next();
+                this.reseeded(seed)
+            },
+            |fork| format!("seed {}", fork.seed),
+        )
+    }
+
+    /// Derives an independent generator for one of many tasks, such as
+    /// one entity in a `par_iter`. Like [`RandomNumberGenerator::stream`],
+    /// the result depends only on the seed and `key`, so it doesn't matter
+    /// which thread runs each task, or in what order.
+    ///
+    /// The same key always gives the same sequence, so fork a new
+    /// generator each frame and derive tasks from that.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bevy::prelude::*;
+    /// use my_library::RandomNumberGenerator;
+    ///
+    /// #[derive(Component)]
+    /// struct Jitter(f32);
+    ///
+    /// fn jitter(mut rng: ResMut<RandomNumberGenerator>, mut query: Query<(Entity, &mut Jitter)>) {
+    ///     let frame = rng.fork();
+    ///     query.par_iter_mut().for_each(|(entity, mut jitter)| {
+    ///         let mut rng = frame.task(entity.to_bits());
+    ///         jitter.0 = rng.range(-1.0..1.0);
+    ///     });
+    /// }
+    /// ```
+    pub fn task(&self, key: u64) -> Self {
+        self.reseeded(crate::task_seed(self.seed(), key))
+    }
+
+    /// Captures the current state of the generator.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::RandomNumberGenerator;
+    /// let mut rng = RandomNumberGenerator::new();
+    /// let snapshot = rng.snapshot();
+    /// let first: u32 = rng.next();
+    /// rng.restore(&snapshot);
+    /// assert_eq!(first, rng.next::<u32>());
+    /// ```
+    pub fn snapshot(&self) -> RngSnapshot<R> {
+        RngSnapshot {
+            seed: self.seed,
+            rng: self.rng.clone(),
         }
     }
 
+    /// Returns the generator to a previously captured state.
+    pub fn restore(&mut self, snapshot: &RngSnapshot<R>) {
+        self.rng = snapshot.rng.clone();
+        self.seed = snapshot.seed;
+    }
+
+    /// Creates a new `RandomNumberGenerator` from a captured state.
+    pub fn from_snapshot(snapshot: &RngSnapshot<R>) -> Self {
+        Self::from_state(snapshot.seed, snapshot.rng.clone())
+    }
+
+    /// Starts recording calls into an [`RngTrace`], keeping the most
+    /// recent `capacity` of them. Any previous trace is discarded.
+    ///
+    /// Derived generators ([`RandomNumberGenerator::stream`], `fork` and
+    /// `task`) aren't traced unless tracing is enabled on them too.
+    pub fn enable_tracing(&mut self, capacity: usize) {
+        self.trace = Some(RngTrace::new(capacity));
+    }
+
+    /// Stops recording calls, returning the trace recorded so far.
+    pub fn disable_tracing(&mut self) -> Option<RngTrace> {
+        self.trace.take()
+    }
+
+    /// Returns a copy of the trace recorded so far, or `None` if tracing
+    /// is disabled.
+    pub fn trace(&self) -> Option<RngTrace> {
+        self.trace.clone()
+    }
+
+    /// Runs `call`, recording it in the trace if tracing is enabled.
+    /// Calls made by other traced methods aren't recorded separately.
+    #[track_caller]
+    fn traced<A, T>(
+        &mut self,
+        method: &str,
+        args: A,
+        request: impl FnOnce(&A) -> String,
+        call: impl FnOnce(&mut Self, A) -> T,
+        result: impl FnOnce(&T) -> String,
+    ) -> T {
+        if self.trace.is_none() {
+            return call(self, args);
+        }
+        let location = Location::caller();
+        let scope = TraceScope::enter();
+        let request = scope.outermost().then(|| request(&args));
+        let value = call(self, args);
+        if let (Some(trace), Some(request)) = (self.trace.as_mut(), request) {
+            trace.record(method, request, result(&value), location);
+        }
+        value
+    }
+
+    /// Like `traced`, for draws whose results might not be printable.
+    /// Instead of the result, the trace records the state the generator
+    /// was left in, which differs from the first call that drew
+    /// differently.
+    #[track_caller]
+    fn traced_draw<T>(
+        &mut self,
+        method: &str,
+        request: impl FnOnce() -> &'static str,
+        draw: impl FnOnce(&mut R) -> T,
+    ) -> T {
+        if self.trace.is_none() {
+            return draw(&mut self.rng);
+        }
+        let location = Location::caller();
+        let scope = TraceScope::enter();
+        let value = draw(&mut self.rng);
+        if scope.outermost()
+            && let Some(trace) = self.trace.as_mut()
+        {
+            trace.record(
+                method,
+                request().to_string(),
+                describe_state(&self.rng),
+                location,
+            );
+        }
+        value
+    }
+
     /// Generates a random number within the specified range.
     ///
     /// # Arguments
@@ -81,45 +349,593 @@ impl RandomNumberGenerator {
     /// let one_to_nine = rng.range(1..10);
     /// let one_to_ten = rng.range(1..=10);
     /// ```
+    #[track_caller]
     pub fn range<T>(&mut self, range: impl SampleRange<T>) -> T
     where
-        T: rand::distr::uniform::SampleUniform + PartialOrd,
+        T: SampleUniform + PartialOrd,
     {
-        self.rng.random_range(range)
+        self.traced_draw("range", type_name::<T>, |rng| rng.random_range(range))
     }
 
     /// Generates a new random number of the requested type.
     #[allow(clippy::should_implement_trait)]
+    #[track_caller]
     pub fn next<T>(&mut self) -> T
     where
         rand::distr::StandardUniform: rand::prelude::Distribution<T>,
     {
-        self.rng.random()
+        self.traced_draw("next", type_name::<T>, |rng| rng.random())
+    }
+
+    /// Draws a value from any `rand` distribution, such as those in the
+    /// re-exported [`rand_distr`](crate::rand_distr) crate. Building a
+    /// distribution once and sampling it repeatedly avoids re-validating
+    /// its parameters on every call.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::{RandomNumberGenerator, rand_distr::Normal};
+    /// let mut rng = RandomNumberGenerator::new();
+    /// let damage_spread = Normal::new(10.0, 2.0).unwrap();
+    /// let damage: f32 = rng.sample(&damage_spread);
+    /// ```
+    #[track_caller]
+    pub fn sample<T, D>(&mut self, distribution: &D) -> T
+    where
+        D: Distribution<T>,
+    {
+        self.traced_draw("sample", type_name::<D>, |rng| rng.sample(distribution))
+    }
+
+    /// Generates a normally distributed (Gaussian) number: values cluster
+    /// around `mean`, with about 68% falling within `std_dev` of it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `std_dev` is negative or not finite.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::RandomNumberGenerator;
+    /// let mut rng = RandomNumberGenerator::new();
+    /// let damage = rng.normal(10.0, 2.0).max(0.0);
+    /// ```
+    #[track_caller]
+    pub fn normal(&mut self, mean: f32, std_dev: f32) -> f32 {
+        let distribution = Normal::new(mean, std_dev)
+            .unwrap_or_else(|e| panic!("Invalid normal distribution ({mean}, {std_dev}): {e}"));
+        self.traced(
+            "normal",
+            &distribution,
+            describe,
+            |this, distribution| this.sample(distribution),
+            describe,
+        )
+    }
+
+    /// Generates an exponentially distributed number, such as the time
+    /// until the next event when events happen `lambda` times per unit of
+    /// time on average. The mean is `1 / lambda`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lambda` is negative or NaN.
+    #[track_caller]
+    pub fn exponential(&mut self, lambda: f32) -> f32 {
+        let distribution = Exp::new(lambda)
+            .unwrap_or_else(|e| panic!("Invalid exponential distribution ({lambda}): {e}"));
+        self.traced(
+            "exponential",
+            &distribution,
+            describe,
+            |this, distribution| this.sample(distribution),
+            describe,
+        )
+    }
+
+    /// Generates a Poisson distributed count: the number of events in a
+    /// unit of time, when events happen `lambda` times on average.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lambda` is not positive, or is too large.
+    #[track_caller]
+    pub fn poisson(&mut self, lambda: f32) -> u64 {
+        let distribution = Poisson::new(lambda)
+            .unwrap_or_else(|e| panic!("Invalid Poisson distribution ({lambda}): {e}"));
+        self.traced(
+            "poisson",
+            &distribution,
+            describe,
+            |this, distribution| this.sample::<f32, _>(distribution) as u64,
+            describe,
+        )
+    }
+
+    /// Generates a number between `min` and `max`, most likely to be near
+    /// `mode`.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `min <= mode <= max`.
+    #[track_caller]
+    pub fn triangular(&mut self, min: f32, max: f32, mode: f32) -> f32 {
+        let distribution = Triangular::new(min, max, mode).unwrap_or_else(|e| {
+            panic!("Invalid triangular distribution ({min}, {max}, {mode}): {e}")
+        });
+        self.traced(
+            "triangular",
+            &distribution,
+            describe,
+            |this, distribution| this.sample(distribution),
+            describe,
+        )
+    }
+
+    /// Generates a number between 0 and 1 from a beta distribution. Useful
+    /// for random probabilities and percentages: the mean is
+    /// `alpha / (alpha + beta)`, and larger parameters give tighter results.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `alpha` or `beta` is not positive.
+    #[track_caller]
+    pub fn beta(&mut self, alpha: f32, beta: f32) -> f32 {
+        let distribution = Beta::new(alpha, beta)
+            .unwrap_or_else(|e| panic!("Invalid beta distribution ({alpha}, {beta}): {e}"));
+        self.traced(
+            "beta",
+            &distribution,
+            describe,
+            |this, distribution| this.sample(distribution),
+            describe,
+        )
+    }
+
+    /// Counts how many of `trials` attempts succeed, when each succeeds
+    /// with probability `p` (e.g. hits from a volley of arrows).
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `p` is between 0 and 1.
+    #[track_caller]
+    pub fn binomial(&mut self, trials: u64, p: f64) -> u64 {
+        let distribution = Binomial::new(trials, p)
+            .unwrap_or_else(|e| panic!("Invalid binomial distribution ({trials}, {p}): {e}"));
+        self.traced(
+            "binomial",
+            &distribution,
+            describe,
+            |this, distribution| this.sample(distribution),
+            describe,
+        )
+    }
+
+    /// Parses and rolls a dice expression, such as `3d6+2`, `2d20kh1`,
+    /// `4d6dl1`, `d%` or `3d6!`. See [`DiceExpr`] for the full syntax.
+    ///
+    /// Returns an error if the expression could not be parsed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::RandomNumberGenerator;
+    /// let mut rng = RandomNumberGenerator::new();
+    /// let roll = rng.roll_dice("3d6+2").unwrap();
+    /// assert!(roll.total >= 5 && roll.total <= 20);
+    /// assert_eq!(roll.dice.len(), 3);
+    /// assert!(rng.roll_dice("3d").is_err());
+    /// ```
+    #[track_caller]
+    pub fn roll_dice(&mut self, dice: &str) -> anyhow::Result<DiceRoll> {
+        let dice: DiceExpr = dice.parse()?;
+        Ok(self.roll(&dice))
+    }
+
+    /// Rolls a pre-parsed dice expression, returning the total and the
+    /// individual die results.
+    #[track_caller]
+    pub fn roll(&mut self, dice: &DiceExpr) -> DiceRoll {
+        self.traced(
+            "roll",
+            dice,
+            |dice| dice.to_string(),
+            |this, dice| dice.roll_with(|sides| this.rng.random_range(1..=sides)),
+            |roll| roll.total.to_string(),
+        )
+    }
+
+    /// Picks an entry from a [`WeightedTable`], rolling any nested tables
+    /// it lands on. Returns `None` if the table is empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::*;
+    /// let table = WeightedTable::new().with("common", 9.0).with("rare", 1.0);
+    /// let mut rng = RandomNumberGenerator::new();
+    /// let item = rng.roll_table(&table);
+    /// assert!(item.is_some());
+    /// ```
+    #[track_caller]
+    pub fn roll_table<'a, T>(&mut self, table: &'a WeightedTable<T>) -> Option<&'a T> {
+        self.traced(
+            "roll_table",
+            table,
+            |table| format!("{} entries", table.len()),
+            |this, table| table.sample_with(&mut this.rng),
+            describe_option,
+        )
+    }
+
+    /// Draws the next item from a [`ShuffleBag`], refilling it if it is
+    /// empty. Returns `None` if the bag has no items.
+    #[track_caller]
+    pub fn draw<'a, T>(&mut self, bag: &'a mut ShuffleBag<T>) -> Option<&'a T> {
+        self.traced(
+            "draw",
+            bag,
+            |bag| format!("{} remaining", bag.remaining()),
+            |this, bag| bag.draw_with(&mut this.rng),
+            describe_option,
+        )
+    }
+
+    /// Makes an attempt with a [`ChanceAccumulator`], returning `true` on
+    /// success. Each miss makes the next attempt more likely to succeed.
+    #[track_caller]
+    pub fn chance(&mut self, accumulator: &mut ChanceAccumulator) -> bool {
+        self.traced(
+            "chance",
+            accumulator,
+            |accumulator| format!("{}", accumulator.current_chance()),
+            |this, accumulator| accumulator.attempt_with(&mut this.rng),
+            describe,
+        )
+    }
+
+    /// Shuffles a slice into a random order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::RandomNumberGenerator;
+    /// let mut rng = RandomNumberGenerator::new();
+    /// let mut deck: Vec<u32> = (1..=52).collect();
+    /// rng.shuffle(&mut deck);
+    /// assert_eq!(deck.len(), 52);
+    /// ```
+    #[track_caller]
+    pub fn shuffle<T>(&mut self, slice: &mut [T]) {
+        self.traced(
+            "shuffle",
+            slice,
+            |slice| format!("{} items", slice.len()),
+            |this, slice| slice.shuffle(&mut this.rng),
+            |_| String::new(),
+        )
+    }
+
+    /// Picks a random element from a slice, or `None` if it is empty.
+    #[track_caller]
+    pub fn choose<'a, T>(&mut self, slice: &'a [T]) -> Option<&'a T> {
+        self.traced(
+            "choose",
+            slice,
+            |slice| format!("{} items", slice.len()),
+            |this, slice| slice.choose(&mut this.rng),
+            |pick| describe_pick(slice, *pick),
+        )
+    }
+
+    /// Picks `amount` distinct elements from a slice (without
+    /// replacement), in random order. If the slice has fewer than `amount`
+    /// elements, all of them are returned.
+    #[track_caller]
+    pub fn choose_multiple<'a, T>(&mut self, slice: &'a [T], amount: usize) -> Vec<&'a T> {
+        self.traced(
+            "choose_multiple",
+            amount,
+            |amount| format!("{amount} of {} items", slice.len()),
+            |this, amount| {
+                slice
+                    .choose_multiple(&mut this.rng, amount)
+                    .collect::<Vec<_>>()
+            },
+            |picks| describe_picks(slice, picks),
+        )
+    }
+
+    /// Picks a random element from a slice, with the chance of each element
+    /// being picked proportional to the weight returned by `weight`.
+    ///
+    /// Returns `None` if the slice is empty, or the weights are invalid
+    /// (negative, or all zero). For repeated picks from the same set of
+    /// options, a [`WeightedTable`] is much faster.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::RandomNumberGenerator;
+    /// let mut rng = RandomNumberGenerator::new();
+    /// let moves = [("attack", 5), ("defend", 3), ("flee", 0)];
+    /// let (name, _) = rng.choose_weighted(&moves, |(_, weight)| *weight).unwrap();
+    /// assert_ne!(*name, "flee");
+    /// ```
+    #[track_caller]
+    pub fn choose_weighted<'a, T, W>(
+        &mut self,
+        slice: &'a [T],
+        weight: impl Fn(&T) -> W,
+    ) -> Option<&'a T>
+    where
+        W: SampleUniform + Weight + PartialOrd,
+    {
+        self.traced(
+            "choose_weighted",
+            weight,
+            |_| format!("{} items", slice.len()),
+            |this, weight| slice.choose_weighted(&mut this.rng, weight).ok(),
+            |pick| describe_pick(slice, *pick),
+        )
+    }
+
+    /// Picks a random item from an iterator, consuming it. This works with
+    /// iterators of unknown length (such as query results).
+    #[track_caller]
+    pub fn choose_iter<I: IntoIterator>(&mut self, iter: I) -> Option<I::Item> {
+        self.traced(
+            "choose_iter",
+            iter,
+            |_| String::new(),
+            |this, iter| iter.into_iter().choose(&mut this.rng),
+            describe_option,
+        )
+    }
+
+    /// Picks `amount` distinct items from an iterator of unknown length,
+    /// using reservoir sampling. The iterator is consumed exactly once.
+    ///
+    /// The order of the returned items is not random; shuffle them with
+    /// [`RandomNumberGenerator::shuffle`] if it matters.
+    #[track_caller]
+    pub fn reservoir_sample<I: IntoIterator>(&mut self, iter: I, amount: usize) -> Vec<I::Item> {
+        self.traced(
+            "reservoir_sample",
+            (iter, amount),
+            |(_, amount)| amount.to_string(),
+            |this, (iter, amount)| iter.into_iter().choose_multiple(&mut this.rng, amount),
+            |sample| format!("{} items", sample.len()),
+        )
+    }
+
+    /// Picks a point inside a rectangle, with every point equally likely.
+    #[track_caller]
+    pub fn point_in_rect(&mut self, rect: &Rect2D) -> Vec2 {
+        let (min, max) = (rect.min(), rect.max());
+        self.traced(
+            "point_in_rect",
+            (min, max),
+            describe,
+            |this, (min, max)| Vec2::new(this.range(min.x..=max.x), this.range(min.y..=max.y)),
+            describe,
+        )
+    }
+
+    /// Picks a point inside a circle, with every point equally likely.
+    ///
+    /// Picking an angle and a distance directly would bunch points up
+    /// near the center; this corrects for the larger area further out.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bevy::prelude::*;
+    /// use my_library::RandomNumberGenerator;
+    /// let mut rng = RandomNumberGenerator::new();
+    /// // A velocity in any direction, with no bias toward the diagonals
+    /// let velocity = rng.point_in_circle(Vec2::ZERO, 1.0);
+    /// assert!(velocity.length() <= 1.0);
+    /// ```
+    #[track_caller]
+    pub fn point_in_circle(&mut self, center: Vec2, radius: f32) -> Vec2 {
+        self.traced(
+            "point_in_circle",
+            (center, radius),
+            describe,
+            |this, (center, radius)| this.point_in_annulus(center, 0.0, radius),
+            describe,
+        )
+    }
+
+    /// Picks a point on the edge of a circle.
+    #[track_caller]
+    pub fn point_on_circle(&mut self, center: Vec2, radius: f32) -> Vec2 {
+        self.traced(
+            "point_on_circle",
+            (center, radius),
+            describe,
+            |this, (center, radius)| center + this.unit_vec2() * radius,
+            describe,
+        )
+    }
+
+    /// Picks a point in the ring between `inner_radius` and `outer_radius`,
+    /// with every point equally likely.
+    #[track_caller]
+    pub fn point_in_annulus(&mut self, center: Vec2, inner_radius: f32, outer_radius: f32) -> Vec2 {
+        self.traced(
+            "point_in_annulus",
+            (center, inner_radius, outer_radius),
+            describe,
+            |this, (center, inner_radius, outer_radius)| {
+                let (inner, outer) = (inner_radius * inner_radius, outer_radius * outer_radius);
+                let distance = (inner + this.next::<f32>() * (outer - inner)).sqrt();
+                center + this.unit_vec2() * distance
+            },
+            describe,
+        )
+    }
+
+    /// Picks a point inside a sphere, with every point equally likely.
+    #[track_caller]
+    pub fn point_in_sphere(&mut self, center: Vec3, radius: f32) -> Vec3 {
+        self.traced(
+            "point_in_sphere",
+            (center, radius),
+            describe,
+            |this, (center, radius)| {
+                let distance = this.next::<f32>().cbrt() * radius;
+                center + this.unit_vec3() * distance
+            },
+            describe,
+        )
+    }
+
+    /// Generates a 2D vector of length 1, pointing in a random direction.
+    #[track_caller]
+    pub fn unit_vec2(&mut self) -> Vec2 {
+        self.traced(
+            "unit_vec2",
+            (),
+            |_| String::new(),
+            |this, _| Vec2::from_angle(this.range(0.0..TAU)),
+            describe,
+        )
+    }
+
+    /// Generates a 3D vector of length 1, pointing in a random direction.
+    /// Every direction is equally likely.
+    #[track_caller]
+    pub fn unit_vec3(&mut self) -> Vec3 {
+        self.traced(
+            "unit_vec3",
+            (),
+            |_| String::new(),
+            |this, _| {
+                // Archimedes: height on a sphere is uniform, so pick a height
+                // and an angle around the vertical axis.
+                let z: f32 = this.range(-1.0..=1.0);
+                let (sin, cos) = this.range(0.0..TAU).sin_cos();
+                let radius = (1.0 - z * z).sqrt();
+                Vec3::new(radius * cos, radius * sin, z)
+            },
+            describe,
+        )
+    }
+
+    /// Generates a random rotation, with every orientation equally likely.
+    #[track_caller]
+    pub fn rotation(&mut self) -> Quat {
+        self.traced(
+            "rotation",
+            (),
+            |_| String::new(),
+            |this, _| {
+                // Shoemake's method for uniform random quaternions.
+                let (u1, u2, u3): (f32, f32, f32) = (this.next(), this.next(), this.next());
+                let (a, b) = ((1.0 - u1).sqrt(), u1.sqrt());
+                let (sin2, cos2) = (TAU * u2).sin_cos();
+                let (sin3, cos3) = (TAU * u3).sin_cos();
+                Quat::from_xyzw(a * sin2, a * cos2, b * sin3, b * cos3).normalize()
+            },
+            describe,
+        )
+    }
+
+    /// Generates a color with a hue (in degrees) picked from `hues`, and the
+    /// given saturation and lightness (from 0 to 1).
+    ///
+    /// Hues wrap around at 360, so `330.0..390.0` picks reds on either side
+    /// of 0.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use my_library::RandomNumberGenerator;
+    /// let mut rng = RandomNumberGenerator::new();
+    /// let greenish = rng.color_in_hue_range(90.0..150.0, 0.8, 0.5);
+    /// ```
+    #[track_caller]
+    pub fn color_in_hue_range<H>(&mut self, hues: H, saturation: f32, lightness: f32) -> Color
+    where
+        H: SampleRange<f32>,
+    {
+        self.traced(
+            "color_in_hue_range",
+            hues,
+            |_| format!("saturation {saturation}, lightness {lightness}"),
+            |this, hues| Color::hsl(this.range(hues).rem_euclid(360.0), saturation, lightness),
+            describe,
+        )
+    }
+
+    /// Scatters points over a region, with no two points closer than the
+    /// sampler's spacing. See [`PoissonDisk`].
+    #[track_caller]
+    pub fn poisson_disk(&mut self, sampler: &PoissonDisk) -> Vec<Vec2> {
+        self.traced(
+            "poisson_disk",
+            sampler,
+            |_| String::new(),
+            |this, sampler| sampler.sample_with(&mut this.rng),
+            |points| format!("{} points", points.len()),
+        )
     }
 }
 
-impl Default for RandomNumberGenerator {
+impl<R: RngBackend> Default for RandomNumberGenerator<R> {
     fn default() -> Self {
-        Self::new()
+        Self::from_entropy()
     }
 }
 
-/// `RandomPlugin` is a Bevy plugin that inserts a `RandomNumberGenerator`
-/// resource into your application.
-///
-/// Once you add the plugin (with `App::new.add_plugin(RandomPlugin)`),
-/// you can access a random number generator in systems with
-/// `rng: Res<RandomNumberGenerator>`.
-pub struct RandomPlugin;
-impl bevy::prelude::Plugin for RandomPlugin {
-    fn build(&self, app: &mut bevy::prelude::App) {
-        app.insert_resource(RandomNumberGenerator::new());
+/// `RandomNumberGenerator` can be passed to anything that takes a
+/// [`rand::Rng`], such as [`NameGenerator::generate`](crate::NameGenerator::generate).
+/// Draws made this way continue the generator's sequence, and are traced
+/// like any other.
+impl<R: RngBackend> RngCore for RandomNumberGenerator<R> {
+    #[track_caller]
+    fn next_u32(&mut self) -> u32 {
+        self.traced_draw("next_u32", || "", |rng| rng.next_u32())
+    }
+
+    #[track_caller]
+    fn next_u64(&mut self) -> u64 {
+        self.traced_draw("next_u64", || "", |rng| rng.next_u64())
+    }
+
+    #[track_caller]
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        self.traced_draw("fill_bytes", || "", |rng| rng.fill_bytes(dst))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<R: RngBackend + serde::Serialize> serde::Serialize for RandomNumberGenerator<R> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.snapshot().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, R> serde::Deserialize<'de> for RandomNumberGenerator<R>
+where
+    R: RngBackend + serde::de::DeserializeOwned,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self::from_snapshot(&RngSnapshot::deserialize(
+            deserializer,
+        )?))
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use rand::RngCore;
 
     #[test]
     fn test_range_bounds() {
@@ -162,4 +978,352 @@ mod test {
         let _: i32 = rng.next();
         let _ = rng.next::<f32>();
     }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let mut rng = RandomNumberGenerator::seeded(1);
+        let _: u64 = rng.next();
+        let snapshot = rng.snapshot();
+        let first: Vec<u64> = (0..100).map(|_| rng.range(0..u64::MAX)).collect();
+        rng.restore(&snapshot);
+        let second: Vec<u64> = (0..100).map(|_| rng.range(0..u64::MAX)).collect();
+        assert_eq!(first, second);
+        let mut copy = RandomNumberGenerator::from_snapshot(&snapshot);
+        assert_eq!(copy.range(0..u64::MAX), first[0]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mut rng = RandomNumberGenerator::seeded(1);
+        let _: u64 = rng.next();
+        let saved = ron::to_string(&rng).unwrap();
+        let mut loaded: RandomNumberGenerator = ron::from_str(&saved).unwrap();
+        (0..100).for_each(|_| assert_eq!(rng.next::<u64>(), loaded.next::<u64>()));
+    }
+
+    #[test]
+    fn test_streams() {
+        let mut rng = RandomNumberGenerator::seeded(1);
+        let mut walls = rng.stream("walls");
+        let mut cosmetic = rng.stream("cosmetic");
+        let _: u64 = rng.next();
+        let mut walls_again = rng.stream("walls");
+        (0..100).for_each(|_| {
+            let n = walls.next::<u64>();
+            assert_eq!(n, walls_again.next::<u64>());
+            assert_ne!(n, cosmetic.next::<u64>());
+        });
+        assert_eq!(
+            RandomNumberGenerator::seeded(1).stream("walls").seed(),
+            walls.seed()
+        );
+    }
+
+    #[test]
+    fn test_fork() {
+        let mut rng = (
+            RandomNumberGenerator::seeded(1),
+            RandomNumberGenerator::seeded(1),
+        );
+        let mut forks = (rng.0.fork(), rng.1.fork());
+        (0..100).for_each(|_| assert_eq!(forks.0.next::<u64>(), forks.1.next::<u64>()));
+        assert_ne!(forks.0.seed(), rng.0.fork().seed());
+    }
+
+    #[test]
+    fn test_collections() {
+        let mut rng = RandomNumberGenerator::seeded(1);
+        let mut deck: Vec<u32> = (0..52).collect();
+        rng.shuffle(&mut deck);
+        let mut sorted = deck.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..52).collect::<Vec<u32>>());
+
+        assert!(rng.choose::<u32>(&[]).is_none());
+        assert!(deck.contains(rng.choose(&deck).unwrap()));
+
+        let mut hand = rng.choose_multiple(&deck, 5);
+        hand.sort();
+        hand.dedup();
+        assert_eq!(hand.len(), 5);
+        assert_eq!(rng.choose_multiple(&deck[..3], 5).len(), 3);
+
+        let weighted = [(1, 0.0), (2, 1.0)];
+        (0..100).for_each(|_| assert_eq!(rng.choose_weighted(&weighted, |w| w.1).unwrap().0, 2));
+        assert!(rng.choose_weighted(&[(1, 0.0)], |w| w.1).is_none());
+
+        assert!(rng.choose_iter(0..0).is_none());
+        assert!((0..10).contains(&rng.choose_iter(0..10).unwrap()));
+        let mut sample = rng.reservoir_sample((0..1000).filter(|n| n % 2 == 0), 10);
+        sample.sort();
+        sample.dedup();
+        assert_eq!(sample.len(), 10);
+        assert!(sample.iter().all(|n| n % 2 == 0));
+    }
+
+    #[test]
+    fn test_collections_reproducibility() {
+        let mut rng = (
+            RandomNumberGenerator::seeded(1),
+            RandomNumberGenerator::seeded(1),
+        );
+        let mut decks: (Vec<u32>, Vec<u32>) = ((0..52).collect(), (0..52).collect());
+        rng.0.shuffle(&mut decks.0);
+        rng.1.shuffle(&mut decks.1);
+        assert_eq!(decks.0, decks.1);
+        assert_eq!(
+            rng.0.choose_multiple(&decks.0, 5),
+            rng.1.choose_multiple(&decks.1, 5)
+        );
+        assert_eq!(
+            rng.0.reservoir_sample(0..1000, 5),
+            rng.1.reservoir_sample(0..1000, 5)
+        );
+    }
+
+    #[test]
+    fn test_distributions_reproducibility() {
+        let mut rng = (
+            RandomNumberGenerator::seeded(1),
+            RandomNumberGenerator::seeded(1),
+        );
+        (0..1000).for_each(|_| {
+            assert_eq!(rng.0.normal(10.0, 2.0), rng.1.normal(10.0, 2.0));
+            assert_eq!(rng.0.exponential(0.5), rng.1.exponential(0.5));
+            assert_eq!(rng.0.poisson(3.0), rng.1.poisson(3.0));
+            assert_eq!(
+                rng.0.triangular(0.0, 10.0, 2.0),
+                rng.1.triangular(0.0, 10.0, 2.0)
+            );
+            assert_eq!(rng.0.beta(2.0, 5.0), rng.1.beta(2.0, 5.0));
+            assert_eq!(rng.0.binomial(20, 0.3), rng.1.binomial(20, 0.3));
+        });
+    }
+
+    #[test]
+    fn test_distributions() {
+        let mut rng = RandomNumberGenerator::seeded(1);
+        let n = 10_000;
+        let mean = (0..n).map(|_| rng.normal(10.0, 2.0)).sum::<f32>() / n as f32;
+        assert!((mean - 10.0).abs() < 0.1);
+        let mean = (0..n).map(|_| rng.exponential(0.5)).sum::<f32>() / n as f32;
+        assert!((mean - 2.0).abs() < 0.1);
+        let mean = (0..n).map(|_| rng.poisson(3.0)).sum::<u64>() as f32 / n as f32;
+        assert!((mean - 3.0).abs() < 0.1);
+        for _ in 0..n {
+            assert!((0.0..=10.0).contains(&rng.triangular(0.0, 10.0, 2.0)));
+            assert!((0.0..=1.0).contains(&rng.beta(2.0, 5.0)));
+            assert!(rng.binomial(20, 0.3) <= 20);
+        }
+    }
+
+    #[test]
+    fn test_geometry() {
+        let mut rng = RandomNumberGenerator::seeded(1);
+        let rect = Rect2D::new(Vec2::new(-10.0, -5.0), Vec2::new(10.0, 5.0));
+        let center = Vec2::new(3.0, 4.0);
+        for _ in 0..1000 {
+            assert!(rect.contains(rng.point_in_rect(&rect)));
+            assert!(rng.point_in_circle(center, 2.0).distance(center) <= 2.0 + 1e-4);
+            assert!((rng.point_on_circle(center, 2.0).distance(center) - 2.0).abs() < 1e-4);
+            let distance = rng.point_in_annulus(center, 1.0, 2.0).distance(center);
+            assert!((1.0 - 1e-4..=2.0 + 1e-4).contains(&distance));
+            assert!(rng.point_in_sphere(Vec3::ONE, 2.0).distance(Vec3::ONE) <= 2.0 + 1e-4);
+            assert!(rng.unit_vec2().is_normalized());
+            assert!(rng.unit_vec3().is_normalized());
+            assert!(rng.rotation().is_normalized());
+            let color = rng.color_in_hue_range(330.0..390.0, 1.0, 0.5);
+            let hue = bevy::prelude::Hsla::from(color).hue;
+            assert!(!(30.0 + 1e-3..330.0 - 1e-3).contains(&hue), "{hue}");
+        }
+    }
+
+    #[test]
+    fn test_unit_vec2_is_unbiased() {
+        // Directions near the diagonals should be no more likely than any
+        // others: count how many fall within 22.5 degrees of an axis.
+        let mut rng = RandomNumberGenerator::seeded(1);
+        let near_axis = (0..10_000)
+            .map(|_| rng.unit_vec2())
+            .filter(|v| v.x.abs().max(v.y.abs()) > (TAU / 16.0).cos())
+            .count();
+        assert!((4_800..5_200).contains(&near_axis), "{near_axis}");
+    }
+
+    #[test]
+    fn test_geometry_reproducibility() {
+        let mut rng = (
+            RandomNumberGenerator::seeded(1),
+            RandomNumberGenerator::seeded(1),
+        );
+        (0..1000).for_each(|_| {
+            assert_eq!(
+                rng.0.point_in_circle(Vec2::ZERO, 1.0),
+                rng.1.point_in_circle(Vec2::ZERO, 1.0)
+            );
+            assert_eq!(rng.0.unit_vec3(), rng.1.unit_vec3());
+            assert_eq!(rng.0.rotation(), rng.1.rotation());
+        });
+    }
+
+    #[test]
+    fn test_tasks() {
+        let mut rng = RandomNumberGenerator::seeded(1);
+        let first: Vec<u32> = (0..10).map(|key| rng.task(key).next()).collect();
+        let _: u32 = rng.next();
+        let again: Vec<u32> = (0..10).rev().map(|key| rng.task(key).next()).collect();
+        assert_eq!(first, again.into_iter().rev().collect::<Vec<_>>());
+        assert_ne!(first[0], first[1]);
+        assert_ne!(
+            RandomNumberGenerator::seeded(2).task(0).next::<u32>(),
+            first[0]
+        );
+    }
+
+    #[test]
+    fn test_default_backend_unchanged() {
+        // Seeds saved before backends were selectable must still reproduce
+        // the same games.
+        #[cfg(feature = "pcg")]
+        let mut expected = crate::Pcg64Mcg::seeded(1);
+        #[cfg(all(not(feature = "pcg"), feature = "xorshift"))]
+        let mut expected = crate::XorShiftRng::seeded(1);
+        #[cfg(all(not(feature = "pcg"), not(feature = "xorshift")))]
+        let mut expected = crate::ChaCha12Rng::seeded(1);
+        let mut rng = RandomNumberGenerator::seeded(1);
+        for _ in 0..100 {
+            assert_eq!(rng.next::<u64>(), expected.next_u64());
+        }
+    }
+
+    #[test]
+    fn test_backends() {
+        for backend in BackendKind::ALL {
+            let mut rng = (
+                RandomNumberGenerator::with_backend(*backend, 1),
+                RandomNumberGenerator::with_backend(*backend, 1),
+            );
+            for _ in 0..100 {
+                assert_eq!(rng.0.range(0..1000), rng.1.range(0..1000));
+            }
+            // Derived generators keep the backend
+            assert_eq!(rng.0.stream("walls").backend(), *backend);
+            assert_eq!(rng.0.fork().backend(), *backend);
+            assert_eq!(rng.0.task(1).backend(), *backend);
+        }
+    }
+
+    #[test]
+    fn test_static_backend() {
+        let mut rng = (
+            RandomNumberGenerator::<crate::SplitMix64>::from_seed(1),
+            RandomNumberGenerator::with_backend(BackendKind::SplitMix, 1),
+        );
+        for _ in 0..100 {
+            assert_eq!(rng.0.next::<u64>(), rng.1.next::<u64>());
+        }
+        let snapshot = rng.0.snapshot();
+        let mut restored = RandomNumberGenerator::from_snapshot(&snapshot);
+        assert_eq!(rng.0.next::<u64>(), restored.next::<u64>());
+    }
+
+    #[test]
+    fn test_fair_randomness_reproducibility() {
+        let mut rng = (
+            RandomNumberGenerator::seeded(1),
+            RandomNumberGenerator::seeded(1),
+        );
+        let mut bags = (ShuffleBag::new(0..7), ShuffleBag::new(0..7));
+        let mut chances = (ChanceAccumulator::new(0.25), ChanceAccumulator::new(0.25));
+        (0..1000).for_each(|_| {
+            assert_eq!(rng.0.draw(&mut bags.0), rng.1.draw(&mut bags.1));
+            assert_eq!(rng.0.chance(&mut chances.0), rng.1.chance(&mut chances.1));
+        });
+    }
+
+    #[test]
+    fn test_dice_reproducibility() {
+        let mut rng = (
+            RandomNumberGenerator::seeded(1),
+            RandomNumberGenerator::seeded(1),
+        );
+        let expr: DiceExpr = "4d6!dl1+2".parse().unwrap();
+        (0..1000).for_each(|_| assert_eq!(rng.0.roll(&expr), rng.1.roll(&expr)));
+    }
+
+    #[test]
+    fn test_table_reproducibility() {
+        let mut rng = (
+            RandomNumberGenerator::seeded(1),
+            RandomNumberGenerator::seeded(1),
+        );
+        let table = WeightedTable::from_weights((0..20).map(|n| (n, n as f64 + 1.0)));
+        (0..1000).for_each(|_| assert_eq!(rng.0.roll_table(&table), rng.1.roll_table(&table)));
+    }
+
+    #[test]
+    fn test_tracing() {
+        let mut rng = RandomNumberGenerator::seeded(1);
+        let mut untraced = RandomNumberGenerator::seeded(1);
+        assert!(rng.trace().is_none());
+        rng.enable_tracing(10);
+        let n = rng.range(1..=6);
+        let point = rng.point_in_rect(&Rect2D::new(Vec2::ZERO, Vec2::ONE));
+        // Tracing doesn't change the results
+        assert_eq!(n, untraced.range(1..=6));
+        assert_eq!(
+            point,
+            untraced.point_in_rect(&Rect2D::new(Vec2::ZERO, Vec2::ONE))
+        );
+
+        let trace = rng.disable_tracing().unwrap();
+        let entries: Vec<&crate::TraceEntry> = trace.entries().collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].method, "range");
+        assert_eq!(entries[0].request, "i32");
+        assert!(entries[0].result.starts_with("state "));
+        assert!(entries[0].location.contains("random.rs"));
+        // The ranges drawn inside point_in_rect aren't recorded separately
+        assert_eq!(entries[1].method, "point_in_rect");
+
+        let _ = rng.next::<u32>();
+        assert!(rng.trace().is_none());
+
+        // Results that can't be printed are traced too.
+        struct Coin;
+        impl Distribution<Coin> for rand::distr::StandardUniform {
+            fn sample<G: Rng + ?Sized>(&self, rng: &mut G) -> Coin {
+                rng.next_u32();
+                Coin
+            }
+        }
+        rng.enable_tracing(10);
+        let _: Coin = rng.next();
+        assert_eq!(rng.trace().unwrap().entries().count(), 1);
+    }
+
+    #[test]
+    fn test_rng_core() {
+        // Drawing through `RngCore` continues the same, traced sequence
+        let mut rng = (
+            RandomNumberGenerator::seeded(1),
+            RandomNumberGenerator::seeded(1),
+        );
+        assert_eq!(rng.0.next_u64(), rng.1.next::<u64>());
+        rng.0.enable_tracing(10);
+        rng.0.next_u32();
+        assert_eq!(rng.0.trace().unwrap().entries().count(), 1);
+    }
+
+    #[test]
+    fn test_dice_bounds() {
+        let mut rng = RandomNumberGenerator::new();
+        for _ in 0..1000 {
+            let roll = rng.roll_dice("3d6+2").unwrap();
+            assert!(roll.total >= 5 && roll.total <= 20);
+            assert!(roll.dice.iter().all(|die| (1..=6).contains(&die.value)));
+            assert!(rng.roll_dice("d%").unwrap().total <= 100);
+        }
+    }
 }
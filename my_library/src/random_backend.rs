@@ -0,0 +1,400 @@
+use rand::{RngCore, SeedableRng, rand_core::impls};
+use std::{fmt, str::FromStr};
+
+pub use rand::rngs::StdRng;
+pub use rand_chacha::{ChaCha8Rng, ChaCha12Rng, ChaCha20Rng};
+#[cfg(feature = "pcg")]
+pub use rand_pcg::Pcg64Mcg;
+#[cfg(feature = "xorshift")]
+pub use rand_xorshift::XorShiftRng;
+
+/// The backend [`RandomNumberGenerator`](crate::RandomNumberGenerator)
+/// uses unless another is named: PCG, or XorShift if only the `xorshift`
+/// feature is enabled. With neither, it is ChaCha12, the algorithm behind
+/// `StdRng`.
+#[cfg(feature = "pcg")]
+pub type DefaultRng = Pcg64Mcg;
+#[cfg(all(not(feature = "pcg"), feature = "xorshift"))]
+pub type DefaultRng = XorShiftRng;
+#[cfg(all(not(feature = "pcg"), not(feature = "xorshift")))]
+pub type DefaultRng = ChaCha12Rng;
+
+/// A random number generation algorithm that can power a
+/// [`RandomNumberGenerator`](crate::RandomNumberGenerator).
+///
+/// Implementations are provided for the algorithms listed in
+/// [`BackendKind`], and for [`DynamicRng`], which picks one at runtime.
+pub trait RngBackend: RngCore + Clone + Send + Sync + 'static {
+    /// Creates a generator from a 64-bit seed.
+    fn seeded(seed: u64) -> Self;
+
+    /// Creates a generator using the same algorithm as this one, with a
+    /// different seed.
+    fn reseeded(&self, seed: u64) -> Self {
+        Self::seeded(seed)
+    }
+
+    /// The algorithm's name, for log messages.
+    fn name(&self) -> &'static str {
+        let name = std::any::type_name::<Self>();
+        name.rsplit("::").next().unwrap_or(name)
+    }
+}
+
+impl RngBackend for StdRng {
+    fn seeded(seed: u64) -> Self {
+        StdRng::seed_from_u64(seed)
+    }
+}
+
+impl RngBackend for ChaCha8Rng {
+    fn seeded(seed: u64) -> Self {
+        ChaCha8Rng::seed_from_u64(seed)
+    }
+}
+
+impl RngBackend for ChaCha12Rng {
+    fn seeded(seed: u64) -> Self {
+        ChaCha12Rng::seed_from_u64(seed)
+    }
+}
+
+impl RngBackend for ChaCha20Rng {
+    fn seeded(seed: u64) -> Self {
+        ChaCha20Rng::seed_from_u64(seed)
+    }
+}
+
+#[cfg(feature = "pcg")]
+impl RngBackend for Pcg64Mcg {
+    fn seeded(seed: u64) -> Self {
+        Pcg64Mcg::seed_from_u64(seed)
+    }
+}
+
+#[cfg(feature = "xorshift")]
+impl RngBackend for XorShiftRng {
+    fn seeded(seed: u64) -> Self {
+        XorShiftRng::seed_from_u64(seed)
+    }
+}
+
+/// SplitMix64: a tiny, very fast generator with 64 bits of state. It is
+/// good enough for cosmetic effects, but its short period makes it a poor
+/// choice for anything that draws billions of numbers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl RngCore for SplitMix64 {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dst)
+    }
+}
+
+impl SeedableRng for SplitMix64 {
+    type Seed = [u8; 8];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        Self {
+            state: u64::from_le_bytes(seed),
+        }
+    }
+
+    fn seed_from_u64(state: u64) -> Self {
+        Self { state }
+    }
+}
+
+impl RngBackend for SplitMix64 {
+    fn seeded(seed: u64) -> Self {
+        SplitMix64::seed_from_u64(seed)
+    }
+}
+
+/// The random number generation algorithms that can be chosen at runtime,
+/// with [`RandomNumberGenerator::with_backend`](crate::RandomNumberGenerator::with_backend)
+/// or [`RandomPlugin::with_backend`](crate::RandomPlugin::with_backend).
+///
+/// The default is PCG (or XorShift, if only the `xorshift` feature is
+/// enabled; or `Std` if neither is).
+///
+/// With the `serde` feature, a `BackendKind` is saved as its
+/// [name](BackendKind::name). Loading the name of a backend this build
+/// doesn't include is an error, rather than a different backend.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BackendKind {
+    /// PCG (`Pcg64Mcg`): fast, small and statistically strong.
+    #[cfg(feature = "pcg")]
+    Pcg,
+    /// XorShift: very fast, but with known statistical weaknesses.
+    #[cfg(feature = "xorshift")]
+    XorShift,
+    /// The algorithm behind `rand`'s `StdRng` (currently ChaCha12).
+    Std,
+    /// ChaCha8: a cryptographically-derived generator, slower but very
+    /// hard to predict.
+    ChaCha,
+    /// [`SplitMix64`]: the fastest option, with a short period.
+    SplitMix,
+}
+
+impl BackendKind {
+    /// Every backend enabled in this build.
+    pub const ALL: &[BackendKind] = &[
+        #[cfg(feature = "pcg")]
+        BackendKind::Pcg,
+        #[cfg(feature = "xorshift")]
+        BackendKind::XorShift,
+        BackendKind::Std,
+        BackendKind::ChaCha,
+        BackendKind::SplitMix,
+    ];
+
+    /// The name used by [`Display`](fmt::Display) and [`FromStr`].
+    pub fn name(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "pcg")]
+            BackendKind::Pcg => "pcg",
+            #[cfg(feature = "xorshift")]
+            BackendKind::XorShift => "xorshift",
+            BackendKind::Std => "std",
+            BackendKind::ChaCha => "chacha",
+            BackendKind::SplitMix => "splitmix",
+        }
+    }
+}
+
+impl Default for BackendKind {
+    fn default() -> Self {
+        #[cfg(feature = "pcg")]
+        return BackendKind::Pcg;
+        #[cfg(all(not(feature = "pcg"), feature = "xorshift"))]
+        return BackendKind::XorShift;
+        #[cfg(all(not(feature = "pcg"), not(feature = "xorshift")))]
+        return BackendKind::Std;
+    }
+}
+
+impl fmt::Display for BackendKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for BackendKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        BackendKind::ALL
+            .iter()
+            .find(|kind| kind.name().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<_> = BackendKind::ALL.iter().map(|kind| kind.name()).collect();
+                anyhow::Error::msg(format!(
+                    "Unknown random backend {s}, expected one of: {}",
+                    names.join(", ")
+                ))
+            })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for BackendKind {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for BackendKind {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// A generator whose algorithm is chosen at runtime, as made by
+/// [`RandomNumberGenerator::with_backend`](crate::RandomNumberGenerator::with_backend)
+/// and [`RandomPlugin::with_backend`](crate::RandomPlugin::with_backend).
+/// Every draw checks which algorithm to run, so prefer naming a backend
+/// type (or using the [`DefaultRng`]) when the choice is fixed.
+///
+/// The `Std` backend is stored as `ChaCha12Rng`, which is the same
+/// algorithm (and produces the same numbers) as `StdRng`, but exposes its
+/// state for snapshots.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DynamicRng {
+    #[cfg(feature = "pcg")]
+    Pcg(Pcg64Mcg),
+    #[cfg(feature = "xorshift")]
+    XorShift(XorShiftRng),
+    Std(ChaCha12Rng),
+    ChaCha(ChaCha8Rng),
+    SplitMix(SplitMix64),
+}
+
+impl DynamicRng {
+    /// Creates a generator using the specified algorithm.
+    pub fn new(kind: BackendKind, seed: u64) -> Self {
+        match kind {
+            #[cfg(feature = "pcg")]
+            BackendKind::Pcg => DynamicRng::Pcg(RngBackend::seeded(seed)),
+            #[cfg(feature = "xorshift")]
+            BackendKind::XorShift => DynamicRng::XorShift(RngBackend::seeded(seed)),
+            BackendKind::Std => DynamicRng::Std(RngBackend::seeded(seed)),
+            BackendKind::ChaCha => DynamicRng::ChaCha(RngBackend::seeded(seed)),
+            BackendKind::SplitMix => DynamicRng::SplitMix(RngBackend::seeded(seed)),
+        }
+    }
+
+    /// The algorithm this generator uses.
+    pub fn kind(&self) -> BackendKind {
+        match self {
+            #[cfg(feature = "pcg")]
+            DynamicRng::Pcg(_) => BackendKind::Pcg,
+            #[cfg(feature = "xorshift")]
+            DynamicRng::XorShift(_) => BackendKind::XorShift,
+            DynamicRng::Std(_) => BackendKind::Std,
+            DynamicRng::ChaCha(_) => BackendKind::ChaCha,
+            DynamicRng::SplitMix(_) => BackendKind::SplitMix,
+        }
+    }
+}
+
+impl RngCore for DynamicRng {
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            #[cfg(feature = "pcg")]
+            DynamicRng::Pcg(rng) => rng.next_u32(),
+            #[cfg(feature = "xorshift")]
+            DynamicRng::XorShift(rng) => rng.next_u32(),
+            DynamicRng::Std(rng) => rng.next_u32(),
+            DynamicRng::ChaCha(rng) => rng.next_u32(),
+            DynamicRng::SplitMix(rng) => rng.next_u32(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            #[cfg(feature = "pcg")]
+            DynamicRng::Pcg(rng) => rng.next_u64(),
+            #[cfg(feature = "xorshift")]
+            DynamicRng::XorShift(rng) => rng.next_u64(),
+            DynamicRng::Std(rng) => rng.next_u64(),
+            DynamicRng::ChaCha(rng) => rng.next_u64(),
+            DynamicRng::SplitMix(rng) => rng.next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        match self {
+            #[cfg(feature = "pcg")]
+            DynamicRng::Pcg(rng) => rng.fill_bytes(dst),
+            #[cfg(feature = "xorshift")]
+            DynamicRng::XorShift(rng) => rng.fill_bytes(dst),
+            DynamicRng::Std(rng) => rng.fill_bytes(dst),
+            DynamicRng::ChaCha(rng) => rng.fill_bytes(dst),
+            DynamicRng::SplitMix(rng) => rng.fill_bytes(dst),
+        }
+    }
+}
+
+impl RngBackend for DynamicRng {
+    /// Creates a generator using the default algorithm.
+    fn seeded(seed: u64) -> Self {
+        DynamicRng::new(BackendKind::default(), seed)
+    }
+
+    fn reseeded(&self, seed: u64) -> Self {
+        DynamicRng::new(self.kind(), seed)
+    }
+
+    fn name(&self) -> &'static str {
+        self.kind().name()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_split_mix() {
+        // Reference values from the original SplitMix64 implementation
+        let mut rng = SplitMix64::seeded(1234567);
+        assert_eq!(rng.next_u64(), 6457827717110365317);
+        assert_eq!(rng.next_u64(), 3203168211198807973);
+    }
+
+    #[test]
+    fn test_std_matches_std_rng() {
+        let mut dynamic = DynamicRng::new(BackendKind::Std, 1);
+        let mut std = StdRng::seeded(1);
+        for _ in 0..100 {
+            assert_eq!(dynamic.next_u64(), std.next_u64());
+        }
+    }
+
+    #[test]
+    fn test_backends_differ() {
+        let firsts: Vec<u64> = BackendKind::ALL
+            .iter()
+            .map(|kind| DynamicRng::new(*kind, 1).next_u64())
+            .collect();
+        for (i, first) in firsts.iter().enumerate() {
+            assert!(!firsts[i + 1..].contains(first));
+        }
+    }
+
+    #[test]
+    fn test_reseeded_keeps_kind() {
+        for kind in BackendKind::ALL {
+            let rng = DynamicRng::new(*kind, 1);
+            assert_eq!(rng.reseeded(2).kind(), *kind);
+            assert_eq!(rng.reseeded(2), DynamicRng::new(*kind, 2));
+        }
+    }
+
+    #[test]
+    fn test_parse_kind() {
+        for kind in BackendKind::ALL {
+            assert_eq!(kind.to_string().parse::<BackendKind>().unwrap(), *kind);
+        }
+        assert_eq!(
+            " ChaCha ".parse::<BackendKind>().unwrap(),
+            BackendKind::ChaCha
+        );
+        assert!("mersenne".parse::<BackendKind>().is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_kind() {
+        for kind in BackendKind::ALL {
+            let saved = ron::to_string(kind).unwrap();
+            assert_eq!(saved, format!("\"{kind}\""));
+            assert_eq!(ron::from_str::<BackendKind>(&saved).unwrap(), *kind);
+        }
+        let error = ron::from_str::<BackendKind>("\"mersenne\"").unwrap_err();
+        assert!(error.to_string().contains("Unknown random backend"));
+    }
+}
@@ -0,0 +1,307 @@
+use crate::{BackendKind, DefaultRng, DynamicRng, RandomNumberGenerator, RngBackend};
+use bevy::{log, prelude::*};
+use std::{any::Any, marker::PhantomData};
+
+/// The environment variable [`RandomPlugin::new`] reads a seed from.
+pub const SEED_ENV_VAR: &str = "RANDOM_SEED";
+
+/// The command-line argument [`RandomPlugin::new`] reads a seed from.
+pub const SEED_ARG: &str = "--seed";
+
+/// `RandomPlugin` is a Bevy plugin that inserts a `RandomNumberGenerator`
+/// resource into your application.
+///
+/// Once you add the plugin (with `App::new.add_plugin(RandomPlugin::new())`),
+/// you can access a random number generator in systems with
+/// `rng: Res<RandomNumberGenerator>`.
+///
+/// The seed is chosen from (in order of preference):
+///
+/// 1. A fixed seed, set with [`RandomPlugin::seeded`].
+/// 2. A command-line argument, `--seed 1234` or `--seed=1234` by default.
+/// 3. An environment variable, `RANDOM_SEED` by default.
+/// 4. A random seed from the operating system.
+///
+/// Seeds may be given in decimal or hexadecimal (`0x...`). The chosen seed
+/// is logged at startup, so that a run can be reproduced by passing the
+/// same seed again:
+///
+/// ```text
+/// RANDOM_SEED=12345 cargo run -p flappy_dragon_base
+/// cargo run -p bouncy -- --seed 12345
+/// ```
+///
+/// The generator uses the default backend (PCG) unless another is chosen
+/// by type (`RandomPlugin::<ChaCha8Rng>::default()`), or at runtime with
+/// [`RandomPlugin::with_backend`].
+pub struct RandomPlugin<R: RngBackend = DefaultRng> {
+    seed: Option<u64>,
+    env_var: Option<String>,
+    arg: Option<String>,
+    /// The algorithm chosen at runtime, if any.
+    backend: Option<BackendKind>,
+    rng: PhantomData<R>,
+    #[cfg(feature = "locking")]
+    thread_generators: bool,
+}
+
+impl RandomPlugin {
+    /// Creates a plugin that reads a seed from the `--seed` argument or the
+    /// `RANDOM_SEED` environment variable, falling back to a random seed.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a plugin that always uses the specified seed, ignoring the
+    /// command line and environment.
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            seed: Some(seed),
+            env_var: None,
+            arg: None,
+            ..Self::default()
+        }
+    }
+}
+
+impl<R: RngBackend> RandomPlugin<R> {
+    /// Reads the seed from a different environment variable.
+    pub fn with_env_var<S: ToString>(mut self, name: S) -> Self {
+        self.env_var = Some(name.to_string());
+        self
+    }
+
+    /// Reads the seed from a different command-line argument.
+    pub fn with_arg<S: ToString>(mut self, name: S) -> Self {
+        self.arg = Some(name.to_string());
+        self
+    }
+
+    /// Uses a different random number generation algorithm. The choice
+    /// can be made at runtime, e.g. from a settings file.
+    ///
+    /// The plugin then inserts a `RandomNumberGenerator<DynamicRng>`, so
+    /// systems use `Res<RandomNumberGenerator<DynamicRng>>` or
+    /// `SystemRng<DynamicRng>`, and the plugins that draw from it need the
+    /// same backend: `EntityRngPlugin::<DynamicRng>::default()` and
+    /// `WfcPlugin::<Tile, DynamicRng>::default()`.
+    ///
+    /// ```
+    /// use my_library::*;
+    /// let backend = "chacha".parse().unwrap_or_default();
+    /// let plugin = RandomPlugin::new().with_backend(backend);
+    /// ```
+    pub fn with_backend(self, backend: BackendKind) -> RandomPlugin<DynamicRng> {
+        RandomPlugin {
+            seed: self.seed,
+            env_var: self.env_var,
+            arg: self.arg,
+            backend: Some(backend),
+            rng: PhantomData,
+            #[cfg(feature = "locking")]
+            thread_generators: self.thread_generators,
+        }
+    }
+
+    /// Gives each thread its own generator, so that systems taking
+    /// `Res<RandomNumberGenerator>` don't wait for each other. Seeded runs
+    /// no longer reproduce exactly; see
+    /// [`RandomNumberGenerator::with_thread_generators`].
+    #[cfg(feature = "locking")]
+    pub fn with_thread_generators(mut self) -> Self {
+        self.thread_generators = true;
+        self
+    }
+
+    fn choose_seed(&self) -> (u64, String) {
+        if let Some(seed) = self.seed {
+            return (seed, "fixed".to_string());
+        }
+        if let Some(arg) = &self.arg
+            && let Some(seed) = seed_from_args(std::env::args(), arg)
+        {
+            return (seed, format!("argument {arg}"));
+        }
+        if let Some(env_var) = &self.env_var
+            && let Ok(value) = std::env::var(env_var)
+        {
+            if let Some(seed) = parse_seed(&value) {
+                return (seed, format!("environment variable {env_var}"));
+            }
+            log::warn!("Ignoring invalid seed in {env_var}: {value}");
+        }
+        (rand::random(), "random".to_string())
+    }
+}
+
+impl<R: RngBackend> Default for RandomPlugin<R> {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            env_var: Some(SEED_ENV_VAR.to_string()),
+            arg: Some(SEED_ARG.to_string()),
+            backend: None,
+            rng: PhantomData,
+            #[cfg(feature = "locking")]
+            thread_generators: false,
+        }
+    }
+}
+
+impl<R: RngBackend> Plugin for RandomPlugin<R> {
+    fn build(&self, app: &mut App) {
+        let (seed, source) = self.choose_seed();
+        let mut backend = R::seeded(seed);
+        // Only `with_backend` chooses an algorithm at runtime, and it
+        // always returns a `RandomPlugin<DynamicRng>`.
+        if let Some(kind) = self.backend
+            && let Some(dynamic) = (&mut backend as &mut dyn Any).downcast_mut::<DynamicRng>()
+        {
+            *dynamic = DynamicRng::new(kind, seed);
+        }
+        log::info!(
+            "Random seed: {seed} ({source}), backend: {}",
+            backend.name()
+        );
+        let rng = RandomNumberGenerator::from_state(seed, backend);
+        #[cfg(feature = "locking")]
+        let rng = match self.thread_generators {
+            true => rng.with_thread_generators(),
+            false => rng,
+        };
+        app.insert_resource(rng);
+    }
+}
+
+fn parse_seed(value: &str) -> Option<u64> {
+    let value = value.trim();
+    match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+fn seed_from_args(args: impl Iterator<Item = String>, arg: &str) -> Option<u64> {
+    let mut args = args.skip(1);
+    while let Some(current) = args.next() {
+        let value = if current == arg {
+            args.next()
+        } else {
+            current
+                .strip_prefix(arg)
+                .and_then(|rest| rest.strip_prefix('='))
+                .map(|value| value.to_string())
+        };
+        if let Some(value) = value {
+            let seed = parse_seed(&value);
+            if seed.is_none() {
+                log::warn!("Ignoring invalid seed for {arg}: {value}");
+            }
+            return seed;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        ChaCha8Rng, EntityRng, EntityRngPlugin, LoadedAssets, RngKey, SystemRng, Tile, WfcModel,
+        WfcPlugin, WfcSolver, WfcTask,
+    };
+
+    fn args(args: &[&str]) -> impl Iterator<Item = String> {
+        std::iter::once("game")
+            .chain(args.iter().copied())
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    #[test]
+    fn test_parse_seed() {
+        assert_eq!(parse_seed("1234"), Some(1234));
+        assert_eq!(parse_seed(" 0xff "), Some(255));
+        assert_eq!(parse_seed("banana"), None);
+    }
+
+    #[test]
+    fn test_seed_from_args() {
+        assert_eq!(seed_from_args(args(&["--seed", "42"]), "--seed"), Some(42));
+        assert_eq!(
+            seed_from_args(args(&["-v", "--seed=42"]), "--seed"),
+            Some(42)
+        );
+        assert_eq!(seed_from_args(args(&["--seeds=42"]), "--seed"), None);
+        assert_eq!(seed_from_args(args(&["--seed"]), "--seed"), None);
+        assert_eq!(seed_from_args(args(&[]), "--seed"), None);
+    }
+
+    #[test]
+    fn test_fixed_seed() {
+        let mut app = App::new();
+        app.add_plugins(RandomPlugin::seeded(42));
+        let rng = app.world().resource::<RandomNumberGenerator>();
+        assert_eq!(rng.seed(), 42);
+        assert_eq!(rng.snapshot(), RandomNumberGenerator::seeded(42).snapshot());
+    }
+
+    #[test]
+    fn test_backend() {
+        let mut app = App::new();
+        app.add_plugins(RandomPlugin::seeded(42).with_backend(BackendKind::SplitMix));
+        let rng = app.world().resource::<RandomNumberGenerator<DynamicRng>>();
+        assert_eq!(rng.seed(), 42);
+        assert_eq!(rng.backend(), BackendKind::SplitMix);
+
+        let mut app = App::new();
+        app.add_plugins(RandomPlugin::<ChaCha8Rng>::default());
+        assert!(
+            app.world()
+                .get_resource::<RandomNumberGenerator<ChaCha8Rng>>()
+                .is_some()
+        );
+    }
+
+    #[derive(Resource, Default)]
+    struct Drawn(Vec<u32>);
+
+    fn draw(mut rng: SystemRng<DynamicRng>, mut drawn: ResMut<Drawn>) {
+        drawn.0.push(rng.get().range(0..u32::MAX));
+    }
+
+    #[test]
+    fn test_backend_plugins() {
+        let model = WfcModel::new()
+            .with_tile(Tile::Wall, 1.0)
+            .with_tile(Tile::Floor, 1.0)
+            .with_neighbours(Tile::Wall, Tile::Floor)
+            .with_neighbours(Tile::Floor, Tile::Floor);
+        let mut app = App::new();
+        app.add_plugins((
+            RandomPlugin::seeded(42).with_backend(BackendKind::SplitMix),
+            EntityRngPlugin::<DynamicRng>::default(),
+            WfcPlugin::<Tile, DynamicRng>::default(),
+        ))
+        .init_resource::<Assets<Image>>()
+        .init_resource::<LoadedAssets>()
+        .init_resource::<Drawn>()
+        .add_systems(Update, draw);
+        let entity = app.world_mut().spawn(RngKey(1)).id();
+        let task = WfcTask::new("Level", WfcSolver::new(&model, 8, 8)).with_steps_per_frame(100);
+        let task = app.world_mut().spawn(task).id();
+        for _ in 0..5 {
+            app.update();
+        }
+
+        assert_eq!(app.world().resource::<Drawn>().0.len(), 5);
+        let world = app.world_mut();
+        assert!(world.get::<EntityRng<DynamicRng>>(entity).is_some());
+        let task = world.get::<WfcTask<Tile>>(task).unwrap();
+        assert!(task.result().is_some());
+    }
+}
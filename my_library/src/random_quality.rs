@@ -0,0 +1,304 @@
+//! Statistical tests of random number quality, used by the `rng_quality`
+//! report binary and the library's own tests.
+use crate::{BackendKind, RandomNumberGenerator};
+use std::fmt;
+
+/// The outcome of one statistical test.
+///
+/// The p-value is the probability that a perfect generator would produce
+/// a result at least this far from the expected one. Very small p-values
+/// suggest the generator is biased; but a perfect generator will still
+/// "fail" a test at significance 0.01 about 1% of the time.
+#[derive(Clone, Debug)]
+pub struct QualityCheck {
+    pub backend: BackendKind,
+    pub method: &'static str,
+    pub test: &'static str,
+    pub statistic: f64,
+    pub p_value: f64,
+}
+
+impl QualityCheck {
+    /// Returns `true` if the p-value is at least `significance`.
+    pub fn passed(&self, significance: f64) -> bool {
+        self.p_value >= significance
+    }
+}
+
+impl fmt::Display for QualityCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<10} {:<20} {:<20} statistic {:>12.4}  p {:.4}",
+            self.backend, self.method, self.test, self.statistic, self.p_value
+        )
+    }
+}
+
+/// A test statistic and its p-value.
+#[derive(Clone, Copy, Debug)]
+pub struct TestResult {
+    pub statistic: f64,
+    pub p_value: f64,
+}
+
+/// Pearson's chi-square test that every bucket was equally likely.
+pub fn chi_square_uniform(counts: &[u64]) -> TestResult {
+    let total: u64 = counts.iter().sum();
+    let expected = total as f64 / counts.len() as f64;
+    let statistic: f64 = counts
+        .iter()
+        .map(|count| (*count as f64 - expected).powi(2) / expected)
+        .sum();
+    let degrees_of_freedom = (counts.len() - 1) as f64;
+    TestResult {
+        statistic,
+        p_value: gamma_q(degrees_of_freedom / 2.0, statistic / 2.0),
+    }
+}
+
+/// The Kolmogorov-Smirnov test that samples are uniform between 0 and 1.
+pub fn kolmogorov_smirnov_uniform(samples: &[f64]) -> TestResult {
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let n = sorted.len() as f64;
+    let statistic = sorted
+        .iter()
+        .enumerate()
+        .map(|(i, x)| {
+            let below = x - i as f64 / n;
+            let above = (i + 1) as f64 / n - x;
+            below.max(above)
+        })
+        .fold(0.0, f64::max);
+    // Stephens' approximation for the Kolmogorov distribution
+    let lambda = (n.sqrt() + 0.12 + 0.11 / n.sqrt()) * statistic;
+    let p_value = (1..=100)
+        .map(|j| {
+            let sign = if j % 2 == 1 { 2.0 } else { -2.0 };
+            sign * (-2.0 * (j * j) as f64 * lambda * lambda).exp()
+        })
+        .sum::<f64>()
+        .clamp(0.0, 1.0);
+    TestResult { statistic, p_value }
+}
+
+/// The Wald-Wolfowitz runs test: counts runs of samples above and below
+/// `median`. Too few runs suggest streaks; too many suggest alternation.
+pub fn runs_test(samples: &[f64], median: f64) -> TestResult {
+    let above: Vec<bool> = samples.iter().map(|x| *x >= median).collect();
+    let n1 = above.iter().filter(|a| **a).count() as f64;
+    let n2 = above.len() as f64 - n1;
+    let n = n1 + n2;
+    let runs = 1 + above.windows(2).filter(|pair| pair[0] != pair[1]).count();
+    let mean = 2.0 * n1 * n2 / n + 1.0;
+    let variance = 2.0 * n1 * n2 * (2.0 * n1 * n2 - n) / (n * n * (n - 1.0));
+    let z = (runs as f64 - mean) / variance.sqrt();
+    TestResult {
+        statistic: z,
+        p_value: two_sided_normal(z),
+    }
+}
+
+/// Tests that each sample is uncorrelated with the next (lag-1 serial
+/// correlation). The statistic is the correlation coefficient.
+pub fn serial_correlation(samples: &[f64]) -> TestResult {
+    let n = samples.len() as f64;
+    let mean = samples.iter().sum::<f64>() / n;
+    let variance: f64 = samples.iter().map(|x| (x - mean).powi(2)).sum();
+    let covariance: f64 = samples
+        .windows(2)
+        .map(|pair| (pair[0] - mean) * (pair[1] - mean))
+        .sum();
+    let correlation = covariance / variance;
+    TestResult {
+        statistic: correlation,
+        p_value: two_sided_normal(correlation * n.sqrt()),
+    }
+}
+
+/// Runs every test against every method of a generator using `backend`,
+/// drawing `samples` numbers for each method.
+pub fn check_backend(backend: BackendKind, seed: u64, samples: usize) -> Vec<QualityCheck> {
+    // (a `&mut` works with or without the `locking` feature)
+    let rng = &mut RandomNumberGenerator::with_backend(backend, seed);
+    let mut checks = Vec::new();
+    let mut add = |method: &'static str, test: &'static str, result: TestResult| {
+        checks.push(QualityCheck {
+            backend,
+            method,
+            test,
+            statistic: result.statistic,
+            p_value: result.p_value,
+        })
+    };
+
+    // Integers: every value in the range should be equally likely
+    let ints: Vec<u32> = (0..samples).map(|_| rng.range(0..10)).collect();
+    add(
+        "range(0..10)",
+        "chi-square",
+        chi_square_uniform(&histogram(&ints, 10)),
+    );
+    let pairs: Vec<u32> = ints.chunks_exact(2).map(|p| p[0] * 10 + p[1]).collect();
+    add(
+        "range(0..10)",
+        "chi-square (pairs)",
+        chi_square_uniform(&histogram(&pairs, 100)),
+    );
+    let as_floats: Vec<f64> = ints.iter().map(|i| *i as f64).collect();
+    add(
+        "range(0..10)",
+        "serial correlation",
+        serial_correlation(&as_floats),
+    );
+
+    // Floats
+    let floats: Vec<f64> = (0..samples).map(|_| rng.range(0.0..1.0)).collect();
+    check_unit_floats("range(0.0..1.0)", &floats, &mut add);
+
+    // Raw integers, scaled down to [0, 1)
+    let raw: Vec<f64> = (0..samples)
+        .map(|_| rng.next::<u32>() as f64 / (u32::MAX as f64 + 1.0))
+        .collect();
+    check_unit_floats("next::<u32>()", &raw, &mut add);
+
+    checks
+}
+
+fn check_unit_floats(
+    method: &'static str,
+    samples: &[f64],
+    add: &mut impl FnMut(&'static str, &'static str, TestResult),
+) {
+    let buckets: Vec<u32> = samples.iter().map(|x| (x * 20.0) as u32).collect();
+    add(
+        method,
+        "chi-square",
+        chi_square_uniform(&histogram(&buckets, 20)),
+    );
+    add(
+        method,
+        "kolmogorov-smirnov",
+        kolmogorov_smirnov_uniform(samples),
+    );
+    add(method, "runs", runs_test(samples, 0.5));
+    add(method, "serial correlation", serial_correlation(samples));
+}
+
+fn histogram(values: &[u32], buckets: usize) -> Vec<u64> {
+    let mut counts = vec![0; buckets];
+    values.iter().for_each(|v| counts[*v as usize] += 1);
+    counts
+}
+
+/// The probability of a standard normal variable being at least `|z|`
+/// from zero.
+fn two_sided_normal(z: f64) -> f64 {
+    // erfc(x) = Q(1/2, x^2)
+    gamma_q(0.5, z * z / 2.0)
+}
+
+/// The regularized upper incomplete gamma function, Q(a, x).
+fn gamma_q(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let scale = (-x + a * x.ln() - ln_gamma(a)).exp();
+    if x < a + 1.0 {
+        // Series expansion of P(a, x)
+        let (mut term, mut sum, mut ap) = (1.0 / a, 1.0 / a, a);
+        for _ in 0..1000 {
+            ap += 1.0;
+            term *= x / ap;
+            sum += term;
+            if term.abs() < sum.abs() * 1e-15 {
+                break;
+            }
+        }
+        (1.0 - sum * scale).clamp(0.0, 1.0)
+    } else {
+        // Continued fraction for Q(a, x), by the modified Lentz method
+        let tiny = 1e-300;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..1000 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < 1e-15 {
+                break;
+            }
+        }
+        (scale * h).clamp(0.0, 1.0)
+    }
+}
+
+/// ln(Γ(x)), using the Lanczos approximation.
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.18009172947146,
+        -86.50532032941677,
+        24.01409824083091,
+        -1.231739572450155,
+        0.1208650973866179e-2,
+        -0.5395239384953e-5,
+    ];
+    let tmp = x + 5.5 - (x + 0.5) * (x + 5.5).ln();
+    let series = COEFFICIENTS
+        .iter()
+        .enumerate()
+        .fold(1.000000000190015, |sum, (i, c)| {
+            sum + c / (x + 1.0 + i as f64)
+        });
+    -tmp + (2.5066282746310005 * series / x).ln()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_reference_values() {
+        // Γ(5) = 24
+        assert!((ln_gamma(5.0) - 24f64.ln()).abs() < 1e-9);
+        // chi-square with 2 degrees of freedom: Q = e^(-x/2)
+        assert!((gamma_q(1.0, 1.5) - (-1.5f64).exp()).abs() < 1e-9);
+        // 95% of a normal distribution is within 1.96 standard deviations
+        assert!((two_sided_normal(1.96) - 0.05).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_detects_bias() {
+        assert!(chi_square_uniform(&[100, 100, 100, 100]).p_value > 0.99);
+        assert!(chi_square_uniform(&[150, 100, 100, 50]).p_value < 0.001);
+        let ramp: Vec<f64> = (0..1000).map(|i| (i as f64 / 1000.0).powi(2)).collect();
+        assert!(kolmogorov_smirnov_uniform(&ramp).p_value < 0.001);
+        assert!(serial_correlation(&ramp).p_value < 0.001);
+        let alternating: Vec<f64> = (0..1000).map(|i| (i % 2) as f64).collect();
+        assert!(runs_test(&alternating, 0.5).p_value < 0.001);
+    }
+
+    #[test]
+    fn test_backends() {
+        // A reduced version of the `rng_quality` report. The seed is fixed,
+        // so this can't fail by chance.
+        for backend in BackendKind::ALL {
+            for check in check_backend(*backend, 1, 10_000) {
+                assert!(check.passed(0.001), "{check}");
+            }
+        }
+    }
+}
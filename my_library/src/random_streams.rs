@@ -0,0 +1,114 @@
+use crate::{DefaultRng, RandomNumberGenerator, RngBackend};
+use bevy::{
+    ecs::system::{SystemName, SystemParam},
+    prelude::*,
+};
+
+/// Derives the seed for a named stream from a master seed.
+///
+/// This uses FNV-1a (which, unlike `DefaultHasher`, is stable between
+/// Rust releases) followed by a SplitMix64 finalizer, so that similar
+/// names still produce unrelated seeds.
+pub(crate) fn stream_seed(seed: u64, name: &str) -> u64 {
+    let hash = name.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    split_mix(seed ^ split_mix(hash))
+}
+
+/// Derives the seed for a numbered task from a master seed.
+pub(crate) fn task_seed(seed: u64, key: u64) -> u64 {
+    split_mix(split_mix(seed) ^ key)
+}
+
+fn split_mix(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// `SystemRng` gives a system its own random number stream, derived from
+/// the seed of the global [`RandomNumberGenerator`] resource and the name
+/// of the system. Other systems drawing more (or fewer) random numbers
+/// can't change the sequence a system receives.
+///
+/// The stream is keyed by the system's name, so renaming or moving the
+/// system will change its sequence.
+///
+/// `SystemRng` reads the `RandomNumberGenerator` resource, so it can't be
+/// combined with `ResMut<RandomNumberGenerator>` in the same system. If the
+/// generator uses another backend, name it:
+/// `SystemRng<DynamicRng>` reads `RandomNumberGenerator<DynamicRng>`, as
+/// inserted by [`RandomPlugin::with_backend`](crate::RandomPlugin::with_backend).
+///
+/// ## Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use my_library::*;
+///
+/// fn spawn_enemy(mut rng: SystemRng) {
+///     let x = rng.get().range(-512.0..512.0);
+///     println!("Spawning at {x}");
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct SystemRng<'w, 's, R: RngBackend = DefaultRng> {
+    master: Res<'w, RandomNumberGenerator<R>>,
+    system: SystemName<'s>,
+    stream: Local<'s, Option<RandomNumberGenerator<R>>>,
+}
+
+impl<R: RngBackend> SystemRng<'_, '_, R> {
+    /// Returns this system's random number generator, creating it on
+    /// first use.
+    pub fn get(&mut self) -> &mut RandomNumberGenerator<R> {
+        let Self {
+            master,
+            system,
+            stream,
+        } = self;
+        stream.get_or_insert_with(|| master.stream(system.name()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_stream_seed_is_stable() {
+        // Changing the derivation would break every saved seed.
+        assert_ne!(stream_seed(1, "walls"), stream_seed(1, "wall"));
+        assert_ne!(stream_seed(1, "walls"), stream_seed(2, "walls"));
+        assert_eq!(stream_seed(1, "walls"), 0x3daf_dd87_cf27_5b64);
+        assert_ne!(task_seed(1, 1), task_seed(1, 2));
+        assert_ne!(task_seed(1, 1), task_seed(2, 1));
+    }
+
+    #[derive(Resource, Default)]
+    struct Results(Vec<u32>);
+
+    fn draw(mut rng: SystemRng, mut results: ResMut<Results>) {
+        let n = rng.get().range(0..u32::MAX);
+        results.0.push(n);
+    }
+
+    #[test]
+    fn test_system_rng() {
+        let mut app = App::new();
+        app.insert_resource(RandomNumberGenerator::seeded(1))
+            .init_resource::<Results>()
+            .add_systems(Update, draw);
+        app.update();
+        app.update();
+        let results = &app.world().resource::<Results>().0;
+        assert_eq!(results.len(), 2);
+        assert_ne!(results[0], results[1]);
+        assert_ne!(
+            results[0],
+            RandomNumberGenerator::seeded(1).range(0..u32::MAX)
+        );
+    }
+}
@@ -0,0 +1,446 @@
+use rand::RngCore;
+use std::{cell::Cell, collections::VecDeque, fmt, panic::Location, path::Path};
+
+/// `RngTrace` records the most recent calls made to a
+/// [`RandomNumberGenerator`](crate::RandomNumberGenerator) while tracing is
+/// enabled: which method was called, what was asked for, what it returned,
+/// and where it was called from.
+///
+/// Generic draws ([`range`](crate::RandomNumberGenerator::range), `next`
+/// and `sample`) can return types that can't be printed, so they record
+/// the value's type as the request, and the state the generator was left
+/// in as the result. A divergence still shows up at the first call that
+/// drew different numbers.
+///
+/// Calls are identified by their source location, not by the system that
+/// made them. With `#[track_caller]`, that is the line in your system
+/// that called the generator.
+///
+/// Traces are for finding desyncs. Record a trace in two runs that should
+/// behave identically (e.g. a game and its replay, or two networked peers),
+/// save both, and [`RngTrace::first_divergence`] reports the first call at
+/// which they differ---usually pointing straight at the system that drew
+/// an extra number, or drew one in a different order.
+///
+/// Only calls made directly by your code are recorded; numbers drawn
+/// inside a method (such as the two ranges behind `point_in_rect`) are
+/// part of that method's entry.
+///
+/// ## Example
+///
+/// ```
+/// use my_library::*;
+/// let run = |extra: bool| {
+///     let mut rng = RandomNumberGenerator::seeded(1);
+///     rng.enable_tracing(100);
+///     let _ = rng.range(1..=6);
+///     if extra {
+///         let _ = rng.next::<f32>();
+///     }
+///     let _ = rng.range(1..=6);
+///     rng.trace().unwrap()
+/// };
+/// let divergence = run(false).first_divergence(&run(true)).unwrap();
+/// assert_eq!(divergence.call, 1);
+/// println!("{divergence}");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct RngTrace {
+    capacity: usize,
+    calls: u64,
+    entries: VecDeque<TraceEntry>,
+}
+
+/// One call recorded in an [`RngTrace`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceEntry {
+    /// The number of calls made before this one, since tracing started.
+    pub call: u64,
+    pub method: String,
+    /// The call's arguments (such as the requested range).
+    pub request: String,
+    pub result: String,
+    /// The calling source file, line and column. This identifies the
+    /// calling system's code, but not the system itself.
+    pub location: String,
+}
+
+/// The first difference between two traces, found by
+/// [`RngTrace::first_divergence`]. An entry is `None` if that trace ended
+/// (or stopped recording) before the call.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceDivergence {
+    pub call: u64,
+    pub left: Option<TraceEntry>,
+    pub right: Option<TraceEntry>,
+}
+
+const HEADER: &str = "# rng trace, calls: ";
+
+impl RngTrace {
+    /// Creates an empty trace that keeps the last `capacity` calls.
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            calls: 0,
+            entries: VecDeque::with_capacity(capacity.clamp(1, 4096)),
+        }
+    }
+
+    /// The maximum number of calls kept. Older calls are forgotten.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of calls made since tracing started, including those
+    /// that have been forgotten.
+    pub fn calls(&self) -> u64 {
+        self.calls
+    }
+
+    /// The recorded calls, oldest first.
+    pub fn entries(&self) -> impl Iterator<Item = &TraceEntry> {
+        self.entries.iter()
+    }
+
+    pub(crate) fn record(
+        &mut self,
+        method: &str,
+        request: String,
+        result: String,
+        location: &Location,
+    ) {
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(TraceEntry {
+            call: self.calls,
+            method: method.to_string(),
+            request,
+            result,
+            location: location.to_string(),
+        });
+        self.calls += 1;
+    }
+
+    /// Finds the entry for a call, if it is still recorded.
+    pub fn get(&self, call: u64) -> Option<&TraceEntry> {
+        let first = self.entries.front()?.call;
+        self.entries
+            .get(usize::try_from(call.checked_sub(first)?).ok()?)
+    }
+
+    /// Compares two traces, returning the first call at which they differ
+    /// (or `None` if they match). Calls forgotten by either trace can't be
+    /// compared, so the comparison starts at the oldest call both traces
+    /// still hold.
+    pub fn first_divergence(&self, other: &RngTrace) -> Option<TraceDivergence> {
+        let first = |trace: &RngTrace| trace.entries.front().map_or(trace.calls, |e| e.call);
+        let start = first(self).max(first(other));
+        (start..self.calls.max(other.calls))
+            .map(|call| (call, self.get(call), other.get(call)))
+            .find(|(_, left, right)| match (left, right) {
+                (Some(left), Some(right)) => !left.same_call(right),
+                _ => true,
+            })
+            .map(|(call, left, right)| TraceDivergence {
+                call,
+                left: left.cloned(),
+                right: right.cloned(),
+            })
+    }
+
+    /// Writes the trace as text, one call per line, with tab-separated
+    /// fields. Backslashes, tabs and line breaks within fields are escaped
+    /// as `\\`, `\t`, `\n` and `\r`.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{HEADER}{}, capacity: {}\n", self.calls, self.capacity);
+        for entry in &self.entries {
+            text += &format!(
+                "{}\t{}\t{}\t{}\t{}\n",
+                entry.call,
+                escape(&entry.method),
+                escape(&entry.request),
+                escape(&entry.result),
+                escape(&entry.location)
+            );
+        }
+        text
+    }
+
+    /// Reads a trace written by [`RngTrace::to_text`]. Traces with more
+    /// entries than their capacity, or whose calls aren't numbered in
+    /// order, are rejected.
+    pub fn from_text(text: &str) -> anyhow::Result<Self> {
+        let mut lines = text.lines();
+        let header = lines
+            .next()
+            .and_then(|line| line.strip_prefix(HEADER))
+            .and_then(|line| line.split_once(", capacity: "))
+            .ok_or_else(|| anyhow::Error::msg("Not an RNG trace: missing header"))?;
+        let mut trace = RngTrace::new(header.1.trim().parse()?);
+        trace.calls = header.0.parse()?;
+        for (number, line) in lines.enumerate().filter(|(_, line)| !line.is_empty()) {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [call, method, request, result, location] = fields[..] else {
+                anyhow::bail!("Invalid RNG trace line {}: {line}", number + 2);
+            };
+            if trace.entries.len() == trace.capacity {
+                anyhow::bail!(
+                    "Invalid RNG trace: more entries than its capacity of {}",
+                    trace.capacity
+                );
+            }
+            let field = |field: &str| {
+                unescape(field).ok_or_else(|| {
+                    anyhow::anyhow!("Invalid escape on RNG trace line {}: {line}", number + 2)
+                })
+            };
+            let call: u64 = call.parse()?;
+            let in_order = trace
+                .entries
+                .back()
+                .is_none_or(|last| last.call + 1 == call);
+            if !in_order || call >= trace.calls {
+                anyhow::bail!(
+                    "Invalid RNG trace: call {call} out of order on line {}",
+                    number + 2
+                );
+            }
+            trace.entries.push_back(TraceEntry {
+                call,
+                method: field(method)?,
+                request: field(request)?,
+                result: field(result)?,
+                location: field(location)?,
+            });
+        }
+        Ok(trace)
+    }
+
+    /// Saves the trace to a text file.
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        std::fs::write(path, self.to_text())?;
+        Ok(())
+    }
+
+    /// Loads a trace saved with [`RngTrace::save`].
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        Self::from_text(&std::fs::read_to_string(path)?)
+    }
+}
+
+/// Escapes the characters that separate fields and lines in a saved trace.
+fn escape(field: &str) -> String {
+    let mut escaped = String::with_capacity(field.len());
+    for c in field.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Reverses [`escape`], returning `None` for an unknown escape.
+fn unescape(field: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        unescaped.push(match c {
+            '\\' => match chars.next()? {
+                '\\' => '\\',
+                't' => '\t',
+                'n' => '\n',
+                'r' => '\r',
+                _ => return None,
+            },
+            c => c,
+        });
+    }
+    Some(unescaped)
+}
+
+impl TraceEntry {
+    fn same_call(&self, other: &TraceEntry) -> bool {
+        self.method == other.method
+            && self.request == other.request
+            && self.result == other.result
+            && self.location == other.location
+    }
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#{} {}({}) -> {} at {}",
+            self.call, self.method, self.request, self.result, self.location
+        )
+    }
+}
+
+impl fmt::Display for TraceDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Traces diverge at call {}:", self.call)?;
+        for (side, entry) in [("left", &self.left), ("right", &self.right)] {
+            match entry {
+                Some(entry) => writeln!(f, "  {side:<5} {entry}")?,
+                None => writeln!(f, "  {side:<5} (no call)")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+thread_local! {
+    /// How many traced methods the current thread is inside.
+    static TRACE_DEPTH: Cell<u32> = const { Cell::new(0) };
+}
+
+/// Marks the current thread as inside a traced method, so that methods it
+/// calls internally aren't recorded separately.
+pub(crate) struct TraceScope {
+    outermost: bool,
+}
+
+impl TraceScope {
+    pub(crate) fn enter() -> Self {
+        let depth = TRACE_DEPTH.with(|depth| depth.replace(depth.get() + 1));
+        Self {
+            outermost: depth == 0,
+        }
+    }
+
+    /// Returns `true` if the call should be recorded.
+    pub(crate) fn outermost(&self) -> bool {
+        self.outermost
+    }
+}
+
+impl Drop for TraceScope {
+    fn drop(&mut self) {
+        TRACE_DEPTH.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+/// Describes a result that can be printed.
+pub(crate) fn describe<T: fmt::Debug>(result: &T) -> String {
+    format!("{result:?}")
+}
+
+/// Describes a draw by the state the generator was left in, for results
+/// that might not be printable.
+pub(crate) fn describe_state<R: RngCore + Clone>(rng: &R) -> String {
+    format!("state {:016x}", rng.clone().next_u64())
+}
+
+/// Describes a result whose contents can't be printed.
+pub(crate) fn describe_option<T>(result: &Option<T>) -> String {
+    match result {
+        Some(_) => "Some(..)".to_string(),
+        None => "None".to_string(),
+    }
+}
+
+fn index_of<T>(slice: &[T], pick: &T) -> Option<usize> {
+    slice.iter().position(|item| std::ptr::eq(item, pick))
+}
+
+/// Describes a picked element by its index in the slice.
+pub(crate) fn describe_pick<T>(slice: &[T], pick: Option<&T>) -> String {
+    match pick.and_then(|pick| index_of(slice, pick)) {
+        Some(index) => format!("index {index}"),
+        None => "None".to_string(),
+    }
+}
+
+/// Describes several picked elements by their indices in the slice.
+pub(crate) fn describe_picks<T>(slice: &[T], picks: &[&T]) -> String {
+    let indices: Vec<usize> = picks
+        .iter()
+        .filter_map(|pick| index_of(slice, pick))
+        .collect();
+    format!("indices {indices:?}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn trace(results: &[&str]) -> RngTrace {
+        let mut trace = RngTrace::new(100);
+        for result in results {
+            trace.record(
+                "range",
+                "0..10".to_string(),
+                result.to_string(),
+                Location::caller(),
+            );
+        }
+        trace
+    }
+
+    #[test]
+    fn test_ring_buffer() {
+        let mut trace = RngTrace::new(3);
+        for n in 0..5 {
+            trace.record("next", String::new(), n.to_string(), Location::caller());
+        }
+        assert_eq!(trace.calls(), 5);
+        let calls: Vec<u64> = trace.entries().map(|entry| entry.call).collect();
+        assert_eq!(calls, vec![2, 3, 4]);
+        assert_eq!(trace.get(3).unwrap().result, "3");
+        assert!(trace.get(1).is_none());
+        assert!(trace.get(5).is_none());
+    }
+
+    #[test]
+    fn test_divergence() {
+        assert!(
+            trace(&["1", "2"])
+                .first_divergence(&trace(&["1", "2"]))
+                .is_none()
+        );
+        let divergence = trace(&["1", "2", "3"])
+            .first_divergence(&trace(&["1", "5", "3"]))
+            .unwrap();
+        assert_eq!(divergence.call, 1);
+        assert_eq!(divergence.left.unwrap().result, "2");
+        assert_eq!(divergence.right.unwrap().result, "5");
+        // One trace stopping early is a divergence too
+        let divergence = trace(&["1", "2"]).first_divergence(&trace(&["1"])).unwrap();
+        assert_eq!(divergence.call, 1);
+        assert!(divergence.right.is_none());
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let trace = trace(&["1", "2"]);
+        let loaded = RngTrace::from_text(&trace.to_text()).unwrap();
+        assert_eq!(loaded, trace);
+        assert!(RngTrace::from_text("hello").is_err());
+        let mut full = trace.clone();
+        full.capacity = 1;
+        assert!(RngTrace::from_text(&full.to_text()).is_err());
+        // Calls must be consecutive, and fewer than the number made.
+        let text = trace.to_text();
+        assert!(RngTrace::from_text(&text.replace("\n1\t", "\n2\t")).is_err());
+        assert!(RngTrace::from_text(&text.replace("2, capacity", "1, capacity")).is_err());
+
+        let mut awkward = RngTrace::new(10);
+        awkward.record(
+            "choose",
+            "\"a\tb\"\n\\".to_string(),
+            "line\r\nbreak".to_string(),
+            Location::caller(),
+        );
+        let text = awkward.to_text();
+        assert_eq!(text.lines().count(), 2);
+        assert_eq!(RngTrace::from_text(&text).unwrap(), awkward);
+        assert!(RngTrace::from_text(&text.replace("\\t", "\\q")).is_err());
+    }
+}
@@ -0,0 +1,263 @@
+use rand::Rng;
+
+/// `ShuffleBag` draws items without replacement until the bag is empty,
+/// then refills it---like drawing Tetris pieces from a "7-bag". Every item
+/// comes up exactly once per bag, so results feel random without long
+/// streaks or droughts.
+///
+/// With the `serde` feature enabled, bags (including what is left in
+/// them) can be saved and loaded.
+///
+/// ## Example
+///
+/// ```
+/// use my_library::*;
+/// let mut rng = RandomNumberGenerator::new();
+/// let mut pieces = ShuffleBag::new(["I", "O", "T", "S", "Z", "J", "L"]);
+/// let first_bag: Vec<&str> = (0..7).map(|_| *rng.draw(&mut pieces).unwrap()).collect();
+/// assert!(pieces.items().iter().all(|piece| first_bag.contains(piece)));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        try_from = "SavedBag<T>",
+        bound(deserialize = "T: serde::Deserialize<'de>")
+    )
+)]
+pub struct ShuffleBag<T> {
+    items: Vec<T>,
+    /// Indices of the items still in the bag.
+    remaining: Vec<usize>,
+    last: Option<usize>,
+    /// For bags that avoid repeats, the index of the first item equal to
+    /// each item, so that draws can compare items without `T: PartialEq`.
+    no_repeats: Option<Vec<usize>>,
+}
+
+impl<T> ShuffleBag<T> {
+    /// Creates a full bag containing each of `items` once.
+    pub fn new(items: impl IntoIterator<Item = T>) -> Self {
+        let items: Vec<T> = items.into_iter().collect();
+        Self {
+            remaining: (0..items.len()).collect(),
+            items,
+            last: None,
+            no_repeats: None,
+        }
+    }
+
+    /// Creates a full bag containing each item the specified number of
+    /// times, e.g. `[("Gold", 3), ("Gem", 1)]`.
+    pub fn from_counts(items: impl IntoIterator<Item = (T, usize)>) -> Self
+    where
+        T: Clone,
+    {
+        Self::new(
+            items
+                .into_iter()
+                .flat_map(|(item, count)| std::iter::repeat_n(item, count)),
+        )
+    }
+
+    /// Prevents the last item of one bag being drawn again as the first
+    /// item of the next, so that an item never comes up twice in a row.
+    /// Items are compared by value, so copies added by
+    /// [`from_counts`](Self::from_counts) can still follow each other
+    /// within a bag, and a bag of equal items always repeats.
+    pub fn with_no_repeats(mut self) -> Self
+    where
+        T: PartialEq,
+    {
+        let items = &self.items;
+        self.no_repeats = Some(
+            items
+                .iter()
+                .map(|item| items.iter().position(|other| other == item).unwrap())
+                .collect(),
+        );
+        self
+    }
+
+    /// Every item in the bag, whether or not it has been drawn yet.
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    /// The number of items left before the bag refills.
+    pub fn remaining(&self) -> usize {
+        self.remaining.len()
+    }
+
+    /// Puts every item back in the bag.
+    pub fn refill(&mut self) {
+        self.remaining = (0..self.items.len()).collect();
+    }
+
+    /// Draws an item, refilling the bag first if it is empty. Returns
+    /// `None` if the bag has no items at all.
+    pub(crate) fn draw_with<R: Rng>(&mut self, rng: &mut R) -> Option<&T> {
+        if self.items.is_empty() {
+            return None;
+        }
+        let refilled = self.remaining.is_empty();
+        if refilled {
+            self.refill();
+        }
+        let mut pick = rng.random_range(0..self.remaining.len());
+        if refilled
+            && let Some(same) = &self.no_repeats
+            && let Some(last) = self.last
+            && same[self.remaining[pick]] == same[last]
+        {
+            // Choose uniformly among the items that differ instead
+            let differs = |index: &usize| same[*index] != same[last];
+            let others = self.remaining.iter().filter(|index| differs(index)).count();
+            if others > 0 {
+                let nth = rng.random_range(0..others);
+                pick = self
+                    .remaining
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, index)| differs(index))
+                    .nth(nth)
+                    .map(|(position, _)| position)
+                    .unwrap();
+            }
+        }
+        let index = self.remaining.swap_remove(pick);
+        self.last = Some(index);
+        Some(&self.items[index])
+    }
+}
+
+/// The saved form of a [`ShuffleBag`], checked before it is used.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct SavedBag<T> {
+    items: Vec<T>,
+    remaining: Vec<usize>,
+    last: Option<usize>,
+    no_repeats: Option<Vec<usize>>,
+}
+
+#[cfg(feature = "serde")]
+impl<T> TryFrom<SavedBag<T>> for ShuffleBag<T> {
+    type Error = anyhow::Error;
+
+    fn try_from(saved: SavedBag<T>) -> Result<Self, Self::Error> {
+        let len = saved.items.len();
+        if let Some(index) = saved
+            .remaining
+            .iter()
+            .chain(&saved.last)
+            .chain(saved.no_repeats.iter().flatten())
+            .find(|index| **index >= len)
+        {
+            anyhow::bail!("Item index {index} is out of range for a bag of {len} items");
+        }
+        if let Some(same) = &saved.no_repeats
+            && same.len() != len
+        {
+            anyhow::bail!("Expected {len} entries in no_repeats, found {}", same.len());
+        }
+        let mut seen = vec![false; len];
+        for index in &saved.remaining {
+            if std::mem::replace(&mut seen[*index], true) {
+                anyhow::bail!("Item index {index} is in the bag more than once");
+            }
+        }
+        Ok(Self {
+            items: saved.items,
+            remaining: saved.remaining,
+            last: saved.last,
+            no_repeats: saved.no_repeats,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn rng() -> rand::rngs::StdRng {
+        rand::rngs::StdRng::seed_from_u64(1)
+    }
+
+    #[test]
+    fn test_each_item_once_per_bag() {
+        let mut bag = ShuffleBag::new(0..7);
+        let mut rng = rng();
+        for _ in 0..100 {
+            let mut drawn: Vec<i32> = (0..7).map(|_| *bag.draw_with(&mut rng).unwrap()).collect();
+            drawn.sort();
+            assert_eq!(drawn, (0..7).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_empty() {
+        let mut bag: ShuffleBag<i32> = ShuffleBag::new([]);
+        assert!(bag.draw_with(&mut rng()).is_none());
+    }
+
+    #[test]
+    fn test_from_counts() {
+        let mut bag = ShuffleBag::from_counts([("a", 3), ("b", 1)]);
+        let mut rng = rng();
+        let drawn: Vec<&str> = (0..4).map(|_| *bag.draw_with(&mut rng).unwrap()).collect();
+        assert_eq!(drawn.iter().filter(|item| **item == "a").count(), 3);
+        assert_eq!(bag.remaining(), 0);
+    }
+
+    #[test]
+    fn test_no_repeats() {
+        let mut bag = ShuffleBag::new(0..3).with_no_repeats();
+        let mut rng = rng();
+        let drawn: Vec<i32> = (0..3000)
+            .map(|_| *bag.draw_with(&mut rng).unwrap())
+            .collect();
+        assert!(drawn.windows(2).all(|pair| pair[0] != pair[1]));
+        // A single item has to repeat
+        let mut single = ShuffleBag::new([1]).with_no_repeats();
+        assert_eq!(single.draw_with(&mut rng), Some(&1));
+        assert_eq!(single.draw_with(&mut rng), Some(&1));
+    }
+
+    #[test]
+    fn test_no_repeats_compares_values() {
+        let mut bag = ShuffleBag::from_counts([("a", 1), ("b", 1), ("c", 2)]).with_no_repeats();
+        let mut rng = rng();
+        let bags: Vec<Vec<&str>> = (0..1000)
+            .map(|_| (0..4).map(|_| *bag.draw_with(&mut rng).unwrap()).collect())
+            .collect();
+        assert!(bags.windows(2).all(|pair| pair[0][3] != pair[1][0]));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let mut bag = ShuffleBag::new(0..7).with_no_repeats();
+        let mut rng = rng();
+        bag.draw_with(&mut rng);
+        let text = ron::to_string(&bag).unwrap();
+        let loaded: ShuffleBag<i32> = ron::from_str(&text).unwrap();
+        assert_eq!(loaded, bag);
+        assert_eq!(loaded.remaining(), 6);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_rejects_bad_indices() {
+        let out_of_range = "(items: [1, 2], remaining: [0, 2], last: None, no_repeats: None)";
+        assert!(ron::from_str::<ShuffleBag<i32>>(out_of_range).is_err());
+        let duplicated = "(items: [1, 2], remaining: [1, 1], last: None, no_repeats: None)";
+        assert!(ron::from_str::<ShuffleBag<i32>>(duplicated).is_err());
+        let last = "(items: [1, 2], remaining: [], last: Some(5), no_repeats: Some([0, 1]))";
+        assert!(ron::from_str::<ShuffleBag<i32>>(last).is_err());
+        let same = "(items: [1, 2], remaining: [], last: Some(1), no_repeats: Some([0]))";
+        assert!(ron::from_str::<ShuffleBag<i32>>(same).is_err());
+    }
+}
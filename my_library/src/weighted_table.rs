@@ -0,0 +1,377 @@
+use rand::Rng;
+use std::sync::OnceLock;
+
+/// An entry in a [`WeightedTable`]: either a result, or another table
+/// to roll on.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(serialize = "T: Clone + serde::Serialize"))
+)]
+pub enum TableEntry<T> {
+    Item(T),
+    Table(WeightedTable<T>),
+}
+
+/// A weighted entry, as stored in data files.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(serialize = "T: Clone + serde::Serialize"))
+)]
+pub struct WeightedEntry<T> {
+    pub weight: f64,
+    pub entry: TableEntry<T>,
+}
+
+/// `WeightedTable` picks entries at random, with each entry's chance of
+/// being selected proportional to its weight. Entries may themselves be
+/// tables, which are rolled in turn (e.g. a "rare drop" sub-table).
+///
+/// Sampling uses Vose's alias method, so picking an entry costs two
+/// random numbers regardless of the size of the table. The method's
+/// lookup columns are built on the first roll, so adding entries one at a
+/// time stays cheap.
+///
+/// With the `serde` feature enabled, tables can be loaded from
+/// [RON](https://github.com/ron-rs/ron) data files.
+///
+/// ## Example
+///
+/// ```
+/// use my_library::*;
+/// let rare = WeightedTable::new().with("Sword", 1.0).with("Shield", 1.0);
+/// let loot = WeightedTable::new()
+///     .with("Gold", 80.0)
+///     .with("Potion", 15.0)
+///     .with_table(rare, 5.0);
+/// let mut rng = RandomNumberGenerator::new();
+/// let drop = rng.roll_table(&loot).unwrap();
+/// println!("You found: {drop}");
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        try_from = "Vec<WeightedEntry<T>>",
+        into = "Vec<WeightedEntry<T>>",
+        bound(serialize = "T: Clone + serde::Serialize"),
+        bound(deserialize = "T: serde::Deserialize<'de>")
+    )
+)]
+pub struct WeightedTable<T> {
+    entries: Vec<WeightedEntry<T>>,
+    alias: OnceLock<AliasColumns>,
+}
+
+/// The probability and alias columns of Vose's alias method.
+#[derive(Debug, Clone)]
+struct AliasColumns {
+    probability: Vec<f64>,
+    alias: Vec<usize>,
+}
+
+impl<T> WeightedTable<T> {
+    /// Creates an empty table.
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            alias: OnceLock::new(),
+        }
+    }
+
+    /// Builds a table from `(item, weight)` pairs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any weight is negative, zero or not finite.
+    pub fn from_weights(items: impl IntoIterator<Item = (T, f64)>) -> Self {
+        let entries = items
+            .into_iter()
+            .map(|(item, weight)| WeightedEntry {
+                weight,
+                entry: TableEntry::Item(item),
+            })
+            .collect();
+        Self::from_entries(entries).unwrap()
+    }
+
+    /// Builds a table from a list of weighted entries, returning an error
+    /// if a weight is invalid or a nested table is empty.
+    pub fn from_entries(entries: Vec<WeightedEntry<T>>) -> anyhow::Result<Self> {
+        for entry in entries.iter() {
+            Self::validate(entry)?;
+        }
+        Ok(Self {
+            entries,
+            alias: OnceLock::new(),
+        })
+    }
+
+    /// Adds an item to the table.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the weight is negative, zero or not finite.
+    pub fn with(self, item: T, weight: f64) -> Self {
+        self.with_entry(TableEntry::Item(item), weight)
+    }
+
+    /// Adds a nested table. If it is selected, it is rolled in turn.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the weight is invalid, or the nested table is empty.
+    pub fn with_table(self, table: WeightedTable<T>, weight: f64) -> Self {
+        self.with_entry(TableEntry::Table(table), weight)
+    }
+
+    fn with_entry(mut self, entry: TableEntry<T>, weight: f64) -> Self {
+        let entry = WeightedEntry { weight, entry };
+        Self::validate(&entry).unwrap();
+        self.entries.push(entry);
+        self.alias = OnceLock::new();
+        self
+    }
+
+    /// The number of entries (items and nested tables) in the table.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the table has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The weighted entries that make up the table.
+    pub fn entries(&self) -> &[WeightedEntry<T>] {
+        &self.entries
+    }
+
+    /// Picks an entry, descending into nested tables until an item is
+    /// found. Returns `None` if the table is empty.
+    pub(crate) fn sample_with<R: Rng>(&self, rng: &mut R) -> Option<&T> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let columns = self
+            .alias
+            .get_or_init(|| AliasColumns::build(&self.entries));
+        let column = rng.random_range(0..self.entries.len());
+        let index = if rng.random::<f64>() < columns.probability[column] {
+            column
+        } else {
+            columns.alias[column]
+        };
+        match &self.entries[index].entry {
+            TableEntry::Item(item) => Some(item),
+            TableEntry::Table(table) => table.sample_with(rng),
+        }
+    }
+
+    fn validate(entry: &WeightedEntry<T>) -> anyhow::Result<()> {
+        if !entry.weight.is_finite() || entry.weight <= 0.0 {
+            return Err(anyhow::Error::msg(format!(
+                "Weighted table entries must have a positive weight, found {}",
+                entry.weight
+            )));
+        }
+        if let TableEntry::Table(table) = &entry.entry
+            && table.is_empty()
+        {
+            return Err(anyhow::Error::msg("Nested weighted tables cannot be empty"));
+        }
+        Ok(())
+    }
+}
+
+impl AliasColumns {
+    /// Builds the columns with Vose's algorithm.
+    fn build<T>(entries: &[WeightedEntry<T>]) -> Self {
+        let n = entries.len();
+        let total: f64 = entries.iter().map(|e| e.weight).sum();
+        let mut scaled: Vec<f64> = entries
+            .iter()
+            .map(|e| e.weight * n as f64 / total)
+            .collect();
+        let mut columns = Self {
+            probability: vec![1.0; n],
+            alias: (0..n).collect(),
+        };
+
+        let (mut small, mut large): (Vec<usize>, Vec<usize>) =
+            (0..n).partition(|i| scaled[*i] < 1.0);
+        while let (Some(less), Some(more)) = (small.pop(), large.pop()) {
+            columns.probability[less] = scaled[less];
+            columns.alias[less] = more;
+            scaled[more] = (scaled[more] + scaled[less]) - 1.0;
+            if scaled[more] < 1.0 {
+                small.push(more);
+            } else {
+                large.push(more);
+            }
+        }
+        // Anything left over is within rounding error of 1.0, and keeps
+        // its default probability of 1.
+        columns
+    }
+}
+
+impl<T> Default for WeightedTable<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PartialEq> PartialEq for WeightedTable<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+impl<T> TryFrom<Vec<WeightedEntry<T>>> for WeightedTable<T> {
+    type Error = anyhow::Error;
+
+    fn try_from(entries: Vec<WeightedEntry<T>>) -> Result<Self, Self::Error> {
+        Self::from_entries(entries)
+    }
+}
+
+impl<T> From<WeightedTable<T>> for Vec<WeightedEntry<T>> {
+    fn from(table: WeightedTable<T>) -> Self {
+        table.entries
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> WeightedTable<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    /// Parses a table from a [RON](https://github.com/ron-rs/ron) string.
+    /// The table is a list of weighted entries:
+    ///
+    /// ```ron
+    /// [
+    ///     (weight: 80, entry: Item("Gold")),
+    ///     (weight: 5, entry: Table([
+    ///         (weight: 1, entry: Item("Sword")),
+    ///         (weight: 1, entry: Item("Shield")),
+    ///     ])),
+    /// ]
+    /// ```
+    pub fn from_ron(source: &str) -> anyhow::Result<Self> {
+        Ok(ron::from_str(source)?)
+    }
+
+    /// Loads a table from a RON file. See [`WeightedTable::from_ron`].
+    pub fn load<P: AsRef<std::path::Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::Error::msg(format!("Unable to read {}: {e}", path.display())))?;
+        Self::from_ron(&source)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn rng() -> rand::rngs::StdRng {
+        rand::rngs::StdRng::seed_from_u64(1)
+    }
+
+    #[test]
+    fn test_empty() {
+        let table: WeightedTable<i32> = WeightedTable::new();
+        assert!(table.sample_with(&mut rng()).is_none());
+    }
+
+    #[test]
+    fn test_distribution() {
+        let table = WeightedTable::from_weights([(0, 1.0), (1, 2.0), (2, 7.0)]);
+        let mut rng = rng();
+        let mut counts = [0i32; 3];
+        for _ in 0..100_000 {
+            counts[*table.sample_with(&mut rng).unwrap()] += 1;
+        }
+        let expected = [10_000, 20_000, 70_000];
+        for (count, expected) in counts.iter().zip(expected.iter()) {
+            assert!((count - expected).abs() < 1_000, "{counts:?}");
+        }
+    }
+
+    #[test]
+    fn test_built_one_at_a_time() {
+        let weights = (0..1000).map(|n| (n, n as f64 + 1.0));
+        let table = weights
+            .clone()
+            .fold(WeightedTable::new(), |table, (n, weight)| {
+                table.with(n, weight)
+            });
+        let expected = WeightedTable::from_weights(weights);
+        assert_eq!(table, expected);
+        let (mut first, mut second) = (rng(), rng());
+        for _ in 0..100 {
+            assert_eq!(
+                table.sample_with(&mut first),
+                expected.sample_with(&mut second)
+            );
+        }
+        // Adding an entry after rolling rebuilds the columns.
+        let table = table.with(1000, 1e9);
+        assert_eq!(table.sample_with(&mut first), Some(&1000));
+    }
+
+    #[test]
+    fn test_nested() {
+        let inner = WeightedTable::new().with("inner", 1.0);
+        let table = WeightedTable::new()
+            .with("outer", 1.0)
+            .with_table(inner, 1.0);
+        let mut rng = rng();
+        let inner_count = (0..10_000)
+            .filter(|_| *table.sample_with(&mut rng).unwrap() == "inner")
+            .count();
+        assert!((4_000..6_000).contains(&inner_count));
+    }
+
+    #[test]
+    fn test_invalid_weights() {
+        for weight in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            let entries = vec![WeightedEntry {
+                weight,
+                entry: TableEntry::Item(1),
+            }];
+            assert!(WeightedTable::from_entries(entries).is_err());
+        }
+        let empty = vec![WeightedEntry {
+            weight: 1.0,
+            entry: TableEntry::Table(WeightedTable::<i32>::new()),
+        }];
+        assert!(WeightedTable::from_entries(empty).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_ron() {
+        let table: WeightedTable<String> = WeightedTable::from_ron(
+            r#"[
+                (weight: 80, entry: Item("Gold")),
+                (weight: 5, entry: Table([
+                    (weight: 1, entry: Item("Sword")),
+                ])),
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(table.len(), 2);
+        let text = ron::to_string(&table).unwrap();
+        assert_eq!(WeightedTable::from_ron(&text).unwrap(), table);
+        assert!(WeightedTable::<String>::from_ron("[(weight: 0, entry: Item(\"x\"))]").is_err());
+    }
+}
@@ -1,4 +1,8 @@
-use bevy::{color::palettes::css::BLUE, prelude::*};
+use bevy::{
+    color::palettes::css::BLUE,
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task, block_on, futures_lite::future},
+};
 use bevy_egui::{EguiContexts, egui};
 use my_library::*;
 
@@ -19,11 +23,9 @@ struct GameAssets {
     dice_layout: Handle<TextureAtlasLayout>,
 }
 
+/// The CPU's score. The player's is the library's [`Score`].
 #[derive(Clone, Copy, Resource)]
-struct Scores {
-    player: usize,
-    cpu: usize,
-}
+struct CpuScore(usize);
 
 #[derive(Component)]
 struct HandDie;
@@ -34,8 +36,33 @@ pub struct GameElement;
 #[derive(Resource)]
 struct HandTimer(Timer);
 
+/// The CPU's die rolls, drawn from a shuffle bag so it can't roll a run of
+/// 1s and lose turn after turn.
+#[derive(Resource)]
+struct CpuDice(ShuffleBag<usize>);
+
+/// How the CPU decides whether to keep rolling.
+#[derive(Resource)]
+struct CpuPlayer {
+    game: PushYourLuck,
+    strategy: PushStrategy,
+}
+
+/// Solves the game in the background, so the window opens straight away.
+/// The CPU waits for it before taking its first turn.
 #[derive(Resource)]
-struct FinalScore(Scores);
+struct SolvingGame(Task<PushYourLuck>);
+
+impl CpuPlayer {
+    fn should_roll(&self, cpu_score: &CpuScore, score: &Score, hand_total: usize) -> bool {
+        self.game.should_roll(
+            self.strategy,
+            cpu_score.0 as u32,
+            score.get(),
+            hand_total as u32,
+        )
+    }
+}
 
 fn setup(
     asset_server: Res<AssetServer>,
@@ -51,14 +78,34 @@ fn setup(
         dice_image: texture,
         dice_layout: atlas_handle,
     });
-    commands.insert_resource(Scores { cpu: 0, player: 0 });
+    commands.insert_resource(CpuScore(0));
+    commands.insert_resource(CpuDice(ShuffleBag::new(1..=6).with_no_repeats()));
     commands.insert_resource(HandTimer(Timer::from_seconds(0.5, TimerMode::Repeating)));
 }
 
-fn display_score(scores: Res<Scores>, mut egui_context: EguiContexts) {
+fn solve_game(mut commands: Commands) {
+    let game = PushYourLuck::pig();
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        game.solve();
+        game
+    });
+    commands.insert_resource(SolvingGame(task));
+}
+
+fn finish_solving(mut commands: Commands, mut solving: ResMut<SolvingGame>) {
+    if let Some(game) = block_on(future::poll_once(&mut solving.0)) {
+        commands.remove_resource::<SolvingGame>();
+        commands.insert_resource(CpuPlayer {
+            game,
+            strategy: PushStrategy::BestOdds { risk: 0.0 },
+        });
+    }
+}
+
+fn display_score(score: Res<Score>, cpu_score: Res<CpuScore>, mut egui_context: EguiContexts) {
     egui::Window::new("Total Scores").show(egui_context.ctx_mut(), |ui| {
-        ui.label(format!("Player: {}", scores.player));
-        ui.label(format!("CPU: {}", scores.cpu));
+        ui.label(format!("Player: {}", score.get()));
+        ui.label(format!("CPU: {}", cpu_score.0));
     });
 }
 
@@ -102,7 +149,7 @@ fn player(
     mut commands: Commands<'_, '_>,
     rng: Res<RandomNumberGenerator>,
     assets: Res<GameAssets>,
-    mut scores: ResMut<Scores>,
+    mut score: ResMut<Score>,
     mut state: ResMut<NextState<GamePhase>>,
     mut egui_context: EguiContexts,
 ) {
@@ -141,7 +188,7 @@ fn player(
                     }
                 })
                 .sum();
-            scores.player += hand_total;
+            score.add(hand_total as u32);
             clear_die(&hand_query, &mut commands);
             state.set(GamePhase::Cpu);
         }
@@ -152,13 +199,20 @@ fn player(
 fn cpu(
     hand_query: Query<(Entity, &Sprite), With<HandDie>>,
     mut state: ResMut<NextState<GamePhase>>,
-    mut scores: ResMut<Scores>,
+    mut cpu_score: ResMut<CpuScore>,
+    score: Res<Score>,
     rng: Res<RandomNumberGenerator>,
+    mut dice: ResMut<CpuDice>,
+    cpu_player: Option<Res<CpuPlayer>>,
     mut commands: Commands,
     assets: Res<GameAssets>,
     mut timer: ResMut<HandTimer>,
     time: Res<Time>,
 ) {
+    let Some(cpu_player) = cpu_player else {
+        // Still solving the game
+        return;
+    };
     timer.0.tick(time.delta());
     if timer.0.just_finished() {
         let hand_total: usize = hand_query
@@ -172,8 +226,8 @@ fn cpu(
             })
             .sum();
 
-        if hand_total < 20 && scores.cpu + hand_total < 100 {
-            let new_roll = rng.range(1..7);
+        if cpu_player.should_roll(&cpu_score, &score, hand_total) {
+            let new_roll = *rng.draw(&mut dice.0).unwrap();
             if new_roll == 1 {
                 clear_die(&hand_query, &mut commands);
                 state.set(GamePhase::Player);
@@ -187,7 +241,7 @@ fn cpu(
                 );
             }
         } else {
-            scores.cpu += hand_total;
+            cpu_score.0 += hand_total;
             state.set(GamePhase::Player);
             hand_query
                 .iter()
@@ -196,29 +250,33 @@ fn cpu(
     }
 }
 
-fn check_game_over(mut state: ResMut<NextState<GamePhase>>, scores: Res<Scores>) {
-    if scores.cpu >= 100 || scores.player >= 100 {
+fn check_game_over(
+    mut state: ResMut<NextState<GamePhase>>,
+    score: Res<Score>,
+    cpu_score: Res<CpuScore>,
+) {
+    if cpu_score.0 >= 100 || score.get() >= 100 {
         state.set(GamePhase::End);
     }
 }
 
-fn end_game(mut state: ResMut<NextState<GamePhase>>, scores: Res<Scores>, mut commands: Commands) {
-    commands.insert_resource(FinalScore(*scores));
+fn end_game(
+    mut state: ResMut<NextState<GamePhase>>,
+    score: Res<Score>,
+    cpu_score: Res<CpuScore>,
+    mut commands: Commands,
+) {
+    let winner = match (score.get() as usize) < cpu_score.0 {
+        true => "CPU",
+        false => "Player",
+    };
+    commands.insert_resource(GameOverMessage(format!(
+        "CPU: {}\n{winner} is the winner!",
+        cpu_score.0
+    )));
     state.set(GamePhase::GameOver);
 }
 
-fn display_final_score(scores: Res<FinalScore>, mut egui_context: EguiContexts) {
-    egui::Window::new("Total Scores").show(egui_context.ctx_mut(), |ui| {
-        ui.label(format!("Player: {}", scores.0.player));
-        ui.label(format!("CPU: {}", scores.0.cpu));
-        if scores.0.player < scores.0.cpu {
-            ui.label("CPU is the winner!");
-        } else {
-            ui.label("Player is the winner!");
-        }
-    });
-}
-
 fn main() -> anyhow::Result<()> {
     let mut app = App::new();
 
@@ -246,28 +304,28 @@ fn main() -> anyhow::Result<()> {
         exit => [ cleanup::<GameElement> ]
     );
 
-    add_phase!(app, GamePhase, GamePhase::GameOver,
-        start => [ ],
-        run => [ display_final_score ],
-        exit => [ ]
-    );
-
-    app.add_plugins(DefaultPlugins.set(WindowPlugin {
-        primary_window: Some(Window {
-            title: "Pig".to_string(),
-            resolution: bevy::window::WindowResolution::new(1024.0, 768.0),
+    app.add_systems(Startup, solve_game)
+        .add_systems(
+            Update,
+            finish_solving.run_if(resource_exists::<SolvingGame>),
+        )
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                title: "Pig".to_string(),
+                resolution: bevy::window::WindowResolution::new(1024.0, 768.0),
+                ..default()
+            }),
             ..default()
-        }),
-        ..default()
-    }))
-    .add_plugins(GameStatePlugin::new(
-        GamePhase::MainMenu,
-        GamePhase::Start,
-        GamePhase::GameOver,
-    ))
-    .add_plugins(AssetManager::new().add_image("dice", "dice.png")?)
-    .add_plugins(RandomPlugin)
-    .run();
+        }))
+        .add_plugins(GameStatePlugin::new(
+            GamePhase::MainMenu,
+            GamePhase::Start,
+            GamePhase::GameOver,
+        ))
+        .add_plugins(AssetManager::new().add_image("dice", "dice.png")?)
+        .add_plugins(RandomPlugin::new())
+        .add_plugins(HighScorePlugin::new("pig"))
+        .run();
 
     Ok(())
 }